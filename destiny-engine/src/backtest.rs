@@ -1,5 +1,8 @@
-use crate::{history_data::*, traits::*};
-use anyhow::{anyhow, ensure, Result};
+use crate::{
+    binance::SymbolRules, error::*, history_data::*, indicator::*, kline::*, matching::*,
+    report::*, slippage::*, snapshot::*, traits::*,
+};
+use anyhow::{anyhow, bail, ensure, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Timelike, Utc};
 use derive_builder::Builder;
@@ -9,7 +12,12 @@ use parking_lot::Mutex;
 use rayon::prelude::*;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock, Weak},
+};
 use tokio::time::Instant;
 
 /// 回测配置
@@ -29,16 +37,63 @@ pub struct BacktestConfig {
     /// 挂单手续费率
     #[builder(default = dec!(0.0005))]
     pub fee_rate_maker: Decimal,
-    /// 滑点
-    #[builder(default = dec!(0.01))]
+    /// 强平手续费率
+    #[builder(default = dec!(0.0125))]
+    pub fee_rate_liquidation: Decimal,
+    /// 滑点, 默认为0即不计滑点
+    #[builder(default = Decimal::ZERO)]
     pub slippage_rate: Decimal,
+    /// 滑点模型, 作用于市价单及吃单成交
+    #[builder(default = SlippageModel::Fixed)]
+    pub slippage_model: SlippageModel,
+    /// 撮合模式
+    #[builder(default = MatchMode::Close)]
+    pub match_mode: MatchMode,
+    /// K线内价格路径假设, 仅在[`MatchMode::Ohlc`]下生效
+    #[builder(default = KlinePath::Nearest)]
+    pub kline_path: KlinePath,
+    /// 成交量参与率: 每根K线内交易对的成交数量不超过[`Kline::size`] * 参与率
+    /// <br> 未成交部分保留为部分成交订单, 在后续K线继续撮合. 为空时订单一次全部成交
+    #[builder(default = None)]
+    pub participation_rate: Option<Decimal>,
+    /// 账本导出路径, 回测结束后将各交易对的订单、成交、资金费用与强平记录以JSON格式写入
+    #[builder(default = None)]
+    pub ledger_path: Option<PathBuf>,
+    /// 回测报告权益曲线采样间隔
+    #[builder(default = Duration::hours(1))]
+    pub equity_interval: Duration,
+    /// 每个交易对每个K线周期缓存的已收盘K线数量, 见[`EngineMarket::klines`]
+    #[builder(default = 1000)]
+    pub kline_history: usize,
+    /// 交易规则快照路径, 见[`SymbolRules::load`]. 为空时使用[`SymbolRules::cache_dir`], 目录不存在或无法加载时使用[`SymbolRule::default`]
+    /// <br> 初始化交易对时使用开始时间生效的版本, 之后每日切换到当日生效的版本
+    /// <br> 使用缓存目录时, 快照中不存在的交易对使用[`SymbolRule::default`]; 指定路径时初始化该交易对失败
+    #[builder(default = None)]
+    pub rule_path: Option<PathBuf>,
+    /// 执行每分钟事件[`Strategy::on_minutely`]
+    /// <br> 关闭后定时事件只在整点调度, 时钟只在行情事件与整点推进, 跳过没有行情的分钟
+    #[builder(default = true)]
+    pub timer_minutely: bool,
+    /// 历史数据目录, 目录结构见[`HistoryDataStream`]. 为空时使用[`HistoryData::cache_dir`]
+    #[builder(default = None)]
+    pub history_path: Option<PathBuf>,
 }
 
 pub struct Backtest {
     config: Arc<BacktestConfig>,
-    account: Arc<Mutex<Account>>,
-    trade_time: Arc<Mutex<DateTime<Utc>>>,
-    strategy: Arc<dyn Strategy>,
+    pub(crate) account: Arc<Mutex<Account>>,
+    pub(crate) ledgers: Arc<Mutex<HashMap<String, SymbolLedger>>>,
+    pending_orders: Arc<Mutex<Vec<Order>>>,
+    pub(crate) kline_feed: Arc<Mutex<KlineFeed>>,
+    pub(crate) trade_time: Arc<Mutex<DateTime<Utc>>>,
+    pub(crate) strategy: Arc<dyn Strategy>,
+    /// 通知策略时传入的引擎, 为空时为回测自身. 模拟盘以外层引擎通知策略
+    engine: OnceLock<Weak<dyn Engine>>,
+    rules: Option<SymbolRules>,
+    /// 交易规则快照来自缓存目录而非[`BacktestConfig::rule_path`]
+    rules_implicit: bool,
+    /// 使用自定义交易规则的交易对
+    rules_custom: Mutex<HashSet<String>>,
 }
 
 impl Engine for Backtest {
@@ -51,52 +106,49 @@ impl Engine for Backtest {
 
 impl EngineInit for Backtest {
     fn symbol_init(&self, symbol: &str) -> Result<()> {
+        let rule = match self.rules.as_ref() {
+            Some(rules) => match rules.rule(symbol, self.time()) {
+                Some(rule) => rule,
+                None if self.rules_implicit => {
+                    tracing::warn!("交易规则快照不存在交易对, 使用默认交易规则: {}", symbol);
+                    SymbolRule::default()
+                }
+                None => bail!("交易规则快照不存在交易对: {}", symbol),
+            },
+            None => SymbolRule::default(),
+        };
+        self.symbol_insert(symbol, rule)
+    }
+
+    fn symbol_init_with_rule(&self, symbol: &str, rule: SymbolRule) -> Result<()> {
         ensure!(
-            !self.account.lock().positions.contains_key(symbol),
-            "重复初始化交易对: {}",
-            symbol
+            rule.price_tick > Decimal::ZERO && rule.size_tick > Decimal::ZERO,
+            "交易规则的价格步长与数量步长必须大于0"
         );
-        self.account.lock().positions.insert(
-            symbol.to_string(),
-            SymbolPosition {
-                symbol: Symbol {
-                    symbol: symbol.to_string(),
-                    enable: true,
-                    rule: SymbolRule {
-                        price_min: dec!(1e-8),
-                        price_max: dec!(1e8),
-                        price_tick: dec!(1e-8),
-                        size_min: dec!(1e-8),
-                        size_max: dec!(1e8),
-                        size_tick: dec!(1e-8),
-                        amount_min: dec!(1e-8),
-                        order_max: 200,
-                    },
-                    market: SymbolMarket {
-                        mark: Decimal::ZERO,
-                        index: Decimal::ZERO,
-                        last: Decimal::ZERO,
-                        settlement: Decimal::ZERO,
-                        settlement_time: Default::default(),
-                        time: Default::default(),
-                    },
-                },
-                leverage: 1,
-                long: Position {
-                    side: TradeSide::Long,
-                    price: Decimal::ZERO,
-                    size: Decimal::ZERO,
-                },
-                short: Position {
-                    side: TradeSide::Short,
-                    price: Decimal::ZERO,
-                    size: Decimal::ZERO,
-                },
-                orders: Default::default(),
-            },
+        self.symbol_insert(symbol, rule)?;
+        self.rules_custom.lock().insert(symbol.to_string());
+        Ok(())
+    }
+
+    fn kline_subscribe(&self, symbol: &str, intervals: &[KlineInterval]) -> Result<()> {
+        ensure!(
+            self.account.lock().positions.contains_key(symbol),
+            "交易对不存在: {}",
+            symbol
         );
+        self.kline_feed.lock().subscribe(symbol, intervals)
+    }
 
-        Ok(())
+    fn indicator_register(
+        &self,
+        symbol: &str,
+        interval: KlineInterval,
+        name: &str,
+        indicator: Indicator,
+    ) -> Result<()> {
+        self.kline_feed
+            .lock()
+            .indicator_register(symbol, interval, name, indicator)
     }
 }
 
@@ -375,6 +427,86 @@ impl EngineAccount for Backtest {
             .map(|position| position.margin())
             .unwrap_or_default()
     }
+    fn fundings(&self, symbol: &str) -> Vec<Funding> {
+        self.ledgers
+            .lock()
+            .get(symbol)
+            .map(|ledger| ledger.fundings.clone())
+            .unwrap_or_default()
+    }
+    fn funding_fee(&self, symbol: &str) -> Decimal {
+        self.ledgers
+            .lock()
+            .get(symbol)
+            .map(|ledger| ledger.funding_fee())
+            .unwrap_or_default()
+    }
+    fn orders_history(&self, symbol: &str, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Order> {
+        self.ledgers
+            .lock()
+            .get(symbol)
+            .map(|ledger| ledger.orders_between(begin, end))
+            .unwrap_or_default()
+    }
+    fn trades(&self, symbol: &str, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Trade> {
+        self.ledgers
+            .lock()
+            .get(symbol)
+            .map(|ledger| ledger.trades_between(begin, end))
+            .unwrap_or_default()
+    }
+    fn liquidation_price(&self, symbol: &str, side: TradeSide) -> Decimal {
+        self.account.lock().liquidation_price(symbol, side)
+    }
+    fn position_mode(&self, symbol: &str) -> PositionMode {
+        self.account
+            .lock()
+            .positions
+            .get(symbol)
+            .map(|position| position.position_mode)
+            .unwrap_or(PositionMode::Hedge)
+    }
+    fn margin_mode(&self, symbol: &str) -> MarginMode {
+        self.account
+            .lock()
+            .positions
+            .get(symbol)
+            .map(|position| position.margin_mode)
+            .unwrap_or(MarginMode::Cross)
+    }
+    fn margin_isolated(&self, symbol: &str) -> Decimal {
+        self.account
+            .lock()
+            .positions
+            .get(symbol)
+            .map(|position| position.margin_isolated())
+            .unwrap_or_default()
+    }
+    fn order_group(&self, symbol: &str, id: &str) -> Option<OrderGroup> {
+        self.account
+            .lock()
+            .positions
+            .get(symbol)
+            .and_then(|position| position.groups.get(id))
+            .cloned()
+    }
+    fn snapshot(&self) -> AccountSnapshot {
+        AccountSnapshot::new(self.time(), self.account.lock().clone())
+    }
+    fn order_groups(&self, symbol: &str) -> Vec<OrderGroup> {
+        self.account
+            .lock()
+            .positions
+            .get(symbol)
+            .map(|position| {
+                position
+                    .groups
+                    .values()
+                    .cloned()
+                    .collect::<Vec<OrderGroup>>()
+            })
+            .unwrap_or_default()
+    }
 }
 
 impl EngineMarket for Backtest {
@@ -494,805 +626,1070 @@ impl EngineMarket for Backtest {
             .map(|position| position.symbol.rule.order_max)
             .unwrap_or_default()
     }
+    fn klines(&self, symbol: &str, interval: KlineInterval, limit: usize) -> Vec<Kline> {
+        self.kline_feed.lock().klines(symbol, interval, limit)
+    }
+    fn indicator(&self, symbol: &str, interval: KlineInterval, name: &str) -> Option<Indicator> {
+        self.kline_feed.lock().indicator(symbol, interval, name)
+    }
 }
 
-#[async_trait]
-impl EngineExchange for Backtest {
-    async fn long_market_open(&self, symbol: &str, size: Decimal) -> Result<String> {
-        ensure!(
-            self.account.lock().positions.contains_key(symbol),
-            "交易对不存在: {}",
-            symbol
-        );
+/// 校验一组下单请求: 交易对相同, 客户端订单ID不重复
+pub(crate) fn order_requests_check(requests: &[&OrderRequest]) -> Result<()> {
+    let symbol = &requests[0].symbol;
+    ensure!(
+        requests.iter().all(|request| &request.symbol == symbol),
+        "订单组的交易对必须相同"
+    );
+    let client_ids = requests
+        .iter()
+        .filter_map(|request| request.client_id.as_ref())
+        .collect::<Vec<&String>>();
+    ensure!(
+        client_ids.iter().collect::<HashSet<_>>().len() == client_ids.len(),
+        "客户端订单ID重复"
+    );
+    Ok(())
+}
 
-        let price_mark = self.price_mark(symbol);
-        let size_min = self.rule_size_min(symbol);
-        let size_max = self.rule_size_max(symbol);
-        let size_tick = self.rule_size_tick(symbol);
-        let amount_min = self.rule_amount_min(symbol);
-        let leverage = Decimal::from(self.leverage(symbol));
-        let cash_available = self.cash_available();
+impl Backtest {
+    /// 订单不在订单列表中时的错误: 账本中存在则已完成, 否则不存在
+    fn order_missing(&self, symbol: &str, id: &str) -> OrderError {
+        match self
+            .ledgers
+            .lock()
+            .get(symbol)
+            .and_then(|ledger| ledger.orders.iter().rev().find(|order| order.id == id))
+        {
+            Some(order) => OrderError::Finalized {
+                id: id.to_owned(),
+                status: order.status,
+            },
+            None => OrderError::NotFound {
+                symbol: symbol.to_owned(),
+                id: id.to_owned(),
+            },
+        }
+    }
 
-        let size = size - size % size_tick;
-        ensure!(
-            size >= size_min,
-            "最小数量限制: 数量({}),限制({})",
-            size,
-            size_min,
-        );
+    /// 校验订单修改并返回修改后的订单, 校验规则与下单相同
+    /// <br> 修改价格或增加数量时重新排队, 仅减少数量时保留排队时间
+    /// <br> 重新排队的订单与新订单相同, 在下一根K线开盘即可成交时按吃单成交
+    fn order_amended(
+        &self,
+        account: &Account,
+        order: &Order,
+        price: Decimal,
+        size: Decimal,
+    ) -> Result<Order> {
+        ensure!(order.r#type.is_limit(), "市价单不支持修改: {}", order.id);
         ensure!(
-            size <= size_max,
-            "最大数量限制: 数量({}),限制({})",
+            size > order.deal_size,
+            "修改后数量必须大于已成交数量: 数量({}),已成交({})",
             size,
-            size_max,
+            order.deal_size
         );
 
-        let amount = size * price_mark;
-        ensure!(
-            amount >= amount_min,
-            "最小金额限制: 金额({}),限制({})",
-            amount,
-            amount_min,
-        );
+        // 已触发的条件单按限价单校验
+        let request = OrderRequest {
+            symbol: order.symbol.clone(),
+            r#type: if order.is_pending_trigger() {
+                order.r#type
+            } else {
+                TradeType::Limit
+            },
+            side: order.side,
+            reduce_only: order.reduce_only,
+            close_position: order.close_position,
+            price,
+            size: size - order.deal_size,
+            time_in_force: order.time_in_force,
+            stop_price: order.stop_price,
+            working_type: order.working_type,
+            callback_rate: order.callback_rate,
+            client_id: None,
+        };
+        let validated = self.order_validate(account, request, false)?;
 
-        let margin = amount / leverage;
-        ensure!(
-            cash_available >= margin,
-            "保证金不足: 保证金({}),可用({})",
-            margin,
-            cash_available
-        );
+        let price = validated.price;
+        let size = validated.size + order.deal_size;
+        let queue_time = if price != order.price || size > order.size {
+            self.time()
+        } else {
+            order.queue_time
+        };
+        Ok(Order {
+            price,
+            size,
+            queue_time,
+            ..order.clone()
+        })
+    }
 
-        let order_id = String::gen_id();
-        self.account
-            .lock()
-            .positions
-            .get_mut(symbol)
-            .unwrap()
-            .orders
-            .insert(
-                order_id.clone(),
-                Order {
-                    id: order_id.clone(),
-                    symbol: symbol.to_string(),
-                    r#type: TradeType::Market,
-                    side: TradeSide::Long,
-                    reduce_only: false,
-                    status: OrderStatus::Created,
-                    price: Decimal::ZERO,
-                    size,
-                    deal_price: Decimal::ZERO,
-                    deal_size: Decimal::ZERO,
-                    deal_fee: Decimal::ZERO,
-                    create_time: self.time(),
-                },
-            );
-        Ok(order_id)
+    /// 校验下单请求并创建订单, 不加入订单列表
+    /// <br> [`bracket`] : 是否为括号订单的止损/止盈订单, 其数量在入场订单成交后确定, 不检查持仓数量
+    fn order_new(&self, request: OrderRequest, bracket: bool) -> Result<Order> {
+        // 校验读取的账户状态在同一次加锁内取得, 不复制账户
+        self.order_validate(&self.account.lock(), request, bracket)
     }
-    async fn long_limit_open(&self, symbol: &str, size: Decimal, price: Decimal) -> Result<String> {
+
+    /// 按给定的账户状态校验下单请求并创建订单, 由调用方持有账户锁
+    fn order_validate(
+        &self,
+        account: &Account,
+        request: OrderRequest,
+        bracket: bool,
+    ) -> Result<Order> {
+        let time = self.time();
+        let symbol = request.symbol.as_str();
+        let position = account
+            .positions
+            .get(symbol)
+            .ok_or(anyhow!("交易对不存在: {}", symbol))?;
         ensure!(
-            self.account.lock().positions.contains_key(symbol),
-            "交易对不存在: {}",
-            symbol
+            !(request.r#type.is_market() && request.time_in_force == TimeInForce::Gtx),
+            "市价单不支持只做挂单"
         );
+        if let Some(client_id) = request.client_id.as_ref() {
+            ensure!(
+                !position
+                    .orders
+                    .values()
+                    .any(|order| &order.client_id == client_id),
+                "客户端订单ID重复: {}",
+                client_id
+            );
+        }
 
-        let price_min = self.rule_price_min(symbol);
-        let price_max = self.rule_price_max(symbol);
-        let price_tick = self.rule_price_tick(symbol);
-        let size_min = self.rule_size_min(symbol);
-        let size_max = self.rule_size_max(symbol);
-        let size_tick = self.rule_size_tick(symbol);
-        let amount_min = self.rule_amount_min(symbol);
-        let leverage = Decimal::from(self.leverage(symbol));
-        let cash_available = self.cash_available();
+        let reduce_only = request.reduce_only || request.close_position;
+        let conditional = request.r#type.is_conditional();
+        // 条件单不冻结持仓, 按持仓数量检查
+        let size_available = match (request.side, conditional) {
+            (TradeSide::Long, false) => position.long_size_available(),
+            (TradeSide::Short, false) => position.short_size_available(),
+            (TradeSide::Long, true) => position.long.size,
+            (TradeSide::Short, true) => position.short.size,
+        };
+        let rule = &position.symbol.rule;
+        let size_min = rule.size_min;
+        let size_max = rule.size_max;
+        let size_tick = rule.size_tick;
 
+        let size = if request.close_position && !bracket {
+            size_available
+        } else {
+            request.size
+        };
         let size = size - size % size_tick;
         ensure!(
             size >= size_min,
             "最小数量限制: 数量({}),限制({})",
             size,
-            size_min,
-        );
-        ensure!(
-            size <= size_max,
-            "最大数量限制: 数量({}),限制({})",
-            size,
-            size_max,
-        );
-
-        let price = price - price % price_tick;
-        ensure!(
-            price >= price_min,
-            "最低价格限制: 价格({}),限制({})",
-            price,
-            price_min
-        );
-        ensure!(
-            price <= price_max,
-            "最高价格限制: 价格({}),限制({})",
-            price,
-            price_max
+            size_min
         );
+        if !reduce_only {
+            ensure!(
+                size <= size_max,
+                "最大数量限制: 数量({}),限制({})",
+                size,
+                size_max,
+            );
+        }
 
-        let amount = size * price;
-        ensure!(
-            amount >= amount_min,
-            "最小金额限制: 金额({}),限制({})",
-            amount,
-            amount_min
-        );
+        let price_min = rule.price_min;
+        let price_max = rule.price_max;
+        let price_tick = rule.price_tick;
+
+        let price = if request.r#type.is_limit() {
+            let price = request.price - request.price % price_tick;
+            ensure!(
+                price >= price_min,
+                "最低价格限制: 价格({}),限制({})",
+                price,
+                price_min
+            );
+            ensure!(
+                price <= price_max,
+                "最高价格限制: 价格({}),限制({})",
+                price,
+                price_max
+            );
+            price
+        } else {
+            Decimal::ZERO
+        };
 
-        let margin = amount / leverage;
-        ensure!(
-            cash_available >= margin,
-            "保证金不足: 保证金({}),可用({})",
-            margin,
-            cash_available
-        );
+        let price_trigger = match request.working_type {
+            WorkingType::Last => position.symbol.market.last,
+            WorkingType::Mark => position.symbol.market.mark,
+        };
+        let stop_price = if conditional {
+            if request.r#type == TradeType::TrailingStopMarket {
+                ensure!(
+                    request.callback_rate > Decimal::ZERO && request.callback_rate < Decimal::ONE,
+                    "回调比例错误: {}",
+                    request.callback_rate
+                );
+                // 跟踪止损单的初始触发价格由当前价格与回调比例确定
+                let stop_price = if request.is_buy() {
+                    price_trigger * (Decimal::ONE + request.callback_rate)
+                } else {
+                    price_trigger * (Decimal::ONE - request.callback_rate)
+                };
+                stop_price - stop_price % price_tick
+            } else {
+                let stop_price = request.stop_price - request.stop_price % price_tick;
+                ensure!(
+                    stop_price >= price_min,
+                    "最低触发价格限制: 触发价格({}),限制({})",
+                    stop_price,
+                    price_min
+                );
+                ensure!(
+                    stop_price <= price_max,
+                    "最高触发价格限制: 触发价格({}),限制({})",
+                    stop_price,
+                    price_max
+                );
+                stop_price
+            }
+        } else {
+            Decimal::ZERO
+        };
 
-        let order_id = String::gen_id();
-        self.account
-            .lock()
-            .positions
-            .get_mut(symbol)
-            .unwrap()
-            .orders
-            .insert(
-                order_id.clone(),
-                Order {
-                    id: order_id.clone(),
-                    symbol: symbol.to_string(),
-                    r#type: TradeType::Limit,
-                    side: TradeSide::Long,
-                    reduce_only: false,
-                    status: OrderStatus::Created,
-                    price,
-                    size,
-                    deal_price: Decimal::ZERO,
-                    deal_size: Decimal::ZERO,
-                    deal_fee: Decimal::ZERO,
-                    create_time: self.time(),
-                },
+        if reduce_only {
+            ensure!(
+                bracket || size_available >= size,
+                "持仓数量不足: 数量({}),可用({})",
+                size,
+                size_available,
+            );
+        } else {
+            let price_margin = if request.r#type.is_limit() {
+                price
+            } else if conditional {
+                stop_price
+            } else {
+                position.symbol.market.mark
+            };
+            let amount_min = rule.amount_min;
+            let leverage = Decimal::from(position.leverage);
+            let cash_available = account.cash_available();
+
+            let amount = size * price_margin;
+            ensure!(
+                amount >= amount_min,
+                "最小金额限制: 金额({}),限制({})",
+                amount,
+                amount_min
             );
-        Ok(order_id)
-    }
-    async fn long_market_close(&self, symbol: &str, size: Decimal) -> Result<String> {
-        ensure!(
-            self.account.lock().positions.contains_key(symbol),
-            "交易对不存在: {}",
-            symbol
-        );
 
-        let size_min = self.rule_size_min(symbol);
-        let size_tick = self.rule_size_tick(symbol);
-        let long_size_available = self.long_size_available(symbol);
+            // 单向持仓模式下仅超出反向持仓的部分占用保证金
+            let size_to_open = position.size_to_open(request.side, size);
+            let margin = size_to_open * price_margin / leverage;
+            ensure!(
+                cash_available >= margin,
+                "保证金不足: 保证金({}),可用({})",
+                margin,
+                cash_available
+            );
+        }
 
-        let size = size - size % size_tick;
-        ensure!(
-            size >= size_min,
-            "最小数量限制: 数量({}),限制({})",
+        // 与实盘相同, 订单ID即客户端订单ID
+        let order_id = request.client_id.unwrap_or_else(String::gen_id);
+        let order = Order {
+            id: order_id.clone(),
+            client_id: order_id,
+            group_id: None,
+            symbol: symbol.to_string(),
+            r#type: request.r#type,
+            side: request.side,
+            reduce_only,
+            close_position: request.close_position,
+            time_in_force: request.time_in_force,
+            stop_price,
+            working_type: request.working_type,
+            callback_rate: request.callback_rate,
+            status: OrderStatus::Created,
+            price,
             size,
-            size_min
-        );
+            deal_price: Decimal::ZERO,
+            deal_size: Decimal::ZERO,
+            deal_fee: Decimal::ZERO,
+            deal_slippage: Decimal::ZERO,
+            deal_pnl: Decimal::ZERO,
+            create_time: time,
+            queue_time: time,
+        };
+        if conditional {
+            ensure!(
+                !order.is_triggered(price_trigger),
+                "订单会立即触发: 触发价格({}),当前价格({})",
+                stop_price,
+                price_trigger
+            );
+        }
 
-        ensure!(
-            long_size_available >= size,
-            "持仓数量不足: 数量({}),可用({})",
-            size,
-            long_size_available,
-        );
+        Ok(order)
+    }
+}
 
-        let order_id = String::gen_id();
+#[async_trait]
+impl EngineExchange for Backtest {
+    async fn order_place(&self, request: OrderRequest) -> Result<String> {
+        let order = self.order_new(request, false)?;
+        let order_id = order.id.clone();
         self.account
             .lock()
             .positions
-            .get_mut(symbol)
+            .get_mut(&order.symbol)
             .unwrap()
             .orders
-            .insert(
-                order_id.clone(),
-                Order {
-                    id: order_id.clone(),
-                    symbol: symbol.to_string(),
-                    r#type: TradeType::Market,
-                    side: TradeSide::Long,
-                    reduce_only: true,
-                    status: OrderStatus::Created,
-                    price: Decimal::ZERO,
-                    size,
-                    deal_price: Decimal::ZERO,
-                    deal_size: Decimal::ZERO,
-                    deal_fee: Decimal::ZERO,
-                    create_time: self.time(),
-                },
-            );
+            .insert(order_id.clone(), order);
         Ok(order_id)
     }
-    async fn long_limit_close(
+    async fn order_close(&self, symbol: &str, id: &str) -> Result<()> {
+        self.order_cancel_many(symbol, &[id.to_string()]).await
+    }
+    async fn order_cancel_many(&self, symbol: &str, ids: &[String]) -> Result<()> {
+        let (orders, group_orders) = {
+            let mut account = self.account.lock();
+            let positions = account
+                .positions
+                .get_mut(symbol)
+                .ok_or(anyhow!("交易对不存在: {}", symbol))?;
+
+            if let Some(id) = ids.iter().find(|id| !positions.orders.contains_key(*id)) {
+                return Err(self.order_missing(symbol, id).into());
+            }
+
+            // 回测中撤单立即完成, 订单直接变为已取消, 不经过取消中状态
+            let orders = ids
+                .iter()
+                .filter_map(|id| positions.orders.remove(id))
+                .map(|mut order| {
+                    order.status = OrderStatus::Canceled;
+                    order
+                })
+                .collect::<Vec<Order>>();
+            let group_orders = orders
+                .iter()
+                .flat_map(|order| positions.group_update(order))
+                .collect::<Vec<Order>>();
+            (orders, group_orders)
+        };
+
+        for order in orders.into_iter().chain(group_orders) {
+            self.order_archive(order);
+        }
+        Ok(())
+    }
+    async fn order_amend(
         &self,
         symbol: &str,
-        size: Decimal,
+        id: &str,
         price: Decimal,
+        size: Decimal,
+    ) -> Result<()> {
+        // 校验与替换在同一次加锁内完成, 校验时移出订单, 避免其占用的保证金与持仓数量影响校验
+        let mut account = self.account.lock();
+        let order = account
+            .positions
+            .get_mut(symbol)
+            .ok_or(anyhow!("交易对不存在: {}", symbol))?
+            .orders
+            .remove(id)
+            .ok_or_else(|| self.order_missing(symbol, id))?;
+        let result = self.order_amended(&account, &order, price, size);
+
+        let orders = &mut account.positions.get_mut(symbol).unwrap().orders;
+        match result {
+            Ok(order) => {
+                orders.insert(order.id.clone(), order.clone());
+                self.pending_orders.lock().push(order);
+                Ok(())
+            }
+            Err(err) => {
+                orders.insert(order.id.clone(), order);
+                Err(err)
+            }
+        }
+    }
+    async fn order_place_oco(&self, requests: Vec<OrderRequest>) -> Result<String> {
+        ensure!(requests.len() >= 2, "二选一订单至少包含两个订单");
+        order_requests_check(&requests.iter().collect::<Vec<&OrderRequest>>())?;
+
+        let symbol = requests[0].symbol.clone();
+        let group_id = String::gen_id();
+        let orders = requests
+            .into_iter()
+            .map(|request| {
+                let mut order = self.order_new(request, false)?;
+                order.group_id = Some(group_id.clone());
+                Ok(order)
+            })
+            .collect::<Result<Vec<Order>>>()?;
+
+        let group = OrderGroup {
+            id: group_id.clone(),
+            symbol: symbol.clone(),
+            r#type: OrderGroupType::Oco,
+            entry_id: None,
+            order_ids: orders.iter().map(|order| order.id.clone()).collect(),
+            orders_inactive: Vec::new(),
+            create_time: self.time(),
+        };
+        let mut account = self.account.lock();
+        let positions = account.positions.get_mut(&symbol).unwrap();
+        positions.groups.insert(group_id.clone(), group);
+        for order in orders {
+            positions.orders.insert(order.id.clone(), order);
+        }
+        Ok(group_id)
+    }
+    async fn order_place_bracket(
+        &self,
+        entry: OrderRequest,
+        stop_loss: Option<OrderRequest>,
+        take_profit: Option<OrderRequest>,
     ) -> Result<String> {
         ensure!(
-            self.account.lock().positions.contains_key(symbol),
-            "交易对不存在: {}",
-            symbol
+            !entry.reduce_only && !entry.close_position,
+            "入场订单不能只减仓"
         );
-
-        let price_min = self.rule_price_min(symbol);
-        let price_max = self.rule_price_max(symbol);
-        let price_tick = self.rule_price_tick(symbol);
-        let size_min = self.rule_size_min(symbol);
-        let size_tick = self.rule_size_tick(symbol);
-        let long_size_available = self.long_size_available(symbol);
-
-        let size = size - size % size_tick;
         ensure!(
-            size >= size_min,
-            "最小数量限制: 数量({}),限制({})",
-            size,
-            size_min
+            stop_loss.is_some() || take_profit.is_some(),
+            "括号订单至少包含止损或止盈订单"
         );
+        let brackets = [stop_loss, take_profit]
+            .into_iter()
+            .flatten()
+            .map(|request| OrderRequest {
+                side: entry.side,
+                reduce_only: true,
+                size: entry.size,
+                ..request
+            })
+            .collect::<Vec<OrderRequest>>();
+        order_requests_check(
+            &std::iter::once(&entry)
+                .chain(brackets.iter())
+                .collect::<Vec<&OrderRequest>>(),
+        )?;
+
+        let group_id = String::gen_id();
+        let mut entry = self.order_new(entry, false)?;
+        entry.group_id = Some(group_id.clone());
+        let brackets = brackets
+            .into_iter()
+            .map(|request| {
+                let mut order = self.order_new(request, true)?;
+                order.group_id = Some(group_id.clone());
+                Ok(order)
+            })
+            .collect::<Result<Vec<Order>>>()?;
+
+        let group = OrderGroup {
+            id: group_id.clone(),
+            symbol: entry.symbol.clone(),
+            r#type: OrderGroupType::Bracket,
+            entry_id: Some(entry.id.clone()),
+            order_ids: brackets.iter().map(|order| order.id.clone()).collect(),
+            orders_inactive: brackets,
+            create_time: self.time(),
+        };
+        let mut account = self.account.lock();
+        let positions = account.positions.get_mut(&entry.symbol).unwrap();
+        positions.groups.insert(group_id.clone(), group);
+        positions.orders.insert(entry.id.clone(), entry);
+        Ok(group_id)
+    }
+    async fn order_group_cancel(&self, symbol: &str, id: &str) -> Result<()> {
+        let orders = {
+            let mut account = self.account.lock();
+            let positions = account
+                .positions
+                .get_mut(symbol)
+                .ok_or(anyhow!("交易对不存在: {}", symbol))?;
+            let group = positions
+                .groups
+                .remove(id)
+                .ok_or(OrderError::GroupNotFound {
+                    symbol: symbol.to_owned(),
+                    id: id.to_owned(),
+                })?;
+            group
+                .entry_id
+                .iter()
+                .chain(group.order_ids.iter())
+                .filter_map(|id| positions.orders.remove(id))
+                .map(|mut order| {
+                    order.status = OrderStatus::Canceled;
+                    order
+                })
+                .collect::<Vec<Order>>()
+        };
 
-        let price = price - price % price_tick;
-        ensure!(
-            price >= price_min,
-            "最低价格限制: 价格({}),限制({})",
-            price,
-            price_min
-        );
-        ensure!(
-            price <= price_max,
-            "最高价格限制: 价格({}),限制({})",
-            price,
-            price_max
-        );
-
-        ensure!(
-            long_size_available >= size,
-            "持仓数量不足: 数量({}),可用({})",
-            size,
-            long_size_available,
-        );
-
-        let order_id = String::gen_id();
-        self.account
-            .lock()
+        for order in orders {
+            self.order_archive(order);
+        }
+        Ok(())
+    }
+    async fn leverage_set(&self, symbol: &str, leverage: u32) -> Result<()> {
+        ensure!(leverage >= 1, "杠杆倍率必须大于等于1");
+        let mut account = self.account.lock();
+        let positions = account
             .positions
             .get_mut(symbol)
-            .unwrap()
-            .orders
-            .insert(
-                order_id.clone(),
-                Order {
-                    id: order_id.clone(),
-                    symbol: symbol.to_string(),
-                    r#type: TradeType::Limit,
-                    side: TradeSide::Long,
-                    reduce_only: true,
-                    status: OrderStatus::Created,
-                    price,
-                    size,
-                    deal_price: Decimal::ZERO,
-                    deal_size: Decimal::ZERO,
-                    deal_fee: Decimal::ZERO,
-                    create_time: self.time(),
-                },
+            .ok_or(anyhow!("交易对不存在: {}", symbol))?;
+
+        let notional = (positions.long.size + positions.short.size) * positions.symbol.market.mark;
+        if let Some(tier) = positions.symbol.rule.margin_tier(notional) {
+            ensure!(
+                leverage <= tier.leverage_max,
+                "最大杠杆倍率限制: 杠杆倍率({}),限制({})",
+                leverage,
+                tier.leverage_max
             );
-        Ok(order_id)
-    }
-    async fn short_market_open(&self, symbol: &str, size: Decimal) -> Result<String> {
-        ensure!(
-            self.account.lock().positions.contains_key(symbol),
-            "交易对不存在: {}",
-            symbol
-        );
-
-        let price_mark = self.price_mark(symbol);
-        let size_min = self.rule_size_min(symbol);
-        let size_max = self.rule_size_max(symbol);
-        let size_tick = self.rule_size_tick(symbol);
-        let amount_min = self.rule_amount_min(symbol);
-        let leverage = Decimal::from(self.leverage(symbol));
-        let cash_available = self.cash_available();
+        }
 
-        let size = size - size % size_tick;
-        ensure!(
-            size >= size_min,
-            "最小数量限制: 数量({}),限制({})",
-            size,
-            size_min
-        );
-        ensure!(
-            size <= size_max,
-            "最大数量限制: 数量({}),限制({})",
-            size,
-            size_max,
-        );
+        positions.leverage = leverage;
+        Ok(())
+    }
+    async fn position_mode_set(&self, symbol: &str, mode: PositionMode) -> Result<()> {
+        let mut account = self.account.lock();
+        let positions = account
+            .positions
+            .get_mut(symbol)
+            .ok_or(anyhow!("交易对不存在: {}", symbol))?;
+        if positions.position_mode == mode {
+            return Ok(());
+        }
 
-        let amount = size * price_mark;
         ensure!(
-            amount >= amount_min,
-            "最小金额限制: 金额({}),限制({})",
-            amount,
-            amount_min
+            positions.long.size.is_zero() && positions.short.size.is_zero(),
+            "存在持仓时不能切换持仓模式: {}",
+            symbol
         );
-
-        let margin = amount / leverage;
         ensure!(
-            cash_available >= margin,
-            "保证金不足: 保证金({}),可用({})",
-            margin,
-            cash_available
+            positions.orders.is_empty(),
+            "存在挂单时不能切换持仓模式: {}",
+            symbol
         );
 
-        let order_id = String::gen_id();
-        self.account
-            .lock()
+        positions.position_mode = mode;
+        Ok(())
+    }
+    async fn margin_mode_set(&self, symbol: &str, mode: MarginMode) -> Result<()> {
+        let mut account = self.account.lock();
+        let positions = account
             .positions
             .get_mut(symbol)
-            .unwrap()
-            .orders
-            .insert(
-                order_id.clone(),
-                Order {
-                    id: order_id.clone(),
-                    symbol: symbol.to_string(),
-                    r#type: TradeType::Market,
-                    side: TradeSide::Short,
-                    reduce_only: false,
-                    status: OrderStatus::Created,
-                    price: Decimal::ZERO,
-                    size,
-                    deal_price: Decimal::ZERO,
-                    deal_size: Decimal::ZERO,
-                    deal_fee: Decimal::ZERO,
-                    create_time: self.time(),
-                },
-            );
-        Ok(order_id)
-    }
-    async fn short_limit_open(
-        &self,
-        symbol: &str,
-        size: Decimal,
-        price: Decimal,
-    ) -> Result<String> {
-        ensure!(
-            self.account.lock().positions.contains_key(symbol),
-            "交易对不存在: {}",
-            symbol
-        );
-
-        let price_min = self.rule_price_min(symbol);
-        let price_max = self.rule_price_max(symbol);
-        let price_tick = self.rule_price_tick(symbol);
-        let size_min = self.rule_size_min(symbol);
-        let size_max = self.rule_size_max(symbol);
-        let size_tick = self.rule_size_tick(symbol);
-        let amount_min = self.rule_amount_min(symbol);
-        let leverage = Decimal::from(self.leverage(symbol));
-        let cash_available = self.cash_available();
+            .ok_or(anyhow!("交易对不存在: {}", symbol))?;
+        if positions.margin_mode == mode {
+            return Ok(());
+        }
 
-        let size = size - size % size_tick;
         ensure!(
-            size >= size_min,
-            "最小数量限制: 数量({}),限制({})",
-            size,
-            size_min
+            positions.long.size.is_zero() && positions.short.size.is_zero(),
+            "存在持仓时不能切换保证金模式: {}",
+            symbol
         );
         ensure!(
-            size <= size_max,
-            "最大数量限制: 数量({}),限制({})",
-            size,
-            size_max,
+            positions.orders.is_empty(),
+            "存在挂单时不能切换保证金模式: {}",
+            symbol
         );
 
-        let price = price - price % price_tick;
-        ensure!(
-            price >= price_min,
-            "最低价格限制: 价格({}),限制({})",
-            price,
-            price_min
-        );
-        ensure!(
-            price <= price_max,
-            "最高价格限制: 价格({}),限制({})",
-            price,
-            price_max
-        );
+        positions.margin_mode = mode;
+        positions.long.margin_isolated = Decimal::ZERO;
+        positions.short.margin_isolated = Decimal::ZERO;
+        Ok(())
+    }
+    async fn margin_add(&self, symbol: &str, side: TradeSide, amount: Decimal) -> Result<()> {
+        ensure!(amount > Decimal::ZERO, "保证金数额必须大于0");
+        let mut account = self.account.lock();
+        let cash_available = account.cash_available();
+        let positions = account
+            .positions
+            .get_mut(symbol)
+            .ok_or(anyhow!("交易对不存在: {}", symbol))?;
 
-        let amount = size * price;
+        ensure!(positions.is_isolated(), "非逐仓模式: {}", symbol);
         ensure!(
-            amount >= amount_min,
-            "最小金额限制: 金额({}),限制({})",
-            amount,
-            amount_min
+            !positions.position(side).size.is_zero(),
+            "无持仓时不能调整保证金: {}({})",
+            symbol,
+            side
         );
-
-        let margin = amount / leverage;
         ensure!(
-            cash_available >= margin,
-            "保证金不足: 保证金({}),可用({})",
-            margin,
+            cash_available >= amount,
+            "可用资金不足: 数额({}),可用({})",
+            amount,
             cash_available
         );
 
-        let order_id = String::gen_id();
-        self.account
-            .lock()
+        positions.position_mut(side).margin_isolated += amount;
+        Ok(())
+    }
+    async fn margin_remove(&self, symbol: &str, side: TradeSide, amount: Decimal) -> Result<()> {
+        ensure!(amount > Decimal::ZERO, "保证金数额必须大于0");
+        let mut account = self.account.lock();
+        let positions = account
             .positions
             .get_mut(symbol)
-            .unwrap()
-            .orders
-            .insert(
-                order_id.clone(),
-                Order {
-                    id: order_id.clone(),
-                    symbol: symbol.to_string(),
-                    r#type: TradeType::Limit,
-                    side: TradeSide::Short,
-                    reduce_only: false,
-                    status: OrderStatus::Created,
-                    price,
-                    size,
-                    deal_price: Decimal::ZERO,
-                    deal_size: Decimal::ZERO,
-                    deal_fee: Decimal::ZERO,
-                    create_time: self.time(),
-                },
-            );
-        Ok(order_id)
-    }
-    async fn short_market_close(&self, symbol: &str, size: Decimal) -> Result<String> {
-        ensure!(
-            self.account.lock().positions.contains_key(symbol),
-            "交易对不存在: {}",
-            symbol
-        );
+            .ok_or(anyhow!("交易对不存在: {}", symbol))?;
 
-        let size_tick = self.rule_size_tick(symbol);
-        let size_min = self.rule_size_min(symbol);
-        let short_size_available = self.short_size_available(symbol);
-
-        let size = size - size % size_tick;
+        ensure!(positions.is_isolated(), "非逐仓模式: {}", symbol);
         ensure!(
-            size >= size_min,
-            "最小数量限制: 数量({}),限制({})",
-            size,
-            size_min
+            !positions.position(side).size.is_zero(),
+            "无持仓时不能调整保证金: {}({})",
+            symbol,
+            side
         );
 
+        // 减少后该方向的逐仓保证金需覆盖该方向持仓的初始保证金与未实现亏损
+        let mark_price = positions.symbol.market.mark;
+        let leverage = positions.leverage;
+        let position = positions.position_mut(side);
+        let removable = position.margin_isolated - position.margin(leverage)
+            + position.pnl(mark_price).min(Decimal::ZERO);
         ensure!(
-            short_size_available >= size,
-            "持仓数量不足: 数量({}),可用({})",
-            size,
-            short_size_available
+            removable >= amount,
+            "可减少保证金不足: 数额({}),可减少({})",
+            amount,
+            removable.max(Decimal::ZERO)
         );
 
-        let order_id = String::gen_id();
-        self.account
-            .lock()
-            .positions
-            .get_mut(symbol)
-            .unwrap()
-            .orders
-            .insert(
-                order_id.clone(),
-                Order {
-                    id: order_id.clone(),
-                    symbol: symbol.to_string(),
-                    r#type: TradeType::Market,
-                    side: TradeSide::Short,
-                    reduce_only: true,
-                    status: OrderStatus::Created,
-                    price: Decimal::ZERO,
-                    size,
-                    deal_price: Decimal::ZERO,
-                    deal_size: Decimal::ZERO,
-                    deal_fee: Decimal::ZERO,
-                    create_time: self.time(),
-                },
-            );
-        Ok(order_id)
+        position.margin_isolated -= amount;
+        Ok(())
     }
-    async fn short_limit_close(
-        &self,
-        symbol: &str,
-        size: Decimal,
-        price: Decimal,
-    ) -> Result<String> {
-        ensure!(
-            self.account.lock().positions.contains_key(symbol),
-            "交易对不存在: {}",
-            symbol
-        );
-
-        let size_tick = self.rule_size_tick(symbol);
-        let price_min = self.rule_price_min(symbol);
-        let price_max = self.rule_price_max(symbol);
-        let price_tick = self.rule_price_tick(symbol);
-        let size_min = self.rule_size_min(symbol);
-        let short_size_available = self.short_size_available(symbol);
-
-        let size = size - size % size_tick;
-        ensure!(
-            size >= size_min,
-            "最小数量限制: 数量({}),限制({})",
-            size,
-            size_min
-        );
+}
 
-        let price = price - price % price_tick;
-        ensure!(
-            price >= price_min,
-            "最低价格限制: 价格({}),限制({})",
-            price,
-            price_min
-        );
-        ensure!(
-            price <= price_max,
-            "最高价格限制: 价格({}),限制({})",
-            price,
-            price_max
-        );
+/// 历史数据事件, 同一时间按声明顺序处理: 标记价格、资金费率、指数价格、K线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum HistoryEventType {
+    MarkPrice,
+    FundingRate,
+    IndexPrice,
+    Kline,
+}
 
-        ensure!(
-            short_size_available >= size,
-            "持仓数量不足: 数量({}),可用({})",
-            size,
-            short_size_available
-        );
+enum HistoryEvent {
+    MarkPrice(Kline),
+    FundingRate(FundingRateHistory),
+    IndexPrice(Kline),
+    Kline(Kline),
+}
 
-        let order_id = String::gen_id();
-        self.account
-            .lock()
-            .positions
-            .get_mut(symbol)
-            .unwrap()
-            .orders
-            .insert(
-                order_id.clone(),
-                Order {
-                    id: order_id.clone(),
-                    symbol: symbol.to_string(),
-                    r#type: TradeType::Limit,
-                    side: TradeSide::Short,
-                    reduce_only: true,
-                    status: OrderStatus::Created,
-                    price,
-                    size,
-                    deal_price: Decimal::ZERO,
-                    deal_size: Decimal::ZERO,
-                    deal_fee: Decimal::ZERO,
-                    create_time: self.time(),
-                },
-            );
-        Ok(order_id)
-    }
-    async fn order_close(&self, symbol: &str, id: &str) -> Result<()> {
-        self.account
-            .lock()
-            .positions
-            .get_mut(symbol)
-            .ok_or(anyhow!("交易对不存在: {}", symbol))?
-            .orders
-            .remove(id);
-        Ok(())
-    }
-    async fn order_cancel_many(&self, symbol: &str, ids: &[String]) -> Result<()> {
-        for id in ids {
-            self.account
-                .lock()
-                .positions
-                .get_mut(symbol)
-                .ok_or(anyhow!("交易对不存在: {}", symbol))?
-                .orders
-                .remove(id);
+impl HistoryEvent {
+    /// 事件时间: K线为收盘时间, 资金费率为结算时间
+    fn time(&self) -> DateTime<Utc> {
+        match self {
+            HistoryEvent::FundingRate(funding_rate) => funding_rate.time,
+            HistoryEvent::MarkPrice(kline)
+            | HistoryEvent::IndexPrice(kline)
+            | HistoryEvent::Kline(kline) => kline.open_time + Duration::minutes(1),
         }
-        Ok(())
-    }
-    async fn leverage_set(&self, symbol: &str, leverage: u32) -> Result<()> {
-        ensure!(leverage >= 1, "杠杆倍率必须大于等于1");
-        self.account
-            .lock()
-            .positions
-            .get_mut(symbol)
-            .ok_or(anyhow!("交易对不存在: {}", symbol))?
-            .leverage = leverage;
-        Ok(())
     }
 }
 
-struct SymbolHistoryData_ {
-    funding_rate: HistoryDataStream<FundingRateHistory>,
-    klines: HistoryDataStream<Kline>,
-    index_price_klines: HistoryDataStream<Kline>,
-    mark_price_klines: HistoryDataStream<Kline>,
+enum HistorySourceStream {
+    FundingRate(HistoryDataStream<FundingRateHistory>),
+    Klines(HistoryDataStream<Kline>),
 }
 
-struct SymbolHistoryData(HashMap<String, SymbolHistoryData_>);
+/// 历史数据源: 交易对的一种历史数据流
+struct HistorySource {
+    symbol: String,
+    r#type: HistoryEventType,
+    stream: HistorySourceStream,
+}
 
-impl SymbolHistoryData {
-    pub fn new(symbols: &[String], begin: DateTime<Utc>, end: DateTime<Utc>) -> Self {
-        let mut result = HashMap::new();
-        for symbol in symbols {
-            let history_data = SymbolHistoryData_ {
-                funding_rate: HistoryDataStream::new(
-                    symbol.to_owned(),
-                    HistoryDataStreamType::FundingRate,
-                    begin,
-                    end,
-                ),
-                klines: HistoryDataStream::new(
-                    symbol.to_owned(),
-                    HistoryDataStreamType::Klines,
-                    begin,
-                    end,
-                ),
-                index_price_klines: HistoryDataStream::new(
-                    symbol.to_owned(),
-                    HistoryDataStreamType::IndexPriceKlines,
-                    begin,
-                    end,
-                ),
-                mark_price_klines: HistoryDataStream::new(
+impl HistorySource {
+    fn new(
+        path: &Path,
+        symbol: &str,
+        r#type: HistoryEventType,
+        begin: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Self {
+        let stream_type = match r#type {
+            HistoryEventType::MarkPrice => HistoryDataStreamType::MarkPriceKlines,
+            HistoryEventType::FundingRate => HistoryDataStreamType::FundingRate,
+            HistoryEventType::IndexPrice => HistoryDataStreamType::IndexPriceKlines,
+            HistoryEventType::Kline => HistoryDataStreamType::Klines,
+        };
+        let stream = match r#type {
+            HistoryEventType::FundingRate => {
+                HistorySourceStream::FundingRate(HistoryDataStream::with_path(
+                    path.to_owned(),
                     symbol.to_owned(),
-                    HistoryDataStreamType::MarkPriceKlines,
+                    stream_type,
                     begin,
                     end,
-                ),
-            };
-            result.insert(symbol.to_owned(), history_data);
-        }
-        Self(result)
-    }
-
-    async fn flush_market_settlement_price(
-        &mut self,
-        backtest: &Arc<Backtest>,
-        symbol: &str,
-        date: DateTime<Utc>,
-    ) -> Result<()> {
-        let history_data = self.0.get_mut(symbol).unwrap();
-        if let Some(funding_rate) = history_data.funding_rate.take(date).await? {
-            let mut account = backtest.account.lock();
-            let symbol_position = account.positions.get_mut(symbol).unwrap();
-            symbol_position.symbol.market.settlement = funding_rate.rate;
-            symbol_position.symbol.market.settlement_time = date + Duration::hours(8);
+                ))
+            }
+            _ => HistorySourceStream::Klines(HistoryDataStream::with_path(
+                path.to_owned(),
+                symbol.to_owned(),
+                stream_type,
+                begin,
+                end,
+            )),
+        };
+        Self {
+            symbol: symbol.to_owned(),
+            r#type,
+            stream,
         }
-        Ok(())
     }
 
-    async fn flush_market_last_price(
-        &mut self,
-        backtest: &Arc<Backtest>,
-        symbol: &str,
-        date: DateTime<Utc>,
-    ) -> Result<()> {
-        let history_data = self.0.get_mut(symbol).unwrap();
-        if let Some(mut kline) = history_data
-            .klines
-            .take(date - Duration::minutes(1))
-            .await?
-        {
-            backtest
-                .account
-                .lock()
-                .positions
-                .get_mut(symbol)
-                .unwrap()
-                .symbol
-                .market
-                .last = kline.close;
-
-            backtest.cross_order(symbol).await?;
-
-            kline.symbol = symbol.to_owned();
-            backtest.strategy.on_kline(backtest.clone(), kline).await?;
+    async fn next(&mut self) -> Option<HistoryEvent> {
+        match &mut self.stream {
+            HistorySourceStream::FundingRate(stream) => {
+                let mut funding_rate = stream.next().await?;
+                funding_rate.symbol = self.symbol.clone();
+                Some(HistoryEvent::FundingRate(funding_rate))
+            }
+            HistorySourceStream::Klines(stream) => {
+                let mut kline = stream.next().await?;
+                kline.symbol = self.symbol.clone();
+                Some(match self.r#type {
+                    HistoryEventType::MarkPrice => HistoryEvent::MarkPrice(kline),
+                    HistoryEventType::IndexPrice => HistoryEvent::IndexPrice(kline),
+                    _ => HistoryEvent::Kline(kline),
+                })
+            }
         }
-
-        Ok(())
     }
+}
 
-    async fn flush_market_index_price(
-        &mut self,
-        backtest: &Arc<Backtest>,
-        symbol: &str,
-        date: DateTime<Utc>,
-    ) -> Result<()> {
-        let history_data = self.0.get_mut(symbol).unwrap();
-        if let Some(kline) = history_data
-            .index_price_klines
-            .take(date - Duration::minutes(1))
-            .await?
-        {
-            let mut account = backtest.account.lock();
-            let symbol_position = account.positions.get_mut(symbol).unwrap();
-            symbol_position.symbol.market.index = kline.close;
-        }
-        Ok(())
-    }
+/// 事件排序键: (事件时间, 事件类型, 交易对序号, 数据源序号)
+type HistoryEventKey = (DateTime<Utc>, HistoryEventType, usize, usize);
+
+/// 历史数据调度器: 以最小堆多路归并全部交易对的历史数据流, 按事件时间依次取出
+/// <br> 时钟只推进到下一个事件的时间, 尚未上架或数据缺失的交易对不产生事件
+struct HistoryScheduler {
+    sources: Vec<HistorySource>,
+    /// 各数据源的下一个事件
+    heads: Vec<Option<HistoryEvent>>,
+    heap: BinaryHeap<Reverse<HistoryEventKey>>,
+    symbols: Vec<String>,
+}
 
-    async fn flush_market_mark_price(
-        &mut self,
-        backtest: &Arc<Backtest>,
-        symbol: &str,
-        date: DateTime<Utc>,
-    ) -> Result<()> {
-        let history_data = self.0.get_mut(symbol).unwrap();
-        if let Some(kline) = history_data
-            .mark_price_klines
-            .take(date - Duration::minutes(1))
-            .await?
-        {
-            backtest
-                .account
-                .lock()
-                .positions
-                .get_mut(symbol)
-                .unwrap()
-                .symbol
-                .market
-                .mark = kline.close;
+impl HistoryScheduler {
+    async fn new(
+        path: &Path,
+        symbols: &[String],
+        begin: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Self {
+        let mut symbols = symbols.to_vec();
+        symbols.sort();
+        let mut scheduler = Self {
+            sources: Vec::new(),
+            heads: Vec::new(),
+            heap: BinaryHeap::new(),
+            symbols,
+        };
+        for symbol in scheduler.symbols.clone() {
+            for r#type in [
+                HistoryEventType::MarkPrice,
+                HistoryEventType::FundingRate,
+                HistoryEventType::IndexPrice,
+                HistoryEventType::Kline,
+            ] {
+                scheduler
+                    .sources
+                    .push(HistorySource::new(path, &symbol, r#type, begin, end));
+                scheduler.heads.push(None);
+                scheduler.advance(scheduler.sources.len() - 1).await;
+            }
         }
-        Ok(())
+        scheduler
     }
 
-    pub async fn flush_market(
-        &mut self,
-        backtest: &Arc<Backtest>,
-        symbols: &[String],
-        date: DateTime<Utc>,
-    ) -> Result<()> {
-        for symbol in symbols {
-            self.flush_market_settlement_price(backtest, symbol, date)
-                .await?;
-            self.flush_market_mark_price(backtest, symbol, date).await?;
-            self.flush_market_index_price(backtest, symbol, date)
-                .await?;
-            self.flush_market_last_price(backtest, symbol, date).await?;
+    /// 读取数据源的下一个事件并加入堆
+    async fn advance(&mut self, index: usize) {
+        let source = &mut self.sources[index];
+        let Some(event) = source.next().await else {
+            return;
+        };
+        let symbol_index = self
+            .symbols
+            .binary_search(&source.symbol)
+            .unwrap_or_default();
+        self.heap
+            .push(Reverse((event.time(), source.r#type, symbol_index, index)));
+        self.heads[index] = Some(event);
+    }
+
+    /// 下一个事件的时间
+    fn peek_time(&self) -> Option<DateTime<Utc>> {
+        self.heap.peek().map(|Reverse((time, ..))| *time)
+    }
+
+    /// 取出指定时间的全部事件, 同一时间按事件类型、交易对排列
+    /// <br> 同一数据源在同一时间的重复数据只保留第一条
+    async fn take(&mut self, time: DateTime<Utc>) -> Vec<HistoryEvent> {
+        let mut events = Vec::new();
+        let mut taken = HashSet::new();
+        while let Some(Reverse((event_time, _, _, index))) = self.heap.peek().copied() {
+            if event_time > time {
+                break;
+            }
+            self.heap.pop();
+            let event = self.heads[index].take();
+            self.advance(index).await;
+            if let Some(event) = event {
+                if event_time == time && taken.insert(index) {
+                    events.push(event);
+                }
+            }
         }
-        Ok(())
+        events
     }
 }
 
 impl Backtest {
-    fn new(mut config: BacktestConfig, strategy: Arc<dyn Strategy>) -> Result<Arc<Backtest>> {
-        config.begin = config.begin.truncate_minute()?;
-        config.end = config.end.truncate_minute()?;
-        ensure!(config.begin < config.end, "开始时间必须小于结束时间");
-
+    /// 校验撮合与手续费参数, 起止时间由调用方校验
+    pub(crate) fn new(
+        config: BacktestConfig,
+        strategy: Arc<dyn Strategy>,
+    ) -> Result<Arc<Backtest>> {
         ensure!(config.cash >= Decimal::ZERO, "初始资金必须大于等于0");
 
         ensure!(config.slippage_rate >= Decimal::ZERO, "滑点率必须大于等于0");
 
+        ensure!(
+            config.fee_rate_liquidation >= Decimal::ZERO,
+            "强平手续费率必须大于等于0"
+        );
+
+        ensure!(
+            config.equity_interval >= Duration::minutes(1),
+            "权益曲线采样间隔必须大于等于1分钟"
+        );
+
+        ensure!(config.kline_history > 0, "K线缓存数量必须大于0");
+
+        if let Some(participation_rate) = config.participation_rate {
+            ensure!(
+                participation_rate > Decimal::ZERO && participation_rate <= Decimal::ONE,
+                "成交量参与率必须大于0且小于等于1"
+            );
+        }
+
         let config = Arc::new(config);
 
         let account = Arc::new(Mutex::new(Account {
             cash: config.cash,
             positions: Default::default(),
         }));
+        let ledgers = Arc::new(Mutex::new(HashMap::new()));
+        let pending_orders = Arc::new(Mutex::new(Vec::new()));
+        let kline_feed = Arc::new(Mutex::new(KlineFeed::new(config.kline_history)));
         let trade_time = Arc::new(Mutex::new(config.begin));
+        let rules_implicit = config.rule_path.is_none();
+        let rules = match config.rule_path.as_deref() {
+            Some(path) => Some(SymbolRules::load(path)?),
+            // 缓存目录不存在或没有可用的快照时不使用交易规则快照
+            None => SymbolRules::cache_dir()
+                .ok()
+                .filter(|dir| dir.exists())
+                .and_then(|dir| match SymbolRules::load(&dir) {
+                    Ok(rules) => Some(rules),
+                    Err(err) => {
+                        tracing::warn!("交易规则快照加载失败, 使用默认交易规则: {}", err);
+                        None
+                    }
+                }),
+        };
 
         Ok(Arc::new(Backtest {
             config,
             account,
+            ledgers,
+            pending_orders,
+            kline_feed,
             trade_time,
             strategy,
+            engine: OnceLock::new(),
+            rules,
+            rules_implicit,
+            rules_custom: Mutex::new(HashSet::new()),
         }))
     }
 
-    pub async fn run(config: BacktestConfig, strategy: Arc<dyn Strategy>) -> Result<()> {
-        Self::new(config, strategy)?.run0().await?;
+    pub async fn run(
+        mut config: BacktestConfig,
+        strategy: Arc<dyn Strategy>,
+    ) -> Result<BacktestReport> {
+        config.begin = config.begin.truncate_minute()?;
+        config.end = config.end.truncate_minute()?;
+        ensure!(config.begin < config.end, "开始时间必须小于结束时间");
+        Self::new(config, strategy)?.run0().await
+    }
+}
+
+impl Backtest {
+    /// 通知策略时传入的引擎
+    fn engine(self: &Arc<Self>) -> Arc<dyn Engine> {
+        self.engine
+            .get()
+            .and_then(Weak::upgrade)
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// 以外层引擎通知策略, 只能设置一次
+    pub(crate) fn engine_set(&self, engine: Weak<dyn Engine>) {
+        let _ = self.engine.set(engine);
+    }
+
+    /// 以1分钟K线更新最新价格并撮合订单, 按订阅的周期聚合, 收盘后通知
+    pub(crate) async fn flush_kline(
+        self: &Arc<Self>,
+        kline: &Kline,
+        mark_kline: Option<&Kline>,
+    ) -> Result<()> {
+        self.account
+            .lock()
+            .positions
+            .get_mut(&kline.symbol)
+            .unwrap()
+            .symbol
+            .market
+            .last = kline.close;
+
+        self.cross_order(&kline.symbol, kline, mark_kline).await?;
+
+        let klines = self.kline_feed.lock().push(kline);
+        for kline in klines {
+            self.strategy.on_kline(self.engine(), kline).await?;
+        }
+        Ok(())
+    }
+
+    /// 整点事件: 按每日、每小时、每分钟的顺序执行, 每个事件后通知待通知的订单
+    pub(crate) async fn flush_timer(self: &Arc<Self>, time: DateTime<Utc>) -> Result<()> {
+        if time.hour() == 0 && time.minute() == 0 {
+            self.rules_update(time);
+        }
+        self.on_daily(time).await?;
+        self.on_order_pending().await?;
+        self.on_hourly(time).await?;
+        self.on_order_pending().await?;
+        if self.config.timer_minutely {
+            self.on_minutely(time).await?;
+            self.on_order_pending().await?;
+        }
+        Ok(())
+    }
+
+    /// 下一次定时事件的时间: 每分钟, 或关闭每分钟事件时的下一个整点
+    fn timer_next(&self, time: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        if self.config.timer_minutely {
+            Ok(time + Duration::minutes(1))
+        } else {
+            Ok(time.truncate_hour()? + Duration::hours(1))
+        }
+    }
+
+    /// 处理历史数据事件, 标记价格K线先于同一时间的K线处理并用于撮合
+    async fn flush_history(
+        self: &Arc<Self>,
+        event: HistoryEvent,
+        mark_klines: &mut HashMap<String, Kline>,
+    ) -> Result<()> {
+        match event {
+            HistoryEvent::MarkPrice(kline) => {
+                if let Some(position) = self.account.lock().positions.get_mut(&kline.symbol) {
+                    position.symbol.market.mark = kline.close;
+                }
+                mark_klines.insert(kline.symbol.clone(), kline);
+            }
+            HistoryEvent::FundingRate(funding_rate) => {
+                if let Some(position) = self.account.lock().positions.get_mut(&funding_rate.symbol)
+                {
+                    position.symbol.market.settlement = funding_rate.rate;
+                    position.symbol.market.settlement_time = funding_rate.time + Duration::hours(8);
+                }
+                self.settle_funding(&funding_rate.symbol, funding_rate.rate)
+                    .await?;
+            }
+            HistoryEvent::IndexPrice(kline) => {
+                if let Some(position) = self.account.lock().positions.get_mut(&kline.symbol) {
+                    position.symbol.market.index = kline.close;
+                }
+            }
+            HistoryEvent::Kline(kline) => {
+                let mark_kline = mark_klines.get(&kline.symbol).cloned();
+                self.flush_kline(&kline, mark_kline.as_ref()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn symbol_insert(&self, symbol: &str, rule: SymbolRule) -> Result<()> {
+        ensure!(
+            !self.account.lock().positions.contains_key(symbol),
+            "重复初始化交易对: {}",
+            symbol
+        );
+        self.account
+            .lock()
+            .positions
+            .insert(symbol.to_string(), SymbolPosition::new(symbol, rule));
+        self.ledgers
+            .lock()
+            .insert(symbol.to_string(), Default::default());
+        self.kline_feed.lock().symbol_init(symbol);
+        Ok(())
+    }
+
+    /// 切换到当日生效的交易规则, 自定义交易规则的交易对保持不变
+    fn rules_update(&self, time: DateTime<Utc>) {
+        let Some(rules) = self.rules.as_ref() else {
+            return;
+        };
+        let rules_custom = self.rules_custom.lock();
+        for (symbol, position) in self.account.lock().positions.iter_mut() {
+            if rules_custom.contains(symbol) {
+                continue;
+            }
+            if let Some(rule) = rules.rule(symbol, time) {
+                position.symbol.rule = rule;
+            }
+        }
+    }
+
+    /// 已完成的订单归档到账本, 订单事件在当前事件执行完成后通知
+    fn order_archive(&self, order: Order) {
+        self.ledgers
+            .lock()
+            .get_mut(&order.symbol)
+            .unwrap()
+            .orders
+            .push(order.clone());
+        self.pending_orders.lock().push(order);
+    }
+
+    /// 导出账本
+    fn ledger_export(&self) -> Result<()> {
+        let Some(path) = self.config.ledger_path.as_ref() else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(&*self.ledgers.lock())?;
+        std::fs::write(path, json)?;
+        tracing::info!("账本已导出: {}", path.display());
         Ok(())
     }
 }
 
 impl Backtest {
-    pub async fn run0(self: &Arc<Self>) -> Result<()> {
-        self.strategy.on_init(self.clone()).await?;
+    pub async fn run0(self: &Arc<Self>) -> Result<BacktestReport> {
+        self.strategy.on_init(self.engine()).await?;
 
         ensure!(!self.account.lock().positions.is_empty(), "未初始化交易对");
 
@@ -1304,34 +1701,72 @@ impl Backtest {
             .cloned()
             .collect::<Vec<String>>();
 
-        self.strategy.on_start(self.clone()).await?;
+        self.strategy.on_start(self.engine()).await?;
+        self.on_order_pending().await?;
 
-        let mut begin = self.config.begin;
+        let begin = self.config.begin;
         let end = self.config.end;
 
-        let mut symbol_history_data = SymbolHistoryData::new(&symbols, begin, end);
+        let history_path = match self.config.history_path.as_ref() {
+            Some(path) => path.clone(),
+            None => HistoryData::cache_dir()?,
+        };
+        let mut scheduler = HistoryScheduler::new(&history_path, &symbols, begin, end).await;
+
+        let mut recorder = BacktestRecorder::new(begin, self.config.equity_interval);
 
         let backtest_instant = Instant::now();
 
-        while begin <= end {
-            *self.trade_time.lock() = begin;
+        // 开盘时间为结束时间的K线在其收盘时间通知, 时钟推进到该时间为止, 定时事件不晚于结束时间
+        let last = end + Duration::minutes(1);
+        let mut timer = Some(if self.config.timer_minutely || begin.minute() == 0 {
+            begin
+        } else {
+            self.timer_next(begin)?
+        })
+        .filter(|timer| *timer <= end);
+        while let Some(time) = [scheduler.peek_time(), timer]
+            .into_iter()
+            .flatten()
+            .min()
+            .filter(|time| *time <= last)
+        {
+            recorder.skip_to(time, &self.account.lock());
+            *self.trade_time.lock() = time;
+
+            let mut mark_klines = HashMap::new();
+            for event in scheduler.take(time).await {
+                self.flush_history(event, &mut mark_klines).await?;
+            }
 
-            symbol_history_data
-                .flush_market(self, &symbols, begin)
-                .await?;
+            self.liquidate().await?;
+            self.on_order_pending().await?;
 
-            self.on_daily(begin).await?;
-            self.on_hourly(begin).await?;
-            self.on_minutely(begin).await?;
+            recorder.record(time, &self.account.lock());
 
-            begin += Duration::minutes(1);
+            if Some(time) == timer {
+                self.flush_timer(time).await?;
+                timer = Some(self.timer_next(time)?).filter(|timer| *timer <= end);
+            }
         }
+        recorder.skip_to(last + Duration::minutes(1), &self.account.lock());
+        *self.trade_time.lock() = last;
 
         tracing::info!("回测耗时: {:?}", backtest_instant.elapsed());
 
         self.on_stop().await?;
 
-        Ok(())
+        self.ledger_export()?;
+
+        let report = recorder.finish(self.time(), &self.account.lock(), &self.ledgers.lock());
+        tracing::info!(
+            "回测结果: 收益率({:.4}),最大回撤({:.4}),夏普比率({:.4})",
+            report.total_return,
+            report.max_drawdown,
+            report.sharpe
+        );
+
+        Ok(report)
     }
 }
 
@@ -1341,7 +1776,7 @@ impl Backtest {
             return Ok(());
         }
         let instant = Instant::now();
-        if let Err(err) = self.strategy.on_daily(self.clone()).await {
+        if let Err(err) = self.strategy.on_daily(self.engine()).await {
             tracing::error!("{} 每日事件执行失败: {}", time.str_ymd_hm(), err);
         } else {
             tracing::debug!(
@@ -1358,7 +1793,7 @@ impl Backtest {
             return Ok(());
         }
         let instant = Instant::now();
-        if let Err(err) = self.strategy.on_hourly(self.clone()).await {
+        if let Err(err) = self.strategy.on_hourly(self.engine()).await {
             tracing::error!("{} 每小时事件执行失败: {}", time.str_ymd_hm(), err);
         } else {
             tracing::debug!(
@@ -1372,7 +1807,7 @@ impl Backtest {
 
     async fn on_order(self: &Arc<Self>, order: Order) -> Result<()> {
         let instant = Instant::now();
-        if let Err(err) = self.strategy.on_order(self.clone(), order).await {
+        if let Err(err) = self.strategy.on_order(self.engine(), order).await {
             tracing::error!("{} 订单事件失败: {}", self.time().str_ymd_hm(), err);
         } else {
             tracing::debug!(
@@ -1384,9 +1819,68 @@ impl Backtest {
         Ok(())
     }
 
+    /// 通知撤单、拒绝等非成交产生的订单事件, 直到没有新的待通知订单
+    pub(crate) async fn on_order_pending(self: &Arc<Self>) -> Result<()> {
+        loop {
+            let orders = std::mem::take(&mut *self.pending_orders.lock());
+            if orders.is_empty() {
+                return Ok(());
+            }
+            for order in orders {
+                self.on_order(order).await?;
+            }
+        }
+    }
+
+    async fn on_position(self: &Arc<Self>, event: PositionEvent) -> Result<()> {
+        let instant = Instant::now();
+        if let Err(err) = self.strategy.on_position(self.engine(), event).await {
+            tracing::error!("{} 持仓事件失败: {}", self.time().str_ymd_hm(), err);
+        } else {
+            tracing::debug!(
+                "{} 持仓事件执行耗时: {:?}",
+                self.time().str_ymd_hm(),
+                instant.elapsed()
+            );
+        }
+        Ok(())
+    }
+
+    async fn on_funding(self: &Arc<Self>, funding: Funding) -> Result<()> {
+        let instant = Instant::now();
+        if let Err(err) = self.strategy.on_funding(self.engine(), funding).await {
+            tracing::error!("{} 资金费用事件失败: {}", self.time().str_ymd_hm(), err);
+        } else {
+            tracing::debug!(
+                "{} 资金费用事件执行耗时: {:?}",
+                self.time().str_ymd_hm(),
+                instant.elapsed()
+            );
+        }
+        Ok(())
+    }
+
+    async fn on_liquidation(self: &Arc<Self>, liquidation: Liquidation) -> Result<()> {
+        let instant = Instant::now();
+        if let Err(err) = self
+            .strategy
+            .on_liquidation(self.engine(), liquidation)
+            .await
+        {
+            tracing::error!("{} 强平事件失败: {}", self.time().str_ymd_hm(), err);
+        } else {
+            tracing::debug!(
+                "{} 强平事件执行耗时: {:?}",
+                self.time().str_ymd_hm(),
+                instant.elapsed()
+            );
+        }
+        Ok(())
+    }
+
     async fn on_minutely(self: &Arc<Self>, time: DateTime<Utc>) -> Result<()> {
         let instant = Instant::now();
-        if let Err(err) = self.strategy.on_minutely(self.clone()).await {
+        if let Err(err) = self.strategy.on_minutely(self.engine()).await {
             tracing::error!("{} 每分钟事件执行失败: {}", time.str_ymd_hm(), err);
         } else {
             tracing::debug!(
@@ -1398,116 +1892,438 @@ impl Backtest {
         Ok(())
     }
 
-    async fn on_stop(self: &Arc<Self>) -> Result<()> {
-        self.strategy.on_stop(self.clone()).await?;
+    pub(crate) async fn on_stop(self: &Arc<Self>) -> Result<()> {
+        self.strategy.on_stop(self.engine()).await?;
         Ok(())
     }
 
-    async fn cross_order(self: &Arc<Self>, symbol: &str) -> Result<()> {
-        let orders = {
+    /// 全仓权益低于全仓维持保证金时, 按标记价格强平全部全仓持仓并撤销挂单
+    /// <br> 逐仓持仓某方向的逐仓保证金与未实现盈亏之和低于该方向维持保证金时, 仅强平该方向持仓并撤销该交易对挂单,
+    /// <br> 亏损以该方向的逐仓保证金为限
+    pub(crate) async fn liquidate(self: &Arc<Self>) -> Result<()> {
+        let time = self.time();
+        let (liquidations, position_events) = {
+            let mut account = self.account.lock();
+            let cross = account.is_liquidatable();
+            if cross {
+                tracing::warn!(
+                    "{} 触发全仓强平: 权益({}),维持保证金({})",
+                    time.str_ymd_hm(),
+                    account.equity_cross(),
+                    account.maintenance_cross()
+                );
+            }
+
+            let mut liquidations = Vec::new();
+            let mut canceled_orders = Vec::new();
+            let mut position_events = Vec::new();
+            let mut profit = Decimal::ZERO;
+            for (symbol, positions) in account.positions.iter_mut() {
+                let isolated = positions.is_isolated();
+                let sides = if isolated {
+                    [TradeSide::Long, TradeSide::Short]
+                        .into_iter()
+                        .filter(|side| positions.is_liquidatable_isolated(*side))
+                        .collect::<Vec<_>>()
+                } else if cross {
+                    vec![TradeSide::Long, TradeSide::Short]
+                } else {
+                    continue;
+                };
+                if sides.is_empty() {
+                    continue;
+                }
+
+                let snapshot = positions.position_snapshot();
+                let mark_price = positions.symbol.market.mark;
+                let rule = positions.symbol.rule.clone();
+                let mut symbol_profit = Decimal::ZERO;
+                for side in sides {
+                    let position = positions.position_mut(side);
+                    if position.size.is_zero() {
+                        continue;
+                    }
+                    if isolated {
+                        tracing::warn!(
+                            "{} 触发逐仓强平: 交易对({}),方向({}),逐仓保证金({}),维持保证金({})",
+                            time.str_ymd_hm(),
+                            symbol,
+                            side,
+                            position.margin_isolated,
+                            position.maintenance(mark_price, &rule)
+                        );
+                    }
+                    let liquidation = Liquidation {
+                        symbol: symbol.to_owned(),
+                        side: position.side,
+                        price: mark_price,
+                        size: position.size,
+                        pnl: position.pnl(mark_price),
+                        fee: position.size * mark_price * self.config.fee_rate_liquidation,
+                        time,
+                    };
+                    let mut position_profit = liquidation.pnl - liquidation.fee;
+                    if isolated {
+                        position_profit = position_profit.max(-position.margin_isolated);
+                        position.margin_isolated = Decimal::ZERO;
+                    }
+                    symbol_profit += position_profit;
+                    liquidations.push(liquidation);
+                    position.price = Decimal::ZERO;
+                    position.size = Decimal::ZERO;
+                }
+                canceled_orders.extend(positions.orders.drain().map(|(_, mut order)| {
+                    order.status = OrderStatus::Canceled;
+                    order
+                }));
+                positions.groups.clear();
+                profit += symbol_profit;
+                position_events.extend(positions.position_events(
+                    snapshot,
+                    PositionReason::Liquidation,
+                    time,
+                ));
+            }
+
+            account.cash += profit;
+
+            drop(account);
+            for order in canceled_orders {
+                self.order_archive(order);
+            }
+            let mut ledgers = self.ledgers.lock();
+            for liquidation in liquidations.iter() {
+                ledgers
+                    .get_mut(&liquidation.symbol)
+                    .unwrap()
+                    .liquidations
+                    .push(liquidation.clone());
+            }
+
+            (liquidations, position_events)
+        };
+
+        for liquidation in liquidations {
+            self.on_liquidation(liquidation).await?;
+        }
+        for event in position_events {
+            self.on_position(event).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 按标记价格结算资金费用
+    pub(crate) async fn settle_funding(
+        self: &Arc<Self>,
+        symbol: &str,
+        rate: Decimal,
+    ) -> Result<()> {
+        let time = self.time();
+        let (fundings, position_events) = {
+            let mut account = self.account.lock();
+            let positions = account.positions.get_mut(symbol).unwrap();
+            let snapshot = positions.position_snapshot();
+            let mark_price = positions.symbol.market.mark;
+
+            let fundings = [&positions.long, &positions.short]
+                .into_iter()
+                .filter(|position| position.size > Decimal::ZERO)
+                .map(|position| Funding::new(symbol, position, rate, mark_price, time))
+                .collect::<Vec<Funding>>();
+
+            // 逐仓持仓的资金费用由对应方向的逐仓保证金承担
+            let fee = fundings.iter().map(|funding| funding.fee).sum::<Decimal>();
+            if positions.is_isolated() {
+                for funding in fundings.iter() {
+                    let position = positions.position_mut(funding.side);
+                    position.margin_isolated =
+                        (position.margin_isolated + funding.fee).max(Decimal::ZERO);
+                }
+            }
+            let position_events =
+                positions.position_events(snapshot, PositionReason::Funding, time);
+            account.cash += fee;
+            self.ledgers
+                .lock()
+                .get_mut(symbol)
+                .unwrap()
+                .fundings
+                .extend(fundings.iter().cloned());
+
+            (fundings, position_events)
+        };
+
+        for funding in fundings {
+            self.on_funding(funding).await?;
+        }
+        for event in position_events {
+            self.on_position(event).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 撮合交易对的订单
+    /// <br> 条件单先按触发价格类型对应K线([`mark_kline`]缺失时使用最新价格K线)的最高价与最低价判断是否触发,
+    /// <br> 触发后进入已提交状态, 与普通订单一同撮合. K线路径撮合时自触发位置起撮合, 不会以触发之前的价格成交
+    async fn cross_order(
+        self: &Arc<Self>,
+        symbol: &str,
+        kline: &Kline,
+        mark_kline: Option<&Kline>,
+    ) -> Result<()> {
+        let (triggered_orders, orders, position_events) = {
             let price_last = self.price_last(symbol);
             let mut account = self.account.lock();
             let positions = account.positions.get_mut(symbol).unwrap();
             let mut fee = Decimal::ZERO;
             let mut profit = Decimal::ZERO;
 
-            let cross_order_ids = positions
+            let matcher = KlineMatcher::new(kline, self.config.kline_path);
+            let mut triggered_orders = Vec::new();
+            // 本根K线触发的条件单在最新价格路径上的触发位置与价格, 最新价格未到达触发价格时为收盘
+            let mut trigger_points = HashMap::new();
+            for order in positions
+                .orders
+                .values_mut()
+                .filter(|order| order.is_pending_trigger())
+            {
+                let trigger_kline = match order.working_type {
+                    WorkingType::Last => kline,
+                    WorkingType::Mark => mark_kline.unwrap_or(kline),
+                };
+                let stop_price = order.stop_price;
+                if order.trigger(trigger_kline) {
+                    order.status = OrderStatus::Submitted;
+                    // 跟踪止损单的触发价格随极值移动时, 在到达极值之后回调到触发价格
+                    let rising = order.is_trigger_rising();
+                    let from = if order.stop_price != stop_price {
+                        matcher
+                            .touch(if rising { kline.low } else { kline.high }, !rising)
+                            .map_or(Decimal::ZERO, |(position, _)| position)
+                    } else {
+                        Decimal::ZERO
+                    };
+                    let point = matcher
+                        .touch_from(order.stop_price, rising, from)
+                        .unwrap_or((matcher.end(), matcher.close()));
+                    trigger_points.insert(order.id.clone(), point);
+                    triggered_orders.push(order.clone());
+                }
+            }
+
+            let mut cross_order_ids = positions
                 .orders
                 .par_iter_mut()
                 .filter_map(|(id, order)| {
-                    let corss = match order.r#type {
-                        TradeType::Limit => match order.side {
-                            TradeSide::Long => {
-                                if order.reduce_only {
-                                    order.price <= price_last
-                                } else {
-                                    order.price >= price_last
-                                }
-                            }
-                            TradeSide::Short => {
-                                if order.reduce_only {
+                    if order.is_pending_trigger() {
+                        return None;
+                    }
+                    let trigger_point = trigger_points.get(id).copied();
+                    let triggered = trigger_point.is_some();
+                    let cross = match self.config.match_mode {
+                        MatchMode::Close => {
+                            let cross = if order.r#type.is_limit() {
+                                if order.is_buy() {
                                     order.price >= price_last
                                 } else {
                                     order.price <= price_last
                                 }
+                            } else {
+                                true
+                            };
+                            cross.then_some((Decimal::ZERO, price_last))
+                        }
+                        MatchMode::Ohlc => match trigger_point {
+                            // 本根K线触发的条件单自触发位置起撮合: 市价条件单以触发价格成交, 限价条件单在触发之后到达限价时成交
+                            Some((position, price)) => {
+                                if order.r#type.is_limit() {
+                                    matcher.touch_limit_from(order.is_buy(), order.price, position)
+                                } else {
+                                    Some((position, price))
+                                }
+                            }
+                            None if order.r#type.is_limit() => {
+                                matcher.touch_limit(order.is_buy(), order.price)
                             }
+                            None => Some((Decimal::ZERO, matcher.open())),
                         },
-                        TradeType::Market => true,
                     };
-                    if corss {
-                        Some(id.to_owned())
-                    } else {
-                        if order.status == OrderStatus::Created {
-                            order.status = OrderStatus::Submitted;
+                    match cross {
+                        Some((position, price)) => {
+                            // 市价单、本根K线触发的条件单及本根K线开始后排队的订单在开盘时即可成交视为吃单,
+                            // 其余情况为K线开始前已挂出的挂单
+                            let is_taker = order.r#type.is_market()
+                                || triggered
+                                || (order.queue_time >= kline.open_time && position.is_zero());
+                            Some((position, order.queue_time, id.to_owned(), price, is_taker))
+                        }
+                        None => {
+                            // 立即成交类订单保持已创建状态, 在本轮撮合结束后取消
+                            if order.status == OrderStatus::Created && !order.is_immediate() {
+                                order.status = OrderStatus::Submitted;
+                            }
+                            None
                         }
-                        None
                     }
                 })
-                .collect::<Vec<String>>();
-
+                .collect::<Vec<(Decimal, DateTime<Utc>, String, Decimal, bool)>>();
+            cross_order_ids.sort_by_key(|a| (a.0, a.1));
+
+            // 本根K线剩余可成交数量
+            let mut capacity = self
+                .config
+                .participation_rate
+                .map(|participation_rate| kline.size * participation_rate);
+
+            let time = self.time();
+            let mut trades = Vec::new();
+            let mut position_events = Vec::new();
             let mut cross_orders = Vec::with_capacity(cross_order_ids.len());
-            for id in cross_order_ids {
-                let mut order = positions.orders.remove(&id).unwrap();
+            for (_, _, id, price, is_taker) in cross_order_ids {
+                // 同组订单成交后已被撤销
+                let Some(order) = positions.orders.get(&id) else {
+                    continue;
+                };
+                // 全部平仓订单按成交时的持仓数量平仓
+                if order.close_position {
+                    let size = order.deal_size + positions.position(order.side).size;
+                    positions.orders.get_mut(&id).unwrap().size = size;
+                }
 
-                order.deal_fee = order.size
-                    * price_last
-                    * if order.status == OrderStatus::Created {
+                let order = &positions.orders[&id];
+                if order.time_in_force == TimeInForce::Gtx && is_taker {
+                    cross_orders.extend(positions.order_finish(&id, OrderStatus::Rejected));
+                    continue;
+                }
+                let remaining = order.size - order.deal_size;
+                let size = match capacity {
+                    Some(capacity) => remaining.min(capacity),
+                    None => remaining,
+                };
+                if size.is_zero() {
+                    if order.is_immediate() {
+                        cross_orders.extend(positions.order_finish(&id, OrderStatus::Canceled));
+                    } else if order.status == OrderStatus::Created {
+                        positions.orders.get_mut(&id).unwrap().status = OrderStatus::Submitted;
+                    }
+                    continue;
+                }
+
+                let size = positions.fill_size(order, size);
+                if size.is_zero() || (order.time_in_force == TimeInForce::Fok && size < remaining) {
+                    cross_orders.extend(positions.order_finish(&id, OrderStatus::Canceled));
+                    continue;
+                }
+                if let Some(capacity) = capacity.as_mut() {
+                    *capacity -= size;
+                }
+
+                let slippage = if is_taker {
+                    self.config.slippage_model.slippage(
+                        self.config.slippage_rate,
+                        order.is_buy(),
+                        price,
+                        size,
+                        kline,
+                    )
+                } else {
+                    Decimal::ZERO
+                };
+                let deal_price = if order.is_buy() {
+                    price + slippage
+                } else {
+                    price - slippage
+                };
+                let deal_fee = size
+                    * deal_price
+                    * if is_taker {
                         self.config.fee_rate_taker
                     } else {
                         self.config.fee_rate_maker
                     };
-                fee += order.deal_fee;
-
-                if order.reduce_only {
-                    match order.side {
-                        TradeSide::Long => {
-                            profit += (price_last - positions.long.price) * order.size;
-                            positions.long.size -= order.size;
-                            if positions.long.size == Decimal::ZERO {
-                                positions.long.price = Decimal::ZERO;
-                            }
-                        }
-                        TradeSide::Short => {
-                            profit += (positions.short.price - price_last) * order.size;
-                            positions.short.size -= order.size;
-                            if positions.short.size == Decimal::ZERO {
-                                positions.short.price = Decimal::ZERO;
-                            }
-                        }
-                    };
-                } else {
-                    match order.side {
-                        TradeSide::Long => {
-                            positions.long.price = (order.price * order.size
-                                + positions.long.price * positions.long.size)
-                                / (order.size + positions.long.size);
-                            positions.long.size += order.size;
-                        }
-                        TradeSide::Short => {
-                            positions.short.price = (order.price * order.size
-                                + positions.short.price * positions.short.size)
-                                / (order.size + positions.short.size);
-                            positions.short.size += order.size;
-                        }
+
+                let mut order = positions.orders.remove(&id).unwrap();
+                let snapshot = positions.position_snapshot();
+                let pnl = positions.fill(&mut order, deal_price, size, deal_fee);
+                position_events.extend(positions.position_events(
+                    snapshot,
+                    PositionReason::Trade,
+                    time,
+                ));
+                fee += deal_fee;
+                profit += pnl;
+                order.deal_slippage += slippage * size;
+                trades.push(Trade {
+                    symbol: symbol.to_owned(),
+                    order_id: order.id.clone(),
+                    side: order.side,
+                    reduce_only: order.reduce_only,
+                    price: deal_price,
+                    size,
+                    fee: deal_fee,
+                    is_maker: !is_taker,
+                    pnl,
+                    time,
+                });
+
+                if order.status == OrderStatus::PartialFilled {
+                    if order.reduce_only && positions.position(order.side).size.is_zero() {
+                        // 只减仓单超出当前持仓的部分撤销
+                        order.status = OrderStatus::Canceled;
+                    } else if order.time_in_force == TimeInForce::Ioc {
+                        order.status = OrderStatus::Canceled;
+                    } else {
+                        positions.orders.insert(id, order.clone());
                     }
                 }
 
-                order.status = OrderStatus::Filled;
-                order.deal_price = price_last;
-                order.deal_size = order.size;
-
+                let canceled_orders = positions.group_update(&order);
                 cross_orders.push(order);
+                cross_orders.extend(canceled_orders);
+            }
+
+            // 首次撮合未能成交的立即成交类订单取消
+            let expired_ids = positions
+                .orders
+                .iter()
+                .filter(|(id, order)| {
+                    order.is_immediate()
+                        && ((order.status == OrderStatus::Created && !order.is_pending_trigger())
+                            || trigger_points.contains_key(*id))
+                })
+                .map(|(id, _)| id.to_owned())
+                .collect::<Vec<String>>();
+            for id in expired_ids {
+                cross_orders.extend(positions.order_finish(&id, OrderStatus::Canceled));
             }
 
             account.cash -= fee;
             account.cash += profit;
 
-            cross_orders
+            let mut ledgers = self.ledgers.lock();
+            let ledger = ledgers.get_mut(symbol).unwrap();
+            ledger.trades.extend(trades);
+            ledger.orders.extend(
+                cross_orders
+                    .iter()
+                    .filter(|order| order.status != OrderStatus::PartialFilled)
+                    .cloned(),
+            );
+
+            (triggered_orders, cross_orders, position_events)
         };
 
+        for order in triggered_orders {
+            self.on_order(order).await?;
+        }
         for order in orders {
             self.on_order(order).await?;
         }
+        for event in position_events {
+            self.on_position(event).await?;
+        }
 
         Ok(())
     }
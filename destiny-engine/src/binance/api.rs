@@ -0,0 +1,579 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use destiny_types::prelude::*;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::Deserialize;
+
+pub fn trade_type_to_binance(r#type: TradeType) -> &'static str {
+    match r#type {
+        TradeType::Limit => "LIMIT",
+        TradeType::Market => "MARKET",
+        TradeType::StopMarket => "STOP_MARKET",
+        TradeType::StopLimit => "STOP",
+        TradeType::TakeProfitMarket => "TAKE_PROFIT_MARKET",
+        TradeType::TakeProfitLimit => "TAKE_PROFIT",
+        TradeType::TrailingStopMarket => "TRAILING_STOP_MARKET",
+    }
+}
+
+pub fn trade_type_from_binance(r#type: &str) -> Result<TradeType> {
+    Ok(match r#type {
+        "LIMIT" => TradeType::Limit,
+        "MARKET" => TradeType::Market,
+        "STOP_MARKET" => TradeType::StopMarket,
+        "STOP" => TradeType::StopLimit,
+        "TAKE_PROFIT_MARKET" => TradeType::TakeProfitMarket,
+        "TAKE_PROFIT" => TradeType::TakeProfitLimit,
+        "TRAILING_STOP_MARKET" => TradeType::TrailingStopMarket,
+        _ => return Err(anyhow!("未知的币安订单类型: {}", r#type)),
+    })
+}
+
+pub fn time_in_force_to_binance(time_in_force: TimeInForce) -> &'static str {
+    match time_in_force {
+        TimeInForce::Gtc => "GTC",
+        TimeInForce::Ioc => "IOC",
+        TimeInForce::Fok => "FOK",
+        TimeInForce::Gtx => "GTX",
+    }
+}
+
+pub fn time_in_force_from_binance(time_in_force: &str) -> TimeInForce {
+    match time_in_force {
+        "IOC" => TimeInForce::Ioc,
+        "FOK" => TimeInForce::Fok,
+        "GTX" => TimeInForce::Gtx,
+        _ => TimeInForce::Gtc,
+    }
+}
+
+/// 币安持仓方向: 双向持仓为LONG/SHORT, 单向持仓为BOTH
+pub fn position_side_to_binance(mode: PositionMode, side: TradeSide) -> &'static str {
+    match (mode, side) {
+        (PositionMode::OneWay, _) => "BOTH",
+        (PositionMode::Hedge, TradeSide::Long) => "LONG",
+        (PositionMode::Hedge, TradeSide::Short) => "SHORT",
+    }
+}
+
+pub fn time_from_millis(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinanceExchangeInfo {
+    pub symbols: Vec<BinanceSymbolInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinanceSymbolInfo {
+    pub symbol: String,
+    pub status: String,
+    pub filters: Vec<BinanceSymbolFilter>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "filterType")]
+pub enum BinanceSymbolFilter {
+    #[serde(rename = "PRICE_FILTER", rename_all = "camelCase")]
+    Price {
+        min_price: Decimal,
+        max_price: Decimal,
+        tick_size: Decimal,
+    },
+    #[serde(rename = "LOT_SIZE", rename_all = "camelCase")]
+    LotSize {
+        min_qty: Decimal,
+        max_qty: Decimal,
+        step_size: Decimal,
+    },
+    #[serde(rename = "MIN_NOTIONAL")]
+    MinNotional { notional: Decimal },
+    #[serde(rename = "MAX_NUM_ORDERS")]
+    MaxNumOrders { limit: i64 },
+    #[serde(other)]
+    Other,
+}
+
+impl BinanceSymbolInfo {
+    /// 交易规则, 未提供的过滤器保留[`rule`]中的值
+    pub fn rule(&self, mut rule: SymbolRule) -> SymbolRule {
+        for filter in self.filters.iter() {
+            match filter {
+                BinanceSymbolFilter::Price {
+                    min_price,
+                    max_price,
+                    tick_size,
+                } => {
+                    rule.price_min = *min_price;
+                    rule.price_max = *max_price;
+                    rule.price_tick = *tick_size;
+                }
+                BinanceSymbolFilter::LotSize {
+                    min_qty,
+                    max_qty,
+                    step_size,
+                } => {
+                    rule.size_min = *min_qty;
+                    rule.size_max = *max_qty;
+                    rule.size_tick = *step_size;
+                }
+                BinanceSymbolFilter::MinNotional { notional } => rule.amount_min = *notional,
+                BinanceSymbolFilter::MaxNumOrders { limit } => rule.order_max = *limit,
+                BinanceSymbolFilter::Other => {}
+            }
+        }
+        rule
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinanceLeverageBracket {
+    pub symbol: String,
+    pub brackets: Vec<BinanceBracket>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinanceBracket {
+    pub initial_leverage: u32,
+    pub notional_cap: Decimal,
+    pub maint_margin_ratio: Decimal,
+    pub cum: Decimal,
+}
+
+impl BinanceLeverageBracket {
+    pub fn margin_tiers(&self) -> Vec<MarginTier> {
+        self.brackets
+            .iter()
+            .map(|bracket| {
+                MarginTier::new(
+                    bracket.notional_cap,
+                    bracket.initial_leverage,
+                    bracket.maint_margin_ratio,
+                    bracket.cum,
+                )
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinancePositionMode {
+    pub dual_side_position: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinanceAccount {
+    pub assets: Vec<BinanceAsset>,
+    pub positions: Vec<BinancePosition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinanceAsset {
+    pub asset: String,
+    pub wallet_balance: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinancePosition {
+    pub symbol: String,
+    pub position_side: String,
+    pub position_amt: Decimal,
+    pub entry_price: Decimal,
+    pub leverage: Decimal,
+    pub isolated: bool,
+    pub isolated_wallet: Decimal,
+}
+
+impl BinancePosition {
+    /// 按持仓方向与持仓数量写入交易对持仓, 单向持仓按数量正负区分多空
+    pub fn apply(&self, position: &mut SymbolPosition) {
+        position.leverage = self.leverage.to_u32().unwrap_or(1).max(1);
+        position.margin_mode = if self.isolated {
+            MarginMode::Isolated
+        } else {
+            MarginMode::Cross
+        };
+        position_apply(
+            position,
+            &self.position_side,
+            self.position_amt,
+            self.entry_price,
+            self.isolated_wallet,
+        );
+    }
+}
+
+/// 写入持仓数量, 均价与逐仓保证金
+/// <br> 单向持仓的逐仓保证金写入持仓数量所在方向(无持仓时为多头), 另一方向清零
+pub fn position_apply(
+    position: &mut SymbolPosition,
+    position_side: &str,
+    amount: Decimal,
+    price: Decimal,
+    isolated_wallet: Decimal,
+) {
+    let sides = match position_side {
+        "LONG" => vec![(TradeSide::Long, amount.abs(), isolated_wallet)],
+        "SHORT" => vec![(TradeSide::Short, amount.abs(), isolated_wallet)],
+        _ => {
+            let (long_wallet, short_wallet) = if amount >= Decimal::ZERO {
+                (isolated_wallet, Decimal::ZERO)
+            } else {
+                (Decimal::ZERO, isolated_wallet)
+            };
+            vec![
+                (TradeSide::Long, amount.max(Decimal::ZERO), long_wallet),
+                (TradeSide::Short, (-amount).max(Decimal::ZERO), short_wallet),
+            ]
+        }
+    };
+    for (side, size, margin_isolated) in sides {
+        let position = position.position_mut(side);
+        position.size = size;
+        position.price = if size.is_zero() { Decimal::ZERO } else { price };
+        position.margin_isolated = margin_isolated;
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinancePremiumIndex {
+    pub symbol: String,
+    pub mark_price: Decimal,
+    pub index_price: Decimal,
+    pub last_funding_rate: Decimal,
+    pub next_funding_time: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinanceListenKey {
+    pub listen_key: String,
+}
+
+/// 币安订单, REST接口与用户数据流的订单更新均转换为该格式
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinanceOrder {
+    pub symbol: String,
+    pub client_order_id: String,
+    pub status: String,
+    pub r#type: String,
+    pub orig_type: String,
+    pub side: String,
+    pub position_side: String,
+    pub reduce_only: bool,
+    pub close_position: bool,
+    pub time_in_force: String,
+    pub price: Decimal,
+    pub orig_qty: Decimal,
+    pub executed_qty: Decimal,
+    pub avg_price: Decimal,
+    pub stop_price: Decimal,
+    pub working_type: String,
+    /// 跟踪止损回调比例, 单位为百分比
+    #[serde(default)]
+    pub price_rate: Option<Decimal>,
+    #[serde(default)]
+    pub time: i64,
+    pub update_time: i64,
+}
+
+impl BinanceOrder {
+    /// 转换为订单, 条件单触发前为[`OrderStatus::Created`]
+    /// <br> 成交手续费与已实现盈亏由成交推送累计, 不在此处设置
+    pub fn to_order(&self) -> Result<Order> {
+        let r#type = trade_type_from_binance(&self.orig_type)?;
+        let time_in_force = time_in_force_from_binance(&self.time_in_force);
+        let triggered = self.r#type != self.orig_type;
+        let status = match self.status.as_str() {
+            "NEW" if r#type.is_conditional() && !triggered => OrderStatus::Created,
+            "PARTIALLY_FILLED" => OrderStatus::PartialFilled,
+            "FILLED" => OrderStatus::Filled,
+            "CANCELED" | "EXPIRED_IN_MATCH" => OrderStatus::Canceled,
+            "EXPIRED" if time_in_force == TimeInForce::Gtx => OrderStatus::Rejected,
+            "EXPIRED" => OrderStatus::Canceled,
+            "REJECTED" => OrderStatus::Rejected,
+            _ => OrderStatus::Submitted,
+        };
+        let is_buy = self.side == "BUY";
+        let reduce_only = match self.position_side.as_str() {
+            "LONG" => !is_buy,
+            "SHORT" => is_buy,
+            _ => self.reduce_only || self.close_position,
+        };
+        let side = if is_buy != reduce_only {
+            TradeSide::Long
+        } else {
+            TradeSide::Short
+        };
+        let create_time = if self.time > 0 {
+            self.time
+        } else {
+            self.update_time
+        };
+
+        Ok(Order {
+            id: self.client_order_id.clone(),
+            client_id: self.client_order_id.clone(),
+            group_id: None,
+            symbol: self.symbol.clone(),
+            r#type,
+            side,
+            reduce_only,
+            close_position: self.close_position,
+            time_in_force,
+            stop_price: self.stop_price,
+            working_type: if self.working_type == "MARK_PRICE" {
+                WorkingType::Mark
+            } else {
+                WorkingType::Last
+            },
+            callback_rate: self.price_rate.unwrap_or_default() / Decimal::ONE_HUNDRED,
+            status,
+            price: self.price,
+            size: self.orig_qty,
+            deal_price: self.avg_price,
+            deal_size: self.executed_qty,
+            deal_fee: Decimal::ZERO,
+            deal_slippage: Decimal::ZERO,
+            deal_pnl: Decimal::ZERO,
+            create_time: time_from_millis(create_time),
+            queue_time: time_from_millis(self.update_time),
+        })
+    }
+}
+
+/// 组合数据流消息
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinanceStreamMessage {
+    pub stream: String,
+    pub data: BinanceEvent,
+}
+
+/// 行情与用户数据流推送
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "e")]
+pub enum BinanceEvent {
+    #[serde(rename = "kline")]
+    Kline {
+        #[serde(rename = "k")]
+        kline: BinanceKline,
+    },
+    #[serde(rename = "markPriceUpdate")]
+    MarkPrice {
+        #[serde(rename = "s")]
+        symbol: String,
+        #[serde(rename = "p")]
+        mark_price: Decimal,
+        #[serde(rename = "i")]
+        index_price: Decimal,
+        #[serde(rename = "r")]
+        funding_rate: Decimal,
+        #[serde(rename = "T")]
+        next_funding_time: i64,
+    },
+    #[serde(rename = "ORDER_TRADE_UPDATE")]
+    OrderUpdate {
+        #[serde(rename = "T")]
+        time: i64,
+        #[serde(rename = "o")]
+        order: Box<BinanceOrderUpdate>,
+    },
+    #[serde(rename = "ACCOUNT_UPDATE")]
+    AccountUpdate {
+        #[serde(rename = "T")]
+        time: i64,
+        #[serde(rename = "a")]
+        account: BinanceAccountUpdate,
+    },
+    #[serde(rename = "listenKeyExpired")]
+    ListenKeyExpired,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinanceKline {
+    #[serde(rename = "t")]
+    pub open_time: i64,
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "o")]
+    pub open: Decimal,
+    #[serde(rename = "h")]
+    pub high: Decimal,
+    #[serde(rename = "l")]
+    pub low: Decimal,
+    #[serde(rename = "c")]
+    pub close: Decimal,
+    #[serde(rename = "v")]
+    pub size: Decimal,
+    #[serde(rename = "q")]
+    pub cash: Decimal,
+    #[serde(rename = "V")]
+    pub buy_size: Decimal,
+    #[serde(rename = "Q")]
+    pub buy_cash: Decimal,
+    #[serde(rename = "n")]
+    pub trades: i64,
+    /// 是否已收盘
+    #[serde(rename = "x")]
+    pub closed: bool,
+}
+
+impl BinanceKline {
+    pub fn to_kline(&self) -> Kline {
+        Kline {
+            symbol: self.symbol.clone(),
+            open_time: time_from_millis(self.open_time),
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            size: self.size,
+            cash: self.cash,
+            buy_size: self.buy_size,
+            buy_cash: self.buy_cash,
+            trades: self.trades,
+            interval: KlineInterval::M1,
+        }
+    }
+}
+
+/// 用户数据流的订单更新
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinanceOrderUpdate {
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "c")]
+    pub client_order_id: String,
+    #[serde(rename = "S")]
+    pub side: String,
+    #[serde(rename = "o")]
+    pub r#type: String,
+    #[serde(rename = "ot")]
+    pub orig_type: String,
+    #[serde(rename = "f")]
+    pub time_in_force: String,
+    #[serde(rename = "q")]
+    pub orig_qty: Decimal,
+    #[serde(rename = "p")]
+    pub price: Decimal,
+    #[serde(rename = "ap")]
+    pub avg_price: Decimal,
+    #[serde(rename = "sp")]
+    pub stop_price: Decimal,
+    /// 本次更新的执行类型, 成交为TRADE
+    #[serde(rename = "x")]
+    pub execution_type: String,
+    #[serde(rename = "X")]
+    pub status: String,
+    /// 本次成交数量
+    #[serde(rename = "l")]
+    pub last_qty: Decimal,
+    /// 累计成交数量
+    #[serde(rename = "z")]
+    pub executed_qty: Decimal,
+    /// 本次成交价格
+    #[serde(rename = "L")]
+    pub last_price: Decimal,
+    /// 本次成交手续费
+    #[serde(rename = "n", default)]
+    pub commission: Decimal,
+    #[serde(rename = "T")]
+    pub trade_time: i64,
+    #[serde(rename = "m")]
+    pub is_maker: bool,
+    #[serde(rename = "R")]
+    pub reduce_only: bool,
+    #[serde(rename = "wt")]
+    pub working_type: String,
+    #[serde(rename = "ps")]
+    pub position_side: String,
+    #[serde(rename = "cp", default)]
+    pub close_position: bool,
+    #[serde(rename = "cr", default)]
+    pub price_rate: Option<Decimal>,
+    /// 本次成交的已实现盈亏
+    #[serde(rename = "rp", default)]
+    pub realized_profit: Decimal,
+}
+
+impl BinanceOrderUpdate {
+    pub fn is_trade(&self) -> bool {
+        self.execution_type == "TRADE"
+    }
+
+    /// 强平与自动减仓订单
+    pub fn is_liquidation(&self) -> bool {
+        self.client_order_id.starts_with("autoclose-") || self.client_order_id.starts_with("adl_")
+    }
+
+    pub fn to_binance_order(&self) -> BinanceOrder {
+        BinanceOrder {
+            symbol: self.symbol.clone(),
+            client_order_id: self.client_order_id.clone(),
+            status: self.status.clone(),
+            r#type: self.r#type.clone(),
+            orig_type: self.orig_type.clone(),
+            side: self.side.clone(),
+            position_side: self.position_side.clone(),
+            reduce_only: self.reduce_only,
+            close_position: self.close_position,
+            time_in_force: self.time_in_force.clone(),
+            price: self.price,
+            orig_qty: self.orig_qty,
+            executed_qty: self.executed_qty,
+            avg_price: self.avg_price,
+            stop_price: self.stop_price,
+            working_type: self.working_type.clone(),
+            price_rate: self.price_rate,
+            time: 0,
+            update_time: self.trade_time,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinanceAccountUpdate {
+    /// 更新原因, 如ORDER、FUNDING_FEE
+    #[serde(rename = "m")]
+    pub reason: String,
+    #[serde(rename = "B")]
+    pub balances: Vec<BinanceBalanceUpdate>,
+    #[serde(rename = "P")]
+    pub positions: Vec<BinancePositionUpdate>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinanceBalanceUpdate {
+    #[serde(rename = "a")]
+    pub asset: String,
+    #[serde(rename = "wb")]
+    pub wallet_balance: Decimal,
+    /// 除盈亏与手续费外的余额变化, 如资金费用
+    #[serde(rename = "bc")]
+    pub balance_change: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BinancePositionUpdate {
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "pa")]
+    pub position_amt: Decimal,
+    #[serde(rename = "ep")]
+    pub entry_price: Decimal,
+    #[serde(rename = "mt")]
+    pub margin_type: String,
+    #[serde(rename = "iw")]
+    pub isolated_wallet: Decimal,
+    #[serde(rename = "ps")]
+    pub position_side: String,
+}
@@ -0,0 +1,196 @@
+use crate::error::BinanceError;
+use anyhow::Result;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{Method, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize};
+use sha2::Sha256;
+use std::{
+    sync::atomic::{AtomicI64, Ordering},
+    time::Duration as StdDuration,
+};
+use tokio::time::sleep;
+
+/// 接口鉴权方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinanceSecurity {
+    /// 公开接口
+    None,
+    /// 仅需API Key, 如用户数据流的listenKey接口
+    ApiKey,
+    /// 需API Key与HMAC SHA256签名, 如下单与账户接口
+    Signed,
+}
+
+/// 币安U本位合约REST接口客户端
+/// <br> 限流(429/418)时总是重试; 超时与服务端错误仅对查询、修改、撤销等可重复执行的请求重试, 下单请求由调用方确认结果
+/// <br> 时间戳超出接收窗口时同步服务器时间后重试
+pub struct BinanceClient {
+    http: reqwest::Client,
+    rest_url: String,
+    api_key: String,
+    api_secret: String,
+    recv_window: u64,
+    retry: usize,
+    time_offset: AtomicI64,
+}
+
+#[derive(Deserialize)]
+struct BinanceErrorBody {
+    code: i64,
+    msg: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerTime {
+    server_time: i64,
+}
+
+impl BinanceClient {
+    pub fn new(
+        rest_url: &str,
+        api_key: &str,
+        api_secret: &str,
+        recv_window: u64,
+        timeout: StdDuration,
+        retry: usize,
+    ) -> Result<Self> {
+        Ok(Self {
+            http: reqwest::Client::builder().timeout(timeout).build()?,
+            rest_url: rest_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+            recv_window,
+            retry,
+            time_offset: AtomicI64::new(0),
+        })
+    }
+
+    /// 同步服务器时间, 之后的签名请求使用服务器时间作为时间戳
+    pub async fn time_sync(&self) -> Result<()> {
+        let local = Utc::now().timestamp_millis();
+        let server = self
+            .send::<ServerTime>(&Method::GET, "/fapi/v1/time", &[], BinanceSecurity::None)
+            .await?
+            .server_time;
+        self.time_offset.store(server - local, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+        security: BinanceSecurity,
+    ) -> Result<T> {
+        let repeatable = method != Method::POST || path == "/fapi/v1/listenKey";
+        let mut attempt = 0;
+        loop {
+            let err = match self.send(&method, path, params, security).await {
+                Ok(result) => return Ok(result),
+                Err(err) => err,
+            };
+            if attempt >= self.retry {
+                return Err(err);
+            }
+            attempt += 1;
+
+            if let Some(binance_err) = err.downcast_ref::<BinanceError>() {
+                if binance_err.is_timestamp() {
+                    self.time_sync().await?;
+                    continue;
+                }
+                let limited = matches!(binance_err.status, 418 | 429) || binance_err.code == -1003;
+                if !(limited || repeatable && binance_err.is_retryable()) {
+                    return Err(err);
+                }
+            } else if let Some(http_err) = err.downcast_ref::<reqwest::Error>() {
+                if !(http_err.is_connect() || repeatable && http_err.is_timeout()) {
+                    return Err(err);
+                }
+            } else {
+                return Err(err);
+            }
+
+            let delay = StdDuration::from_millis(200 * 2u64.pow(attempt as u32 - 1));
+            tracing::warn!("{} {} 请求失败, {:?}后重试: {}", method, path, delay, err);
+            sleep(delay).await;
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: &Method,
+        path: &str,
+        params: &[(&str, String)],
+        security: BinanceSecurity,
+    ) -> Result<T> {
+        let mut query = params
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, url_encode(value)))
+            .collect::<Vec<String>>()
+            .join("&");
+        if security == BinanceSecurity::Signed {
+            let timestamp =
+                Utc::now().timestamp_millis() + self.time_offset.load(Ordering::Relaxed);
+            if !query.is_empty() {
+                query.push('&');
+            }
+            query.push_str(&format!(
+                "recvWindow={}&timestamp={}",
+                self.recv_window, timestamp
+            ));
+            let signature = self.sign(&query);
+            query.push_str(&format!("&signature={}", signature));
+        }
+
+        let mut url = format!("{}{}", self.rest_url, path);
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query);
+        }
+        let mut request = self.http.request(method.clone(), url);
+        if security != BinanceSecurity::None {
+            request = request.header("X-MBX-APIKEY", &self.api_key);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if status != StatusCode::OK {
+            let (code, msg) = match serde_json::from_str::<BinanceErrorBody>(&body) {
+                Ok(err) => (err.code, err.msg),
+                Err(_) => (0, body),
+            };
+            return Err(BinanceError {
+                status: status.as_u16(),
+                code,
+                msg,
+            }
+            .into());
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    fn sign(&self, query: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.api_secret.as_bytes())
+            .expect("HMAC密钥长度不受限制");
+        mac.update(query.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+/// 查询参数编码, 仅保留RFC 3986非保留字符
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -0,0 +1,1659 @@
+use super::{api::*, client::*};
+use crate::{
+    backtest::order_requests_check, error::*, indicator::*, kline::*, snapshot::*, traits::*,
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, DurationRound, Timelike, Utc};
+use derive_builder::Builder;
+use destiny_helpers::prelude::*;
+use destiny_types::prelude::*;
+use futures::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use reqwest::Method;
+use rust_decimal::Decimal;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration as StdDuration,
+};
+use tokio::{
+    sync::{mpsc, watch, Notify},
+    time::{interval, sleep, Instant, MissedTickBehavior},
+};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+/// 币安U本位合约主网REST接口地址
+pub const BINANCE_REST_MAINNET: &str = "https://fapi.binance.com";
+/// 币安U本位合约测试网REST接口地址
+pub const BINANCE_REST_TESTNET: &str = "https://testnet.binancefuture.com";
+/// 币安U本位合约主网数据流地址
+pub const BINANCE_WS_MAINNET: &str = "wss://fstream.binance.com";
+/// 币安U本位合约测试网数据流地址
+pub const BINANCE_WS_TESTNET: &str = "wss://stream.binancefuture.com";
+
+/// 保证金资产
+const QUOTE_ASSET: &str = "USDT";
+
+/// 币安实盘配置
+#[derive(Builder)]
+#[builder(setter(into))]
+pub struct BinanceConfig {
+    /// 运行模式, 仅支持[`RunMode::Testnet`]与[`RunMode::Mainnet`]
+    pub run_mode: RunMode,
+    pub api_key: String,
+    pub api_secret: String,
+    /// REST接口地址, 为空时按运行模式使用币安地址
+    #[builder(default = None)]
+    pub rest_url: Option<String>,
+    /// 数据流地址, 为空时按运行模式使用币安地址
+    #[builder(default = None)]
+    pub ws_url: Option<String>,
+    /// 签名请求的接收窗口(毫秒)
+    #[builder(default = 5000)]
+    pub recv_window: u64,
+    /// 请求超时
+    #[builder(default = StdDuration::from_secs(10))]
+    pub timeout: StdDuration,
+    /// 请求因限流、超时或服务端错误失败时的重试次数
+    #[builder(default = 3)]
+    pub retry: usize,
+    /// 数据流断开后的重连间隔
+    #[builder(default = StdDuration::from_secs(1))]
+    pub reconnect_interval: StdDuration,
+    /// 用户数据流listenKey的续期间隔
+    #[builder(default = StdDuration::from_secs(30 * 60))]
+    pub keepalive_interval: StdDuration,
+    /// 每个交易对每个K线周期缓存的已收盘K线数量, 见[`EngineMarket::klines`]
+    #[builder(default = 1000)]
+    pub kline_history: usize,
+}
+
+/// 数据流任务发送给引擎的事件
+pub(crate) enum StreamEvent {
+    /// 已连接, 重连后引擎重新同步账户与订单
+    Connected,
+    Message(String),
+}
+
+/// 币安U本位合约实盘引擎
+/// <br> 账户、持仓与订单以币安为准: 启动与数据流重连时通过REST接口同步, 运行中由用户数据流推送更新
+/// <br> 订单ID即币安的客户端订单ID, 未指定[`OrderRequest::client_id`]时自动生成
+/// <br> 订单组由引擎维护: 组内订单成交或完成后撤销其余订单, 括号订单的入场订单完成后按成交数量提交止损/止盈订单
+pub struct Binance {
+    config: Arc<BinanceConfig>,
+    client: Arc<BinanceClient>,
+    ws_url: String,
+    account: Arc<Mutex<Account>>,
+    ledgers: Arc<Mutex<HashMap<String, SymbolLedger>>>,
+    /// 已归档的订单ID: 交易对 -> 订单ID, 用于忽略归档后重复推送的订单更新
+    orders_finalized: Arc<Mutex<HashMap<String, HashSet<String>>>>,
+    kline_feed: Arc<Mutex<KlineFeed>>,
+    stopped: AtomicBool,
+    stop_notify: Notify,
+    strategy: Arc<dyn Strategy>,
+}
+
+impl Engine for Binance {
+    fn time(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        self.stop_notify.notify_one();
+    }
+}
+
+impl EngineInit for Binance {
+    fn symbol_init(&self, symbol: &str) -> Result<()> {
+        ensure!(
+            !self.account.lock().positions.contains_key(symbol),
+            "重复初始化交易对: {}",
+            symbol
+        );
+        self.account.lock().positions.insert(
+            symbol.to_string(),
+            SymbolPosition::new(symbol, SymbolRule::default()),
+        );
+        self.ledgers
+            .lock()
+            .insert(symbol.to_string(), Default::default());
+        self.orders_finalized
+            .lock()
+            .insert(symbol.to_string(), Default::default());
+        self.kline_feed.lock().symbol_init(symbol);
+        Ok(())
+    }
+
+    fn symbol_init_with_rule(&self, symbol: &str, _rule: SymbolRule) -> Result<()> {
+        bail!("币安实盘的交易规则以交易所为准, 不支持自定义: {}", symbol)
+    }
+
+    fn kline_subscribe(&self, symbol: &str, intervals: &[KlineInterval]) -> Result<()> {
+        ensure!(
+            self.account.lock().positions.contains_key(symbol),
+            "交易对不存在: {}",
+            symbol
+        );
+        self.kline_feed.lock().subscribe(symbol, intervals)
+    }
+
+    fn indicator_register(
+        &self,
+        symbol: &str,
+        interval: KlineInterval,
+        name: &str,
+        indicator: Indicator,
+    ) -> Result<()> {
+        self.kline_feed
+            .lock()
+            .indicator_register(symbol, interval, name, indicator)
+    }
+}
+
+impl Binance {
+    fn position_map<T: Default>(&self, symbol: &str, f: impl FnOnce(&SymbolPosition) -> T) -> T {
+        self.account
+            .lock()
+            .positions
+            .get(symbol)
+            .map(f)
+            .unwrap_or_default()
+    }
+
+    fn orders_filter(&self, symbol: &str, filter: impl Fn(&Order) -> bool) -> Vec<Order> {
+        self.position_map(symbol, |position| {
+            position
+                .orders
+                .values()
+                .filter(|order| filter(order))
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn ledger_map<T: Default>(&self, symbol: &str, f: impl FnOnce(&SymbolLedger) -> T) -> T {
+        self.ledgers.lock().get(symbol).map(f).unwrap_or_default()
+    }
+}
+
+impl EngineAccount for Binance {
+    fn snapshot(&self) -> AccountSnapshot {
+        AccountSnapshot::new(self.time(), self.account.lock().clone())
+    }
+    fn order(&self, symbol: &str, id: &str) -> Option<Order> {
+        self.position_map(symbol, |position| position.orders.get(id).cloned())
+    }
+    fn orders(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |_| true)
+    }
+    fn orders_long(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| order.side == TradeSide::Long)
+    }
+    fn orders_long_open(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| {
+            order.side == TradeSide::Long && !order.reduce_only
+        })
+    }
+    fn orders_long_close(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| {
+            order.side == TradeSide::Long && order.reduce_only
+        })
+    }
+    fn orders_short(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| order.side == TradeSide::Short)
+    }
+    fn orders_short_open(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| {
+            order.side == TradeSide::Short && !order.reduce_only
+        })
+    }
+    fn orders_short_close(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| {
+            order.side == TradeSide::Short && order.reduce_only
+        })
+    }
+    fn leverage(&self, symbol: &str) -> u32 {
+        self.account
+            .lock()
+            .positions
+            .get(symbol)
+            .map(|position| position.leverage)
+            .unwrap_or(1)
+    }
+    fn cash(&self) -> Decimal {
+        self.account.lock().cash
+    }
+    fn cash_available(&self) -> Decimal {
+        self.account.lock().cash_available()
+    }
+    fn cash_frozen(&self) -> Decimal {
+        self.account.lock().cash_frozen()
+    }
+    fn margin(&self) -> Decimal {
+        self.account.lock().margin()
+    }
+    fn pnl(&self) -> Decimal {
+        self.account.lock().pnl()
+    }
+    fn long_price(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.long.price)
+    }
+    fn long_size(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.long.size)
+    }
+    fn long_size_available(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.long_size_available())
+    }
+    fn long_size_frozen(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.long_size_frozen())
+    }
+    fn long_margin(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.margin_long())
+    }
+    fn long_pnl(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.long_pnl())
+    }
+    fn short_price(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.short.price)
+    }
+    fn short_size(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.short.size)
+    }
+    fn short_size_available(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.short_size_available())
+    }
+    fn short_size_frozen(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.short_size_frozen())
+    }
+    fn short_margin(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.margin_short())
+    }
+    fn short_pnl(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.short_pnl())
+    }
+    fn symbols(&self) -> Vec<String> {
+        self.account.lock().positions.keys().cloned().collect()
+    }
+    fn symbol_pnl(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.pnl())
+    }
+    fn symbol_margin(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.margin())
+    }
+    fn fundings(&self, symbol: &str) -> Vec<Funding> {
+        self.ledger_map(symbol, |ledger| ledger.fundings.clone())
+    }
+    fn funding_fee(&self, symbol: &str) -> Decimal {
+        self.ledger_map(symbol, |ledger| ledger.funding_fee())
+    }
+    fn orders_history(&self, symbol: &str, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Order> {
+        self.ledger_map(symbol, |ledger| ledger.orders_between(begin, end))
+    }
+    fn trades(&self, symbol: &str, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Trade> {
+        self.ledger_map(symbol, |ledger| ledger.trades_between(begin, end))
+    }
+    fn liquidation_price(&self, symbol: &str, side: TradeSide) -> Decimal {
+        self.account.lock().liquidation_price(symbol, side)
+    }
+    fn position_mode(&self, symbol: &str) -> PositionMode {
+        self.account
+            .lock()
+            .positions
+            .get(symbol)
+            .map(|position| position.position_mode)
+            .unwrap_or(PositionMode::Hedge)
+    }
+    fn margin_mode(&self, symbol: &str) -> MarginMode {
+        self.account
+            .lock()
+            .positions
+            .get(symbol)
+            .map(|position| position.margin_mode)
+            .unwrap_or(MarginMode::Cross)
+    }
+    fn margin_isolated(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.margin_isolated())
+    }
+    fn order_group(&self, symbol: &str, id: &str) -> Option<OrderGroup> {
+        self.position_map(symbol, |position| position.groups.get(id).cloned())
+    }
+    fn order_groups(&self, symbol: &str) -> Vec<OrderGroup> {
+        self.position_map(symbol, |position| {
+            position.groups.values().cloned().collect()
+        })
+    }
+}
+
+impl EngineMarket for Binance {
+    fn price_mark(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.symbol.market.mark)
+    }
+    fn price_last(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.symbol.market.last)
+    }
+    fn price_index(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.symbol.market.index)
+    }
+    fn price_settlement(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.symbol.market.settlement)
+    }
+    fn time_settlement(&self, symbol: &str) -> DateTime<Utc> {
+        self.position_map(symbol, |position| position.symbol.market.settlement_time)
+    }
+    fn rule_price_min(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.symbol.rule.price_min)
+    }
+    fn rule_price_max(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.symbol.rule.price_max)
+    }
+    fn rule_price_tick(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.symbol.rule.price_tick)
+    }
+    fn rule_size_min(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.symbol.rule.size_min)
+    }
+    fn rule_size_max(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.symbol.rule.size_max)
+    }
+    fn rule_size_tick(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.symbol.rule.size_tick)
+    }
+    fn rule_amount_min(&self, symbol: &str) -> Decimal {
+        self.position_map(symbol, |position| position.symbol.rule.amount_min)
+    }
+    fn rule_order_max(&self, symbol: &str) -> i64 {
+        self.position_map(symbol, |position| position.symbol.rule.order_max)
+    }
+    fn klines(&self, symbol: &str, interval: KlineInterval, limit: usize) -> Vec<Kline> {
+        self.kline_feed.lock().klines(symbol, interval, limit)
+    }
+    fn indicator(&self, symbol: &str, interval: KlineInterval, name: &str) -> Option<Indicator> {
+        self.kline_feed.lock().indicator(symbol, interval, name)
+    }
+}
+
+/// 由下单请求生成待提交的订单
+fn order_init(request: OrderRequest, id: String, time: DateTime<Utc>) -> Order {
+    Order {
+        client_id: id.clone(),
+        id,
+        group_id: None,
+        symbol: request.symbol,
+        r#type: request.r#type,
+        side: request.side,
+        reduce_only: request.reduce_only,
+        close_position: request.close_position,
+        time_in_force: request.time_in_force,
+        stop_price: request.stop_price,
+        working_type: request.working_type,
+        callback_rate: request.callback_rate,
+        status: OrderStatus::Created,
+        price: request.price,
+        size: request.size,
+        deal_price: Decimal::ZERO,
+        deal_size: Decimal::ZERO,
+        deal_fee: Decimal::ZERO,
+        deal_slippage: Decimal::ZERO,
+        deal_pnl: Decimal::ZERO,
+        create_time: time,
+        queue_time: time,
+    }
+}
+
+/// 下单参数
+/// <br> 双向持仓通过持仓方向区分开平仓, 单向持仓通过只减仓区分
+fn order_params(order: &Order, mode: PositionMode) -> Vec<(&'static str, String)> {
+    let is_buy = (order.side == TradeSide::Long) != order.reduce_only;
+    let mut params = vec![
+        ("symbol", order.symbol.clone()),
+        ("side", if is_buy { "BUY" } else { "SELL" }.to_string()),
+        (
+            "positionSide",
+            position_side_to_binance(mode, order.side).to_string(),
+        ),
+        ("type", trade_type_to_binance(order.r#type).to_string()),
+        ("newClientOrderId", order.id.clone()),
+    ];
+    if order.close_position {
+        params.push(("closePosition", "true".to_string()));
+    } else {
+        params.push(("quantity", order.size.normalize().to_string()));
+        if order.reduce_only && mode == PositionMode::OneWay {
+            params.push(("reduceOnly", "true".to_string()));
+        }
+    }
+    if order.r#type.is_limit() {
+        params.push(("price", order.price.normalize().to_string()));
+        params.push((
+            "timeInForce",
+            time_in_force_to_binance(order.time_in_force).to_string(),
+        ));
+    }
+    if order.r#type.is_conditional() {
+        if order.r#type == TradeType::TrailingStopMarket {
+            params.push((
+                "callbackRate",
+                (order.callback_rate * Decimal::ONE_HUNDRED)
+                    .normalize()
+                    .to_string(),
+            ));
+        } else {
+            params.push(("stopPrice", order.stop_price.normalize().to_string()));
+        }
+        params.push((
+            "workingType",
+            match order.working_type {
+                WorkingType::Last => "CONTRACT_PRICE",
+                WorkingType::Mark => "MARK_PRICE",
+            }
+            .to_string(),
+        ));
+    }
+    params
+}
+
+/// 订单完成后对所属订单组的处理
+#[derive(Default)]
+struct GroupAction {
+    /// 需撤销的同组订单
+    cancels: Vec<String>,
+    /// 括号订单入场后需提交的止损/止盈订单
+    activates: Vec<Order>,
+}
+
+impl Binance {
+    pub fn new(config: BinanceConfig, strategy: Arc<dyn Strategy>) -> Result<Arc<Binance>> {
+        let (rest_url, ws_url) = match config.run_mode {
+            RunMode::Mainnet => (BINANCE_REST_MAINNET, BINANCE_WS_MAINNET),
+            RunMode::Testnet => (BINANCE_REST_TESTNET, BINANCE_WS_TESTNET),
+            RunMode::Backtest | RunMode::Paper => bail!("币安实盘不支持{}模式", config.run_mode),
+        };
+        let rest_url = config.rest_url.as_deref().unwrap_or(rest_url).to_string();
+        let ws_url = config
+            .ws_url
+            .as_deref()
+            .unwrap_or(ws_url)
+            .trim_end_matches('/')
+            .to_string();
+        ensure!(config.kline_history > 0, "K线缓存数量必须大于0");
+
+        let client = BinanceClient::new(
+            &rest_url,
+            &config.api_key,
+            &config.api_secret,
+            config.recv_window,
+            config.timeout,
+            config.retry,
+        )?;
+        let kline_feed = KlineFeed::new(config.kline_history);
+
+        Ok(Arc::new(Binance {
+            config: Arc::new(config),
+            client: Arc::new(client),
+            ws_url,
+            account: Arc::new(Mutex::new(Account {
+                cash: Decimal::ZERO,
+                positions: Default::default(),
+            })),
+            ledgers: Arc::new(Mutex::new(HashMap::new())),
+            orders_finalized: Arc::new(Mutex::new(HashMap::new())),
+            kline_feed: Arc::new(Mutex::new(kline_feed)),
+            stopped: AtomicBool::new(false),
+            stop_notify: Notify::new(),
+            strategy,
+        }))
+    }
+
+    /// 运行直到[`Engine::stop`]
+    pub async fn run(config: BinanceConfig, strategy: Arc<dyn Strategy>) -> Result<()> {
+        Self::new(config, strategy)?.run0().await
+    }
+
+    pub async fn run0(self: &Arc<Self>) -> Result<()> {
+        self.strategy.on_init(self.clone()).await?;
+        ensure!(!self.account.lock().positions.is_empty(), "未初始化交易对");
+
+        self.client.time_sync().await?;
+        self.sync_rules().await?;
+        self.sync().await?;
+
+        let listen_key = self.listen_key_create().await?;
+        let (url_tx, url_rx) = watch::channel(self.stream_url(&listen_key));
+        let (event_tx, mut event_rx) = mpsc::channel(4096);
+        let stream = tokio::spawn(stream_run(url_rx, event_tx, self.config.reconnect_interval));
+
+        self.strategy.on_start(self.clone()).await?;
+
+        let mut keepalive = interval(self.config.keepalive_interval);
+        keepalive.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        keepalive.tick().await;
+        let mut minute = self.time().duration_trunc(Duration::minutes(1))? + Duration::minutes(1);
+        let mut connected = false;
+
+        let result = loop {
+            if self.stopped.load(Ordering::Relaxed) {
+                break Ok(());
+            }
+            let minute_wait = (minute - self.time()).to_std().unwrap_or_default();
+            tokio::select! {
+                _ = self.stop_notify.notified() => {}
+                event = event_rx.recv() => match event {
+                    Some(StreamEvent::Connected) => {
+                        if connected {
+                            tracing::warn!("数据流已重连, 重新同步账户与订单");
+                            if let Err(err) = self.sync().await {
+                                tracing::error!("同步账户与订单失败: {}", err);
+                            }
+                        }
+                        connected = true;
+                    }
+                    Some(StreamEvent::Message(text)) => {
+                        if let Some(listen_key) = self.on_message(&text).await? {
+                            url_tx.send_replace(self.stream_url(&listen_key));
+                        }
+                    }
+                    None => break Err(anyhow!("数据流已关闭")),
+                },
+                _ = keepalive.tick() => {
+                    if let Err(err) = self.listen_key_keepalive().await {
+                        tracing::warn!("listenKey续期失败, 重新创建: {}", err);
+                        let listen_key = self.listen_key_create().await?;
+                        url_tx.send_replace(self.stream_url(&listen_key));
+                    }
+                }
+                _ = tokio::time::sleep_until(Instant::now() + minute_wait) => {
+                    self.on_timer(minute).await;
+                    minute += Duration::minutes(1);
+                }
+            }
+        };
+
+        stream.abort();
+        if let Err(err) = self.strategy.on_stop(self.clone()).await {
+            tracing::error!("停止事件执行失败: {}", err);
+        }
+        result
+    }
+
+    /// 组合数据流地址: 各交易对的1分钟K线与标记价格, 以及用户数据流
+    fn stream_url(&self, listen_key: &str) -> String {
+        let mut streams = self
+            .symbols()
+            .iter()
+            .flat_map(|symbol| {
+                let symbol = symbol.to_lowercase();
+                [
+                    format!("{}@kline_1m", symbol),
+                    format!("{}@markPrice@1s", symbol),
+                ]
+            })
+            .collect::<Vec<String>>();
+        streams.sort();
+        streams.push(listen_key.to_string());
+        format!("{}/stream?streams={}", self.ws_url, streams.join("/"))
+    }
+
+    async fn listen_key_create(&self) -> Result<String> {
+        Ok(self
+            .client
+            .request::<BinanceListenKey>(
+                Method::POST,
+                "/fapi/v1/listenKey",
+                &[],
+                BinanceSecurity::ApiKey,
+            )
+            .await?
+            .listen_key)
+    }
+
+    async fn listen_key_keepalive(&self) -> Result<()> {
+        self.client
+            .request::<serde_json::Value>(
+                Method::PUT,
+                "/fapi/v1/listenKey",
+                &[],
+                BinanceSecurity::ApiKey,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// 同步交易规则与维持保证金档位
+    async fn sync_rules(&self) -> Result<()> {
+        let exchange_info = self
+            .client
+            .request::<BinanceExchangeInfo>(
+                Method::GET,
+                "/fapi/v1/exchangeInfo",
+                &[],
+                BinanceSecurity::None,
+            )
+            .await?;
+        let brackets = self
+            .client
+            .request::<Vec<BinanceLeverageBracket>>(
+                Method::GET,
+                "/fapi/v1/leverageBracket",
+                &[],
+                BinanceSecurity::Signed,
+            )
+            .await?;
+
+        let mut account = self.account.lock();
+        for (symbol, position) in account.positions.iter_mut() {
+            let info = exchange_info
+                .symbols
+                .iter()
+                .find(|info| &info.symbol == symbol)
+                .ok_or(anyhow!("币安不存在交易对: {}", symbol))?;
+            position.symbol.enable = info.status == "TRADING";
+            position.symbol.rule = info.rule(position.symbol.rule.clone());
+            if let Some(bracket) = brackets.iter().find(|bracket| &bracket.symbol == symbol) {
+                position.symbol.rule.margin_tiers = bracket.margin_tiers();
+            }
+        }
+        Ok(())
+    }
+
+    /// 同步资金、持仓、行情与未完成订单
+    /// <br> 本地未完成但币安已完成的订单按最终状态归档并通知
+    async fn sync(self: &Arc<Self>) -> Result<()> {
+        let mode = self
+            .client
+            .request::<BinancePositionMode>(
+                Method::GET,
+                "/fapi/v1/positionSide/dual",
+                &[],
+                BinanceSecurity::Signed,
+            )
+            .await?;
+        let binance_account = self
+            .client
+            .request::<BinanceAccount>(
+                Method::GET,
+                "/fapi/v2/account",
+                &[],
+                BinanceSecurity::Signed,
+            )
+            .await?;
+        let premium_indexes = self
+            .client
+            .request::<Vec<BinancePremiumIndex>>(
+                Method::GET,
+                "/fapi/v1/premiumIndex",
+                &[],
+                BinanceSecurity::None,
+            )
+            .await?;
+
+        {
+            let mut account = self.account.lock();
+            account.cash = binance_account
+                .assets
+                .iter()
+                .find(|asset| asset.asset == QUOTE_ASSET)
+                .map(|asset| asset.wallet_balance)
+                .unwrap_or_default();
+            for (symbol, position) in account.positions.iter_mut() {
+                position.position_mode = if mode.dual_side_position {
+                    PositionMode::Hedge
+                } else {
+                    PositionMode::OneWay
+                };
+                for binance_position in binance_account
+                    .positions
+                    .iter()
+                    .filter(|binance_position| &binance_position.symbol == symbol)
+                {
+                    binance_position.apply(position);
+                }
+                if let Some(index) = premium_indexes.iter().find(|index| &index.symbol == symbol) {
+                    let market = &mut position.symbol.market;
+                    market.mark = index.mark_price;
+                    market.index = index.index_price;
+                    market.settlement = index.last_funding_rate;
+                    market.settlement_time = time_from_millis(index.next_funding_time);
+                }
+            }
+        }
+
+        for symbol in self.symbols() {
+            self.sync_orders(&symbol).await?;
+        }
+        Ok(())
+    }
+
+    async fn sync_orders(self: &Arc<Self>, symbol: &str) -> Result<()> {
+        let open_orders = self
+            .client
+            .request::<Vec<BinanceOrder>>(
+                Method::GET,
+                "/fapi/v1/openOrders",
+                &[("symbol", symbol.to_string())],
+                BinanceSecurity::Signed,
+            )
+            .await?
+            .iter()
+            .map(|order| order.to_order())
+            .collect::<Result<Vec<Order>>>()?;
+
+        let missing = {
+            let mut account = self.account.lock();
+            let position = account.positions.get_mut(symbol).unwrap();
+            let missing = position
+                .orders
+                .keys()
+                .filter(|id| !open_orders.iter().any(|order| &order.id == *id))
+                .cloned()
+                .collect::<Vec<String>>();
+            for order in open_orders {
+                order_merge(position, order);
+            }
+            missing
+        };
+
+        for id in missing {
+            let order = self
+                .client
+                .request::<BinanceOrder>(
+                    Method::GET,
+                    "/fapi/v1/order",
+                    &[
+                        ("symbol", symbol.to_string()),
+                        ("origClientOrderId", id.clone()),
+                    ],
+                    BinanceSecurity::Signed,
+                )
+                .await?
+                .to_order()?;
+            self.order_update(order, None).await;
+        }
+        Ok(())
+    }
+
+    /// 处理数据流消息, 用户数据流失效时返回新的listenKey
+    async fn on_message(self: &Arc<Self>, text: &str) -> Result<Option<String>> {
+        let message = match serde_json::from_str::<BinanceStreamMessage>(text) {
+            Ok(message) => message,
+            Err(err) => {
+                tracing::warn!("无法解析的数据流消息({}): {}", err, text);
+                return Ok(None);
+            }
+        };
+        match message.data {
+            BinanceEvent::Kline { kline } => self.on_kline_event(kline).await,
+            BinanceEvent::MarkPrice {
+                symbol,
+                mark_price,
+                index_price,
+                funding_rate,
+                next_funding_time,
+            } => {
+                if let Some(position) = self.account.lock().positions.get_mut(&symbol) {
+                    let market = &mut position.symbol.market;
+                    market.mark = mark_price;
+                    market.index = index_price;
+                    market.settlement = funding_rate;
+                    market.settlement_time = time_from_millis(next_funding_time);
+                }
+            }
+            BinanceEvent::OrderUpdate { time, order } => {
+                let trade = order.is_trade().then(|| *order.clone());
+                match order.to_binance_order().to_order() {
+                    Ok(mut update) => {
+                        update.queue_time = time_from_millis(time);
+                        self.order_update(update, trade).await;
+                    }
+                    Err(err) => tracing::warn!("无法解析的订单更新: {}", err),
+                }
+            }
+            BinanceEvent::AccountUpdate { time, account } => {
+                self.on_account_event(time_from_millis(time), account).await;
+            }
+            BinanceEvent::ListenKeyExpired => {
+                tracing::warn!("listenKey已失效, 重新创建");
+                return Ok(Some(self.listen_key_create().await?));
+            }
+            BinanceEvent::Other => {}
+        }
+        Ok(None)
+    }
+
+    /// 未收盘的K线仅更新最新价格, 收盘后按订阅的周期聚合并通知
+    async fn on_kline_event(self: &Arc<Self>, kline: BinanceKline) {
+        let closed = kline.closed;
+        let kline = kline.to_kline();
+        {
+            let mut account = self.account.lock();
+            let Some(position) = account.positions.get_mut(&kline.symbol) else {
+                return;
+            };
+            position.symbol.market.last = kline.close;
+            position.symbol.market.time = self.time();
+        }
+        if !closed {
+            return;
+        }
+        let klines = self.kline_feed.lock().push(&kline);
+        for kline in klines {
+            if let Err(err) = self.strategy.on_kline(self.clone(), kline).await {
+                tracing::error!("K线事件执行失败: {}", err);
+            }
+        }
+    }
+
+    async fn on_account_event(self: &Arc<Self>, time: DateTime<Utc>, update: BinanceAccountUpdate) {
+        let reason = match update.reason.as_str() {
+            "FUNDING_FEE" => PositionReason::Funding,
+            _ => PositionReason::Trade,
+        };
+        let (fundings, position_events) = {
+            let mut account = self.account.lock();
+            if let Some(balance) = update
+                .balances
+                .iter()
+                .find(|balance| balance.asset == QUOTE_ASSET)
+            {
+                account.cash = balance.wallet_balance;
+            }
+            let fee = update
+                .balances
+                .iter()
+                .find(|balance| balance.asset == QUOTE_ASSET)
+                .map(|balance| balance.balance_change)
+                .unwrap_or_default();
+
+            // 全仓的资金费用更新不推送持仓, 以本地持仓计算
+            let symbols = if reason == PositionReason::Funding && update.positions.is_empty() {
+                account
+                    .positions
+                    .iter()
+                    .filter(|(_, position)| {
+                        position.long.size > Decimal::ZERO || position.short.size > Decimal::ZERO
+                    })
+                    .map(|(symbol, _)| symbol.clone())
+                    .collect::<BTreeSet<String>>()
+            } else {
+                update
+                    .positions
+                    .iter()
+                    .map(|position| position.symbol.clone())
+                    .collect::<BTreeSet<String>>()
+            };
+
+            let mut fundings = Vec::new();
+            let mut position_events = Vec::new();
+            for symbol in symbols {
+                let Some(position) = account.positions.get_mut(&symbol) else {
+                    continue;
+                };
+                let snapshot = position.position_snapshot();
+                if reason == PositionReason::Funding {
+                    let market = &position.symbol.market;
+                    fundings.extend(
+                        [&position.long, &position.short]
+                            .into_iter()
+                            .filter(|position| position.size > Decimal::ZERO)
+                            .map(|side_position| {
+                                Funding::new(
+                                    &symbol,
+                                    side_position,
+                                    market.settlement,
+                                    market.mark,
+                                    time,
+                                )
+                            }),
+                    );
+                }
+                for binance_position in update
+                    .positions
+                    .iter()
+                    .filter(|binance_position| binance_position.symbol == symbol)
+                {
+                    position.margin_mode = if binance_position.margin_type == "isolated" {
+                        MarginMode::Isolated
+                    } else {
+                        MarginMode::Cross
+                    };
+                    position_apply(
+                        position,
+                        &binance_position.position_side,
+                        binance_position.position_amt,
+                        binance_position.entry_price,
+                        binance_position.isolated_wallet,
+                    );
+                }
+                position_events.extend(position.position_events(snapshot, reason, time));
+            }
+
+            // 单个持仓的资金费用以币安的余额变化为准
+            if let [funding] = fundings.as_mut_slice() {
+                funding.fee = fee;
+            }
+            let mut ledgers = self.ledgers.lock();
+            for funding in fundings.iter() {
+                if let Some(ledger) = ledgers.get_mut(&funding.symbol) {
+                    ledger.fundings.push(funding.clone());
+                }
+            }
+            (fundings, position_events)
+        };
+
+        for funding in fundings {
+            if let Err(err) = self.strategy.on_funding(self.clone(), funding).await {
+                tracing::error!("资金费用事件执行失败: {}", err);
+            }
+        }
+        for event in position_events {
+            if let Err(err) = self.strategy.on_position(self.clone(), event).await {
+                tracing::error!("持仓事件执行失败: {}", err);
+            }
+        }
+    }
+
+    /// 以币安的订单状态更新本地订单, [`trade`]为本次成交
+    /// <br> 已完成的订单归档, 并按所属订单组撤销或提交同组订单
+    async fn order_update(self: &Arc<Self>, update: Order, trade: Option<BinanceOrderUpdate>) {
+        let symbol = update.symbol.clone();
+        let (order, liquidation, action) = {
+            let mut account = self.account.lock();
+            let Some(position) = account.positions.get_mut(&symbol) else {
+                return;
+            };
+            let mut orders_finalized = self.orders_finalized.lock();
+            let finalized = orders_finalized.get_mut(&symbol).unwrap();
+            if finalized.contains(&update.id) {
+                return;
+            }
+            let mut ledgers = self.ledgers.lock();
+            let ledger = ledgers.get_mut(&symbol).unwrap();
+
+            let mut order = update;
+            if let Some(previous) = position.orders.get(&order.id) {
+                order.group_id = previous.group_id.clone();
+                order.deal_fee = previous.deal_fee;
+                order.deal_pnl = previous.deal_pnl;
+                order.create_time = previous.create_time;
+            }
+
+            let mut liquidation = None;
+            if let Some(trade) = trade.as_ref() {
+                order.deal_fee += trade.commission;
+                order.deal_pnl += trade.realized_profit;
+                ledger.trades.push(Trade {
+                    symbol: symbol.clone(),
+                    order_id: order.id.clone(),
+                    side: order.side,
+                    reduce_only: order.reduce_only,
+                    price: trade.last_price,
+                    size: trade.last_qty,
+                    fee: trade.commission,
+                    is_maker: trade.is_maker,
+                    pnl: trade.realized_profit,
+                    time: time_from_millis(trade.trade_time),
+                });
+                if trade.is_liquidation() {
+                    let item = Liquidation {
+                        symbol: symbol.clone(),
+                        side: order.side,
+                        price: trade.last_price,
+                        size: trade.last_qty,
+                        pnl: trade.realized_profit,
+                        fee: trade.commission,
+                        time: time_from_millis(trade.trade_time),
+                    };
+                    ledger.liquidations.push(item.clone());
+                    liquidation = Some(item);
+                }
+            }
+
+            let finished = order_finished(&order);
+            if finished {
+                position.orders.remove(&order.id);
+                finalized.insert(order.id.clone());
+                ledger.orders.push(order.clone());
+            } else {
+                position.orders.insert(order.id.clone(), order.clone());
+            }
+            let action = group_action(position, &order);
+            (order, liquidation, action)
+        };
+
+        if order.status != OrderStatus::Submitted || order.deal_size > Decimal::ZERO {
+            if let Err(err) = self.strategy.on_order(self.clone(), order).await {
+                tracing::error!("订单事件执行失败: {}", err);
+            }
+        }
+        if let Some(liquidation) = liquidation {
+            if let Err(err) = self
+                .strategy
+                .on_liquidation(self.clone(), liquidation)
+                .await
+            {
+                tracing::error!("强平事件执行失败: {}", err);
+            }
+        }
+        self.group_execute(&symbol, action).await;
+    }
+
+    async fn group_execute(&self, symbol: &str, action: GroupAction) {
+        for id in action.cancels {
+            if let Err(err) = self.order_cancel_remote(symbol, &id).await {
+                tracing::error!("撤销同组订单失败: 订单({}),{}", id, err);
+            }
+        }
+        for order in action.activates {
+            let id = order.id.clone();
+            if let Err(err) = self.order_submit(order).await {
+                tracing::error!("提交止损/止盈订单失败: 订单({}),{}", id, err);
+            }
+        }
+    }
+
+    /// 整点事件, 与回测相同按每日、每小时、每分钟的顺序执行
+    async fn on_timer(self: &Arc<Self>, time: DateTime<Utc>) {
+        if time.hour() == 0 && time.minute() == 0 {
+            if let Err(err) = self.strategy.on_daily(self.clone()).await {
+                tracing::error!("{} 每日事件执行失败: {}", time.str_ymd_hm(), err);
+            }
+        }
+        if time.minute() == 0 {
+            if let Err(err) = self.strategy.on_hourly(self.clone()).await {
+                tracing::error!("{} 每小时事件执行失败: {}", time.str_ymd_hm(), err);
+            }
+        }
+        if let Err(err) = self.strategy.on_minutely(self.clone()).await {
+            tracing::error!("{} 每分钟事件执行失败: {}", time.str_ymd_hm(), err);
+        }
+    }
+
+    fn order_missing(&self, symbol: &str, id: &str) -> OrderError {
+        match self
+            .ledgers
+            .lock()
+            .get(symbol)
+            .and_then(|ledger| ledger.orders.iter().rev().find(|order| order.id == id))
+        {
+            Some(order) => OrderError::Finalized {
+                id: id.to_owned(),
+                status: order.status,
+            },
+            None => OrderError::NotFound {
+                symbol: symbol.to_owned(),
+                id: id.to_owned(),
+            },
+        }
+    }
+
+    /// 由下单请求生成订单, 校验交易对与客户端订单ID
+    fn order_new(&self, request: OrderRequest) -> Result<Order> {
+        let account = self.account.lock();
+        let position = account
+            .positions
+            .get(&request.symbol)
+            .ok_or(anyhow!("交易对不存在: {}", request.symbol))?;
+        let id = request.client_id.clone().unwrap_or_else(String::gen_id);
+        ensure!(
+            !position.orders.contains_key(&id)
+                && !position
+                    .groups
+                    .values()
+                    .any(|group| group.orders_inactive.iter().any(|order| order.id == id)),
+            "客户端订单ID重复: {}",
+            id
+        );
+        Ok(order_init(request, id, self.time()))
+    }
+
+    /// 提交订单, 返回币安的订单状态
+    /// <br> 请求结果未知(超时或服务端错误)时按客户端订单ID查询, 查询到即视为提交成功
+    async fn order_submit(&self, order: Order) -> Result<()> {
+        let mode = self
+            .account
+            .lock()
+            .positions
+            .get(&order.symbol)
+            .map(|position| position.position_mode)
+            .ok_or(anyhow!("交易对不存在: {}", order.symbol))?;
+        let params = order_params(&order, mode);
+        let result = match self
+            .client
+            .request::<BinanceOrder>(
+                Method::POST,
+                "/fapi/v1/order",
+                &params,
+                BinanceSecurity::Signed,
+            )
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                let unknown = err
+                    .downcast_ref::<BinanceError>()
+                    .map(|err| err.is_unknown() || err.is_retryable())
+                    .or_else(|| {
+                        err.downcast_ref::<reqwest::Error>()
+                            .map(|err| err.is_timeout())
+                    })
+                    .unwrap_or(false);
+                if !unknown {
+                    return Err(err);
+                }
+                tracing::warn!("下单结果未知, 查询订单: {}", order.id);
+                self.client
+                    .request::<BinanceOrder>(
+                        Method::GET,
+                        "/fapi/v1/order",
+                        &[
+                            ("symbol", order.symbol.clone()),
+                            ("origClientOrderId", order.id.clone()),
+                        ],
+                        BinanceSecurity::Signed,
+                    )
+                    .await
+                    .map_err(|_| err)?
+            }
+        };
+
+        let mut account = self.account.lock();
+        let position = account.positions.get_mut(&order.symbol).unwrap();
+        if self
+            .orders_finalized
+            .lock()
+            .get(&order.symbol)
+            .is_some_and(|finalized| finalized.contains(&order.id))
+        {
+            return Ok(());
+        }
+        let mut submitted = result.to_order()?;
+        submitted.group_id = order.group_id;
+        submitted.create_time = order.create_time;
+        if order_finished(&submitted) {
+            // 完成状态以用户数据流推送为准, 在此之前保持未完成
+            submitted.status = OrderStatus::Submitted;
+        }
+        position.orders.insert(submitted.id.clone(), submitted);
+        Ok(())
+    }
+
+    /// 向币安撤销订单, 撤单完成以用户数据流推送为准, 在此之前订单为取消中
+    async fn order_cancel_remote(&self, symbol: &str, id: &str) -> Result<()> {
+        self.client
+            .request::<BinanceOrder>(
+                Method::DELETE,
+                "/fapi/v1/order",
+                &[
+                    ("symbol", symbol.to_string()),
+                    ("origClientOrderId", id.to_string()),
+                ],
+                BinanceSecurity::Signed,
+            )
+            .await?;
+        if let Some(order) = self
+            .account
+            .lock()
+            .positions
+            .get_mut(symbol)
+            .and_then(|position| position.orders.get_mut(id))
+        {
+            order.status = OrderStatus::Canceling;
+        }
+        Ok(())
+    }
+
+    /// 以账户级接口修改后更新全部交易对
+    fn positions_update(&self, f: impl Fn(&mut SymbolPosition)) {
+        self.account.lock().positions.values_mut().for_each(f);
+    }
+
+    fn position_update(&self, symbol: &str, f: impl FnOnce(&mut SymbolPosition)) -> Result<()> {
+        let mut account = self.account.lock();
+        let position = account
+            .positions
+            .get_mut(symbol)
+            .ok_or(anyhow!("交易对不存在: {}", symbol))?;
+        f(position);
+        Ok(())
+    }
+
+    async fn margin_change(
+        &self,
+        symbol: &str,
+        side: TradeSide,
+        amount: Decimal,
+        add: bool,
+    ) -> Result<()> {
+        ensure!(amount > Decimal::ZERO, "保证金数量必须大于0");
+        let mode = {
+            let account = self.account.lock();
+            let position = account
+                .positions
+                .get(symbol)
+                .ok_or(anyhow!("交易对不存在: {}", symbol))?;
+            ensure!(position.is_isolated(), "仅逐仓模式可调整保证金: {}", symbol);
+            ensure!(
+                !position.position(side).size.is_zero(),
+                "无持仓时不能调整保证金: {}({})",
+                symbol,
+                side
+            );
+            position.position_mode
+        };
+        self.client
+            .request::<serde_json::Value>(
+                Method::POST,
+                "/fapi/v1/positionMargin",
+                &[
+                    ("symbol", symbol.to_string()),
+                    (
+                        "positionSide",
+                        position_side_to_binance(mode, side).to_string(),
+                    ),
+                    ("amount", amount.normalize().to_string()),
+                    ("type", if add { "1" } else { "2" }.to_string()),
+                ],
+                BinanceSecurity::Signed,
+            )
+            .await?;
+        self.position_update(symbol, |position| {
+            position.position_mut(side).margin_isolated += if add { amount } else { -amount };
+        })
+    }
+}
+
+fn order_finished(order: &Order) -> bool {
+    matches!(
+        order.status,
+        OrderStatus::Filled | OrderStatus::Canceled | OrderStatus::Rejected
+    )
+}
+
+/// 合并REST接口查询到的未完成订单, 保留本地的订单组与成交累计
+fn order_merge(position: &mut SymbolPosition, mut order: Order) {
+    if let Some(previous) = position.orders.get(&order.id) {
+        order.group_id = previous.group_id.clone();
+        order.deal_fee = previous.deal_fee;
+        order.deal_pnl = previous.deal_pnl;
+        order.create_time = previous.create_time;
+    }
+    position.orders.insert(order.id.clone(), order);
+}
+
+/// 订单更新后的订单组处理, 规则与[`SymbolPosition::group_update`]相同
+/// <br> 区别在于括号订单的入场订单完成(而非首次成交)后才按成交数量提交止损/止盈订单, 避免多次调整已提交订单的数量
+fn group_action(position: &mut SymbolPosition, order: &Order) -> GroupAction {
+    let mut action = GroupAction::default();
+    let Some(group_id) = order.group_id.as_ref() else {
+        return action;
+    };
+    let Some(group) = position.groups.get_mut(group_id) else {
+        return action;
+    };
+    let finished = order_finished(order);
+
+    if group.entry_id.as_ref() == Some(&order.id) {
+        if finished {
+            if order.deal_size.is_zero() {
+                position.groups.remove(group_id);
+            } else {
+                action.activates = group
+                    .orders_inactive
+                    .drain(..)
+                    .map(|mut bracket| {
+                        bracket.size = order.deal_size;
+                        bracket
+                    })
+                    .collect();
+            }
+        }
+        return action;
+    }
+
+    if order.deal_size.is_zero() && !finished {
+        return action;
+    }
+    let group = position.groups.remove(group_id).unwrap();
+    action.cancels = group
+        .entry_id
+        .iter()
+        .chain(group.order_ids.iter())
+        .filter(|id| **id != order.id && position.orders.contains_key(*id))
+        .cloned()
+        .collect();
+    action
+}
+
+/// 数据流任务: 连接组合数据流并转发消息, 断开后按间隔重连, 地址变更时重新连接
+pub(crate) async fn stream_run(
+    mut url_rx: watch::Receiver<String>,
+    event_tx: mpsc::Sender<StreamEvent>,
+    reconnect_interval: StdDuration,
+) {
+    loop {
+        let url = url_rx.borrow_and_update().clone();
+        match connect_async(&url).await {
+            Ok((mut stream, _)) => {
+                if event_tx.send(StreamEvent::Connected).await.is_err() {
+                    return;
+                }
+                loop {
+                    tokio::select! {
+                        message = stream.next() => match message {
+                            Some(Ok(Message::Text(text))) => {
+                                if event_tx
+                                    .send(StreamEvent::Message(text.to_string()))
+                                    .await
+                                    .is_err()
+                                {
+                                    return;
+                                }
+                            }
+                            Some(Ok(Message::Ping(payload))) => {
+                                let _ = stream.send(Message::Pong(payload)).await;
+                            }
+                            Some(Ok(Message::Close(_))) | None => break,
+                            Some(Ok(_)) => {}
+                            Some(Err(err)) => {
+                                tracing::warn!("数据流错误: {}", err);
+                                break;
+                            }
+                        },
+                        changed = url_rx.changed() => {
+                            if changed.is_err() {
+                                return;
+                            }
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(err) => tracing::warn!("数据流连接失败: {}", err),
+        }
+        if event_tx.is_closed() {
+            return;
+        }
+        sleep(reconnect_interval).await;
+    }
+}
+
+#[async_trait]
+impl EngineExchange for Binance {
+    async fn order_place(&self, request: OrderRequest) -> Result<String> {
+        let order = self.order_new(request)?;
+        let id = order.id.clone();
+        self.order_submit(order).await?;
+        Ok(id)
+    }
+    async fn order_amend(
+        &self,
+        symbol: &str,
+        id: &str,
+        price: Decimal,
+        size: Decimal,
+    ) -> Result<()> {
+        let order = self
+            .order(symbol, id)
+            .ok_or_else(|| self.order_missing(symbol, id))?;
+        ensure!(order.r#type.is_limit(), "市价单不支持修改: {}", id);
+        ensure!(
+            size > order.deal_size,
+            "修改后的订单数量必须大于已成交数量: {}",
+            id
+        );
+        let is_buy = (order.side == TradeSide::Long) != order.reduce_only;
+        let result = self
+            .client
+            .request::<BinanceOrder>(
+                Method::PUT,
+                "/fapi/v1/order",
+                &[
+                    ("symbol", symbol.to_string()),
+                    ("origClientOrderId", id.to_string()),
+                    ("side", if is_buy { "BUY" } else { "SELL" }.to_string()),
+                    ("quantity", size.normalize().to_string()),
+                    ("price", price.normalize().to_string()),
+                ],
+                BinanceSecurity::Signed,
+            )
+            .await?
+            .to_order()?;
+        self.position_update(symbol, |position| {
+            if let Some(order) = position.orders.get_mut(id) {
+                order.price = result.price;
+                order.size = result.size;
+                order.queue_time = result.queue_time;
+            }
+        })
+    }
+    async fn order_place_oco(&self, requests: Vec<OrderRequest>) -> Result<String> {
+        ensure!(requests.len() >= 2, "二选一订单至少包含2个订单");
+        order_requests_check(&requests.iter().collect::<Vec<&OrderRequest>>())?;
+
+        let group_id = String::gen_id();
+        let orders = requests
+            .into_iter()
+            .map(|request| {
+                let mut order = self.order_new(request)?;
+                order.group_id = Some(group_id.clone());
+                Ok(order)
+            })
+            .collect::<Result<Vec<Order>>>()?;
+        let symbol = orders[0].symbol.clone();
+        self.position_update(&symbol, |position| {
+            position.groups.insert(
+                group_id.clone(),
+                OrderGroup {
+                    id: group_id.clone(),
+                    symbol: symbol.clone(),
+                    r#type: OrderGroupType::Oco,
+                    entry_id: None,
+                    order_ids: orders.iter().map(|order| order.id.clone()).collect(),
+                    orders_inactive: Vec::new(),
+                    create_time: self.time(),
+                },
+            );
+        })?;
+
+        for order in orders {
+            if let Err(err) = self.order_submit(order).await {
+                let _ = self.order_group_cancel(&symbol, &group_id).await;
+                return Err(err);
+            }
+        }
+        Ok(group_id)
+    }
+    async fn order_place_bracket(
+        &self,
+        entry: OrderRequest,
+        stop_loss: Option<OrderRequest>,
+        take_profit: Option<OrderRequest>,
+    ) -> Result<String> {
+        ensure!(
+            !entry.reduce_only && !entry.close_position,
+            "入场订单不能只减仓"
+        );
+        ensure!(
+            stop_loss.is_some() || take_profit.is_some(),
+            "括号订单至少包含止损或止盈订单"
+        );
+        let brackets = [stop_loss, take_profit]
+            .into_iter()
+            .flatten()
+            .map(|request| OrderRequest {
+                side: entry.side,
+                reduce_only: true,
+                size: entry.size,
+                ..request
+            })
+            .collect::<Vec<OrderRequest>>();
+        order_requests_check(
+            &std::iter::once(&entry)
+                .chain(brackets.iter())
+                .collect::<Vec<&OrderRequest>>(),
+        )?;
+
+        let group_id = String::gen_id();
+        let mut entry = self.order_new(entry)?;
+        entry.group_id = Some(group_id.clone());
+        let brackets = brackets
+            .into_iter()
+            .map(|request| {
+                let mut order = self.order_new(request)?;
+                order.group_id = Some(group_id.clone());
+                Ok(order)
+            })
+            .collect::<Result<Vec<Order>>>()?;
+
+        let symbol = entry.symbol.clone();
+        self.position_update(&symbol, |position| {
+            position.groups.insert(
+                group_id.clone(),
+                OrderGroup {
+                    id: group_id.clone(),
+                    symbol: symbol.clone(),
+                    r#type: OrderGroupType::Bracket,
+                    entry_id: Some(entry.id.clone()),
+                    order_ids: brackets.iter().map(|order| order.id.clone()).collect(),
+                    orders_inactive: brackets,
+                    create_time: self.time(),
+                },
+            );
+        })?;
+        if let Err(err) = self.order_submit(entry).await {
+            self.position_update(&symbol, |position| {
+                position.groups.remove(&group_id);
+            })?;
+            return Err(err);
+        }
+        Ok(group_id)
+    }
+    async fn order_group_cancel(&self, symbol: &str, id: &str) -> Result<()> {
+        let ids = {
+            let mut account = self.account.lock();
+            let position = account
+                .positions
+                .get_mut(symbol)
+                .ok_or(anyhow!("交易对不存在: {}", symbol))?;
+            let group = position
+                .groups
+                .remove(id)
+                .ok_or(OrderError::GroupNotFound {
+                    symbol: symbol.to_owned(),
+                    id: id.to_owned(),
+                })?;
+            group
+                .entry_id
+                .iter()
+                .chain(group.order_ids.iter())
+                .filter(|id| position.orders.contains_key(*id))
+                .cloned()
+                .collect::<Vec<String>>()
+        };
+        for id in ids {
+            self.order_cancel_remote(symbol, &id).await?;
+        }
+        Ok(())
+    }
+    async fn order_close(&self, symbol: &str, id: &str) -> Result<()> {
+        self.order_cancel_many(symbol, &[id.to_string()]).await
+    }
+    async fn order_cancel_many(&self, symbol: &str, ids: &[String]) -> Result<()> {
+        {
+            let account = self.account.lock();
+            let position = account
+                .positions
+                .get(symbol)
+                .ok_or(anyhow!("交易对不存在: {}", symbol))?;
+            if let Some(id) = ids.iter().find(|id| !position.orders.contains_key(*id)) {
+                drop(account);
+                return Err(self.order_missing(symbol, id).into());
+            }
+        }
+        for id in ids {
+            self.order_cancel_remote(symbol, id).await?;
+        }
+        Ok(())
+    }
+    async fn leverage_set(&self, symbol: &str, leverage: u32) -> Result<()> {
+        ensure!(leverage >= 1, "杠杆倍率必须大于等于1");
+        self.client
+            .request::<serde_json::Value>(
+                Method::POST,
+                "/fapi/v1/leverage",
+                &[
+                    ("symbol", symbol.to_string()),
+                    ("leverage", leverage.to_string()),
+                ],
+                BinanceSecurity::Signed,
+            )
+            .await?;
+        self.position_update(symbol, |position| position.leverage = leverage)
+    }
+    /// 币安的持仓模式对账户内全部交易对生效
+    async fn position_mode_set(&self, symbol: &str, mode: PositionMode) -> Result<()> {
+        ensure!(
+            self.account.lock().positions.contains_key(symbol),
+            "交易对不存在: {}",
+            symbol
+        );
+        let result = self
+            .client
+            .request::<serde_json::Value>(
+                Method::POST,
+                "/fapi/v1/positionSide/dual",
+                &[(
+                    "dualSidePosition",
+                    (mode == PositionMode::Hedge).to_string(),
+                )],
+                BinanceSecurity::Signed,
+            )
+            .await;
+        // -4059: 无需修改持仓模式
+        if let Err(err) = result {
+            if err.downcast_ref::<BinanceError>().map(|err| err.code) != Some(-4059) {
+                return Err(err);
+            }
+        }
+        self.positions_update(|position| position.position_mode = mode);
+        Ok(())
+    }
+    async fn margin_mode_set(&self, symbol: &str, mode: MarginMode) -> Result<()> {
+        let result = self
+            .client
+            .request::<serde_json::Value>(
+                Method::POST,
+                "/fapi/v1/marginType",
+                &[
+                    ("symbol", symbol.to_string()),
+                    (
+                        "marginType",
+                        match mode {
+                            MarginMode::Cross => "CROSSED",
+                            MarginMode::Isolated => "ISOLATED",
+                        }
+                        .to_string(),
+                    ),
+                ],
+                BinanceSecurity::Signed,
+            )
+            .await;
+        // -4046: 无需修改保证金模式
+        if let Err(err) = result {
+            if err.downcast_ref::<BinanceError>().map(|err| err.code) != Some(-4046) {
+                return Err(err);
+            }
+        }
+        self.position_update(symbol, |position| position.margin_mode = mode)
+    }
+    async fn margin_add(&self, symbol: &str, side: TradeSide, amount: Decimal) -> Result<()> {
+        self.margin_change(symbol, side, amount, true).await
+    }
+    async fn margin_remove(&self, symbol: &str, side: TradeSide, amount: Decimal) -> Result<()> {
+        self.margin_change(symbol, side, amount, false).await
+    }
+}
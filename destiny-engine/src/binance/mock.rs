@@ -0,0 +1,1407 @@
+use super::api::*;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use derive_builder::Builder;
+use destiny_helpers::prelude::*;
+use destiny_types::prelude::*;
+use futures::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use parking_lot::Mutex;
+use reqwest::Method;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use rust_decimal_macros::dec;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::{Arc, Weak},
+    time::Duration as StdDuration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_tungstenite::tungstenite::{
+    handshake::server::{ErrorResponse, Request, Response},
+    Message,
+};
+
+/// 模拟交易所配置
+#[derive(Builder)]
+#[builder(setter(into))]
+pub struct MockExchangeConfig {
+    #[builder(default = "\"mock-api-key\".to_string()")]
+    pub api_key: String,
+    #[builder(default = "\"mock-api-secret\".to_string()")]
+    pub api_secret: String,
+    /// 初始资金
+    #[builder(default = dec!(10000))]
+    pub cash: Decimal,
+    /// 挂单手续费率
+    #[builder(default = dec!(0.0002))]
+    pub fee_rate_maker: Decimal,
+    /// 吃单手续费率
+    #[builder(default = dec!(0.0005))]
+    pub fee_rate_taker: Decimal,
+    /// 持仓模式, 对全部交易对生效
+    #[builder(default = PositionMode::Hedge)]
+    pub position_mode: PositionMode,
+}
+
+/// 注入的接口错误, 按请求顺序依次生效
+#[derive(Debug, Clone)]
+pub enum MockError {
+    /// 限流: 429, -1003
+    RateLimit,
+    /// 服务端错误: 503, -1001
+    ServerError,
+    /// 正常处理请求, 延迟返回响应, 用于模拟请求超时
+    Timeout(StdDuration),
+    /// 指定的错误响应
+    Reject { status: u16, code: i64, msg: String },
+}
+
+/// 模拟交易所收到的请求
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub params: HashMap<String, String>,
+}
+
+/// 数据流连接, 断开连接时丢弃发送端
+struct MockClient {
+    streams: Vec<String>,
+    tx: mpsc::UnboundedSender<Message>,
+}
+
+struct MockState {
+    account: Account,
+    /// 已完成订单, 用于订单查询
+    orders_finished: HashMap<String, Vec<Order>>,
+    position_mode: PositionMode,
+    listen_key: Option<String>,
+    errors: HashMap<String, VecDeque<MockError>>,
+    requests: Vec<MockRequest>,
+    clients: Vec<MockClient>,
+    connections: usize,
+    time_offset: i64,
+}
+
+/// 错误响应
+type MockReject = (u16, i64, String);
+
+fn reject(code: i64, msg: &str) -> MockReject {
+    (400, code, msg.to_string())
+}
+
+/// 本地模拟的币安U本位合约交易所, 提供REST接口与组合数据流, 用于离线测试实盘引擎
+/// <br> 账户以[`Account`]与[`SymbolPosition`]记账, 成交后按币安格式推送账户更新与订单更新
+/// <br> 行情由测试脚本推送: 每次推送K线时按最高价与最低价撮合挂单与条件单, 市价单及可立即成交的限价单在下单时按最新价格成交
+/// <br> 通过[`MockExchange::error_inject`]注入限流、超时与拒绝等错误, 通过[`MockExchange::disconnect`]断开数据流
+pub struct MockExchange {
+    config: MockExchangeConfig,
+    rest_addr: SocketAddr,
+    ws_addr: SocketAddr,
+    state: Mutex<MockState>,
+}
+
+impl MockExchange {
+    /// 在本地随机端口启动REST接口与数据流服务
+    pub async fn start(config: MockExchangeConfig) -> Result<Arc<MockExchange>> {
+        let rest_listener = TcpListener::bind("127.0.0.1:0").await?;
+        let ws_listener = TcpListener::bind("127.0.0.1:0").await?;
+        let state = MockState {
+            account: Account {
+                cash: config.cash,
+                positions: Default::default(),
+            },
+            orders_finished: HashMap::new(),
+            position_mode: config.position_mode,
+            listen_key: None,
+            errors: HashMap::new(),
+            requests: Vec::new(),
+            clients: Vec::new(),
+            connections: 0,
+            time_offset: 0,
+        };
+        let exchange = Arc::new(MockExchange {
+            config,
+            rest_addr: rest_listener.local_addr()?,
+            ws_addr: ws_listener.local_addr()?,
+            state: Mutex::new(state),
+        });
+
+        let weak = Arc::downgrade(&exchange);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = rest_listener.accept().await {
+                let Some(exchange) = weak.upgrade() else {
+                    return;
+                };
+                tokio::spawn(async move { exchange.rest_serve(stream).await });
+            }
+        });
+        let weak = Arc::downgrade(&exchange);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = ws_listener.accept().await {
+                let weak = weak.clone();
+                tokio::spawn(async move { ws_serve(weak, stream).await });
+            }
+        });
+        Ok(exchange)
+    }
+
+    pub fn rest_url(&self) -> String {
+        format!("http://{}", self.rest_addr)
+    }
+
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}", self.ws_addr)
+    }
+
+    /// 上架交易对: 20倍杠杆, 全仓, 持仓模式与账户一致
+    pub fn symbol_add(&self, symbol: &str, rule: SymbolRule) {
+        let mut state = self.state.lock();
+        let mut position = SymbolPosition::new(symbol, rule);
+        position.leverage = 20;
+        position.position_mode = state.position_mode;
+        state.account.positions.insert(symbol.to_string(), position);
+    }
+
+    /// 推送1分钟K线并按K线的最高价与最低价撮合
+    pub fn kline_push(&self, kline: &Kline, closed: bool) {
+        let mut state = self.state.lock();
+        let Some(position) = state.account.positions.get_mut(&kline.symbol) else {
+            return;
+        };
+        position.symbol.market.last = kline.close;
+        if position.symbol.market.mark.is_zero() {
+            position.symbol.market.mark = kline.close;
+        }
+        let open_time = kline.open_time.timestamp_millis();
+        state.publish(
+            &format!("{}@kline_1m", kline.symbol.to_lowercase()),
+            json!({
+                "e": "kline",
+                "E": Utc::now().timestamp_millis(),
+                "s": kline.symbol,
+                "k": {
+                    "t": open_time,
+                    "T": open_time + 59_999,
+                    "s": kline.symbol,
+                    "i": "1m",
+                    "o": decimal_str(kline.open),
+                    "c": decimal_str(kline.close),
+                    "h": decimal_str(kline.high),
+                    "l": decimal_str(kline.low),
+                    "v": decimal_str(kline.size),
+                    "n": kline.trades,
+                    "x": closed,
+                    "q": decimal_str(kline.cash),
+                    "V": decimal_str(kline.buy_size),
+                    "Q": decimal_str(kline.buy_cash),
+                    "B": "0",
+                }
+            }),
+        );
+        self.cross(&mut state, &kline.symbol, kline.low, kline.high);
+    }
+
+    /// 按收盘价序列从`begin`起每分钟推送一根已收盘K线, 开盘价为前一收盘价
+    pub fn price_path(&self, symbol: &str, begin: DateTime<Utc>, closes: &[Decimal]) {
+        let mut open = closes.first().copied().unwrap_or_default();
+        for (i, close) in closes.iter().enumerate() {
+            self.kline_push(
+                &Kline {
+                    symbol: symbol.to_string(),
+                    open_time: begin + Duration::minutes(i as i64),
+                    open,
+                    high: open.max(*close),
+                    low: open.min(*close),
+                    close: *close,
+                    size: Decimal::ONE,
+                    cash: *close,
+                    buy_size: Decimal::ZERO,
+                    buy_cash: Decimal::ZERO,
+                    trades: 1,
+                    interval: KlineInterval::M1,
+                },
+                true,
+            );
+            open = *close;
+        }
+    }
+
+    /// 推送标记价格与资金费率
+    pub fn mark_price_push(
+        &self,
+        symbol: &str,
+        mark: Decimal,
+        funding_rate: Decimal,
+        next_funding_time: DateTime<Utc>,
+    ) {
+        let mut state = self.state.lock();
+        let Some(position) = state.account.positions.get_mut(symbol) else {
+            return;
+        };
+        let market = &mut position.symbol.market;
+        market.mark = mark;
+        market.index = mark;
+        market.settlement = funding_rate;
+        market.settlement_time = next_funding_time;
+        state.publish(
+            &format!("{}@markPrice@1s", symbol.to_lowercase()),
+            json!({
+                "e": "markPriceUpdate",
+                "E": Utc::now().timestamp_millis(),
+                "s": symbol,
+                "p": decimal_str(mark),
+                "i": decimal_str(mark),
+                "P": decimal_str(mark),
+                "r": decimal_str(funding_rate),
+                "T": next_funding_time.timestamp_millis(),
+            }),
+        );
+    }
+
+    /// 按当前资金费率与标记价格结算资金费用, 推送原因为FUNDING_FEE的账户更新
+    pub fn funding_settle(&self, symbol: &str) {
+        let mut state = self.state.lock();
+        let Some(position) = state.account.positions.get(symbol) else {
+            return;
+        };
+        let market = &position.symbol.market;
+        let fee = [&position.long, &position.short]
+            .into_iter()
+            .filter(|side_position| side_position.size > Decimal::ZERO)
+            .map(|side_position| {
+                Funding::new(
+                    symbol,
+                    side_position,
+                    market.settlement,
+                    market.mark,
+                    Utc::now(),
+                )
+                .fee
+            })
+            .sum::<Decimal>();
+        state.account.cash += fee;
+        state.account_update(symbol, "FUNDING_FEE", fee);
+    }
+
+    /// 为接口注入错误, `path`如`/fapi/v1/order`
+    pub fn error_inject(&self, method: Method, path: &str, error: MockError) {
+        self.state
+            .lock()
+            .errors
+            .entry(format!("{} {}", method, path))
+            .or_default()
+            .push_back(error);
+    }
+
+    /// 服务器时间相对本地时间的偏移(毫秒), 用于模拟时间戳超出接收窗口
+    pub fn time_offset_set(&self, millis: i64) {
+        self.state.lock().time_offset = millis;
+    }
+
+    /// 推送listenKey失效事件, 之后需重新创建listenKey
+    pub fn listen_key_expire(&self) {
+        let mut state = self.state.lock();
+        if let Some(listen_key) = state.listen_key.clone() {
+            state.publish(
+                &listen_key,
+                json!({
+                    "e": "listenKeyExpired",
+                    "E": Utc::now().timestamp_millis(),
+                    "listenKey": listen_key,
+                }),
+            );
+            state.listen_key = None;
+        }
+    }
+
+    /// 断开全部数据流连接
+    pub fn disconnect(&self) {
+        self.state.lock().clients.clear();
+    }
+
+    /// 累计的数据流连接次数
+    pub fn connections(&self) -> usize {
+        self.state.lock().connections
+    }
+
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state.lock().requests.clone()
+    }
+
+    pub fn account(&self) -> Account {
+        self.state.lock().account.clone()
+    }
+
+    /// 查询未完成或已完成的订单
+    pub fn order(&self, symbol: &str, id: &str) -> Option<Order> {
+        self.state.lock().order(symbol, id)
+    }
+
+    async fn rest_serve(self: Arc<Self>, stream: TcpStream) {
+        let mut reader = BufReader::new(stream);
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+                return;
+            }
+            let mut parts = line.split_whitespace();
+            let method = parts.next().unwrap_or_default().to_string();
+            let target = parts.next().unwrap_or_default().to_string();
+            let mut api_key = None;
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                if reader.read_line(&mut header).await.unwrap_or(0) == 0 {
+                    return;
+                }
+                let header = header.trim_end();
+                if header.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    match name.to_ascii_lowercase().as_str() {
+                        "x-mbx-apikey" => api_key = Some(value.trim().to_string()),
+                        "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                        _ => {}
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            if reader.read_exact(&mut body).await.is_err() {
+                return;
+            }
+
+            let (delay, result) = self.handle(&method, &target, api_key.as_deref());
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+            }
+            let (status, body) = match result {
+                Ok(value) => (200, value.to_string()),
+                Err((status, code, msg)) => (status, json!({"code": code, "msg": msg}).to_string()),
+            };
+            let response = format!(
+                "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            if reader
+                .get_mut()
+                .write_all(response.as_bytes())
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+
+    /// 处理请求, 返回响应延迟与响应
+    fn handle(
+        &self,
+        method: &str,
+        target: &str,
+        api_key: Option<&str>,
+    ) -> (Option<StdDuration>, Result<Value, MockReject>) {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let params = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.to_string(), url_decode(value)))
+            .collect::<HashMap<String, String>>();
+        let mut state = self.state.lock();
+        state.requests.push(MockRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            params: params.clone(),
+        });
+
+        let error = state
+            .errors
+            .get_mut(&format!("{} {}", method, path))
+            .and_then(VecDeque::pop_front);
+        let delay = match error {
+            Some(MockError::RateLimit) => {
+                return (None, Err((429, -1003, "Too many requests.".to_string())));
+            }
+            Some(MockError::ServerError) => {
+                return (
+                    None,
+                    Err((
+                        503,
+                        -1001,
+                        "Internal error; unable to process your request.".to_string(),
+                    )),
+                );
+            }
+            Some(MockError::Reject { status, code, msg }) => {
+                return (None, Err((status, code, msg)));
+            }
+            Some(MockError::Timeout(delay)) => Some(delay),
+            None => None,
+        };
+
+        let result = self
+            .authorize(&state, path, query, &params, api_key)
+            .and_then(|_| self.route(&mut state, method, path, &params));
+        (delay, result)
+    }
+
+    /// 校验API Key、签名与时间戳
+    fn authorize(
+        &self,
+        state: &MockState,
+        path: &str,
+        query: &str,
+        params: &HashMap<String, String>,
+        api_key: Option<&str>,
+    ) -> Result<(), MockReject> {
+        if matches!(
+            path,
+            "/fapi/v1/time" | "/fapi/v1/exchangeInfo" | "/fapi/v1/premiumIndex"
+        ) {
+            return Ok(());
+        }
+        if api_key != Some(self.config.api_key.as_str()) {
+            return Err((
+                401,
+                -2015,
+                "Invalid API-key, IP, or permissions for action.".to_string(),
+            ));
+        }
+        if path == "/fapi/v1/listenKey" {
+            return Ok(());
+        }
+
+        let (payload, signature) = query.rsplit_once("&signature=").unwrap_or((query, ""));
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.config.api_secret.as_bytes())
+            .expect("HMAC密钥长度不受限制");
+        mac.update(payload.as_bytes());
+        if hex::encode(mac.finalize().into_bytes()) != signature {
+            return Err(reject(-1022, "Signature for this request is not valid."));
+        }
+
+        let timestamp = params
+            .get("timestamp")
+            .and_then(|timestamp| timestamp.parse::<i64>().ok())
+            .ok_or_else(|| reject(-1102, "Mandatory parameter 'timestamp' was not sent."))?;
+        let recv_window = params
+            .get("recvWindow")
+            .and_then(|recv_window| recv_window.parse::<i64>().ok())
+            .unwrap_or(5000);
+        let server_time = state.server_time();
+        if timestamp < server_time - recv_window || timestamp > server_time + 1000 {
+            return Err(reject(
+                -1021,
+                "Timestamp for this request is outside of the recvWindow.",
+            ));
+        }
+        Ok(())
+    }
+
+    fn route(
+        &self,
+        state: &mut MockState,
+        method: &str,
+        path: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<Value, MockReject> {
+        let param = |key: &str| params.get(key).cloned().unwrap_or_default();
+        match (method, path) {
+            ("GET", "/fapi/v1/time") => Ok(json!({ "serverTime": state.server_time() })),
+            ("GET", "/fapi/v1/exchangeInfo") => Ok(state.exchange_info()),
+            ("GET", "/fapi/v1/leverageBracket") => Ok(state.leverage_brackets()),
+            ("GET", "/fapi/v1/premiumIndex") => Ok(state.premium_indexes()),
+            ("GET", "/fapi/v2/account") => Ok(state.account_info()),
+            ("GET", "/fapi/v1/positionSide/dual") => Ok(json!({
+                "dualSidePosition": state.position_mode == PositionMode::Hedge
+            })),
+            ("POST", "/fapi/v1/positionSide/dual") => {
+                let mode = if param("dualSidePosition") == "true" {
+                    PositionMode::Hedge
+                } else {
+                    PositionMode::OneWay
+                };
+                if mode == state.position_mode {
+                    return Err(reject(-4059, "No need to change position side."));
+                }
+                if state.account.positions.values().any(|position| {
+                    !position.orders.is_empty()
+                        || !position.long.size.is_zero()
+                        || !position.short.size.is_zero()
+                }) {
+                    return Err(reject(
+                        -4068,
+                        "Position side cannot be changed if there exists position.",
+                    ));
+                }
+                state.position_mode = mode;
+                for position in state.account.positions.values_mut() {
+                    position.position_mode = mode;
+                }
+                Ok(json!({"code": 200, "msg": "success"}))
+            }
+            ("POST", "/fapi/v1/listenKey") => {
+                let listen_key = state
+                    .listen_key
+                    .get_or_insert_with(|| format!("mock{}", String::gen_id()))
+                    .clone();
+                Ok(json!({ "listenKey": listen_key }))
+            }
+            ("PUT", "/fapi/v1/listenKey") => match state.listen_key.as_ref() {
+                Some(listen_key) => Ok(json!({ "listenKey": listen_key })),
+                None => Err(reject(-1125, "This listenKey does not exist.")),
+            },
+            ("DELETE", "/fapi/v1/listenKey") => {
+                state.listen_key = None;
+                Ok(json!({}))
+            }
+            ("GET", "/fapi/v1/openOrders") => {
+                let position = state.position(&param("symbol"))?;
+                let mut orders = position.orders.values().collect::<Vec<&Order>>();
+                orders.sort_by_key(|order| (order.create_time, order.id.clone()));
+                Ok(Value::Array(
+                    orders
+                        .into_iter()
+                        .map(|order| order_json(order, position.position_mode))
+                        .collect(),
+                ))
+            }
+            ("GET", "/fapi/v1/order") => {
+                let symbol = param("symbol");
+                let mode = state.position(&symbol)?.position_mode;
+                state
+                    .order(&symbol, &param("origClientOrderId"))
+                    .map(|order| order_json(&order, mode))
+                    .ok_or_else(|| reject(-2013, "Order does not exist."))
+            }
+            ("POST", "/fapi/v1/order") => self.order_place(state, params),
+            ("PUT", "/fapi/v1/order") => {
+                let symbol = param("symbol");
+                let id = param("origClientOrderId");
+                let price = decimal_param(params, "price")?;
+                let size = decimal_param(params, "quantity")?;
+                let position = state.position_mut(&symbol)?;
+                let mode = position.position_mode;
+                let order = position
+                    .orders
+                    .get_mut(&id)
+                    .ok_or_else(|| reject(-2013, "Order does not exist."))?;
+                if order.r#type != TradeType::Limit {
+                    return Err(reject(-1116, "Invalid orderType."));
+                }
+                if size <= order.deal_size {
+                    return Err(reject(-4003, "Quantity less than or equal to zero."));
+                }
+                order.price = price;
+                order.size = size;
+                order.queue_time = Utc::now();
+                let order = order.clone();
+                state.order_update(&order, "AMENDMENT", None);
+                Ok(order_json(&order, mode))
+            }
+            ("DELETE", "/fapi/v1/order") => {
+                let symbol = param("symbol");
+                let id = param("origClientOrderId");
+                let position = state.position_mut(&symbol)?;
+                let mode = position.position_mode;
+                let mut order = position
+                    .orders
+                    .remove(&id)
+                    .ok_or_else(|| reject(-2011, "Unknown order sent."))?;
+                order.status = OrderStatus::Canceled;
+                state.order_finish(order.clone(), "CANCELED");
+                Ok(order_json(&order, mode))
+            }
+            ("POST", "/fapi/v1/leverage") => {
+                let leverage = param("leverage")
+                    .parse::<u32>()
+                    .map_err(|_| reject(-4028, "Leverage is not valid."))?;
+                let position = state.position_mut(&param("symbol"))?;
+                let leverage_max = position
+                    .symbol
+                    .rule
+                    .margin_tiers
+                    .first()
+                    .map(|tier| tier.leverage_max)
+                    .unwrap_or(125);
+                if leverage == 0 || leverage > leverage_max {
+                    return Err(reject(-4028, "Leverage is not valid."));
+                }
+                position.leverage = leverage;
+                Ok(json!({
+                    "leverage": leverage,
+                    "maxNotionalValue": "1000000",
+                    "symbol": position.symbol.symbol,
+                }))
+            }
+            ("POST", "/fapi/v1/marginType") => {
+                let mode = match param("marginType").as_str() {
+                    "ISOLATED" => MarginMode::Isolated,
+                    _ => MarginMode::Cross,
+                };
+                let position = state.position_mut(&param("symbol"))?;
+                if position.margin_mode == mode {
+                    return Err(reject(-4046, "No need to change margin type."));
+                }
+                if !position.long.size.is_zero()
+                    || !position.short.size.is_zero()
+                    || !position.orders.is_empty()
+                {
+                    return Err(reject(
+                        -4048,
+                        "Margin type cannot be changed if there exists position.",
+                    ));
+                }
+                position.margin_mode = mode;
+                Ok(json!({"code": 200, "msg": "success"}))
+            }
+            ("POST", "/fapi/v1/positionMargin") => {
+                let amount = decimal_param(params, "amount")?;
+                let add = param("type") == "1";
+                let cash_available = state.account.cash_available();
+                let position = state.position_mut(&param("symbol"))?;
+                if !position.is_isolated() {
+                    return Err(reject(-4051, "Isolated balance insufficient."));
+                }
+                let side = match param("positionSide").as_str() {
+                    "LONG" => TradeSide::Long,
+                    "SHORT" => TradeSide::Short,
+                    _ if position.long.size >= position.short.size => TradeSide::Long,
+                    _ => TradeSide::Short,
+                };
+                let leverage = position.leverage;
+                let leg = position.position_mut(side);
+                if leg.size.is_zero() {
+                    return Err(reject(-4051, "Isolated balance insufficient."));
+                }
+                if add && amount > cash_available
+                    || !add && amount > leg.margin_isolated - leg.margin(leverage)
+                {
+                    return Err(reject(-4050, "Cross balance insufficient."));
+                }
+                leg.margin_isolated += if add { amount } else { -amount };
+                Ok(json!({
+                    "amount": amount.to_f64(),
+                    "code": 200,
+                    "msg": "Successfully modify position margin.",
+                    "type": if add { 1 } else { 2 },
+                }))
+            }
+            _ => Err((404, -1, "Not found".to_string())),
+        }
+    }
+
+    /// 下单: 校验交易规则与保证金, 推送新订单后撮合可立即成交的订单
+    fn order_place(
+        &self,
+        state: &mut MockState,
+        params: &HashMap<String, String>,
+    ) -> Result<Value, MockReject> {
+        let param = |key: &str| params.get(key).cloned().unwrap_or_default();
+        let symbol = param("symbol");
+        let now = Utc::now().timestamp_millis();
+        let r#type = param("type");
+        if r#type == "TRAILING_STOP_MARKET" {
+            return Err(reject(-1116, "Invalid orderType."));
+        }
+        let mut order = BinanceOrder {
+            symbol: symbol.clone(),
+            client_order_id: params
+                .get("newClientOrderId")
+                .cloned()
+                .unwrap_or_else(String::gen_id),
+            status: "NEW".to_string(),
+            r#type: r#type.clone(),
+            orig_type: r#type,
+            side: param("side"),
+            position_side: params
+                .get("positionSide")
+                .cloned()
+                .unwrap_or("BOTH".to_string()),
+            reduce_only: param("reduceOnly") == "true",
+            close_position: param("closePosition") == "true",
+            time_in_force: params
+                .get("timeInForce")
+                .cloned()
+                .unwrap_or("GTC".to_string()),
+            price: decimal_param(params, "price").unwrap_or_default(),
+            orig_qty: decimal_param(params, "quantity").unwrap_or_default(),
+            executed_qty: Decimal::ZERO,
+            avg_price: Decimal::ZERO,
+            stop_price: decimal_param(params, "stopPrice").unwrap_or_default(),
+            working_type: params
+                .get("workingType")
+                .cloned()
+                .unwrap_or("CONTRACT_PRICE".to_string()),
+            price_rate: None,
+            time: now,
+            update_time: now,
+        }
+        .to_order()
+        .map_err(|_| reject(-1116, "Invalid orderType."))?;
+
+        let cash_available = state.account.cash_available();
+        let finished = state.order_finished_exists(&symbol, &order.id);
+        let position = state.position_mut(&symbol)?;
+        let rule = &position.symbol.rule;
+        let position_side = param("positionSide");
+        if (position.position_mode == PositionMode::Hedge)
+            == matches!(position_side.as_str(), "" | "BOTH")
+        {
+            return Err(reject(
+                -4061,
+                "Order's position side does not match user's setting.",
+            ));
+        }
+        if finished || position.orders.contains_key(&order.id) {
+            return Err(reject(-4116, "ClientOrderId is duplicated."));
+        }
+        if order.r#type.is_limit()
+            && (order.price < rule.price_min
+                || order.price > rule.price_max
+                || !(order.price % rule.price_tick).is_zero())
+        {
+            return Err(reject(-1013, "Filter failure: PRICE_FILTER"));
+        }
+        if !order.close_position
+            && (order.size < rule.size_min
+                || order.size > rule.size_max
+                || !(order.size % rule.size_tick).is_zero())
+        {
+            return Err(reject(-1013, "Filter failure: LOT_SIZE"));
+        }
+        let price = if order.r#type.is_limit() {
+            order.price
+        } else if order.r#type.is_conditional() {
+            order.stop_price
+        } else {
+            position.symbol.market.last
+        };
+        if !order.reduce_only && order.size * price < rule.amount_min {
+            return Err(reject(
+                -4164,
+                "Order's notional must be no smaller than the minimum notional.",
+            ));
+        }
+        if !order.reduce_only
+            && order.size * price / Decimal::from(position.leverage) > cash_available
+        {
+            return Err(reject(-2019, "Margin is insufficient."));
+        }
+
+        let mode = position.position_mode;
+        let last = position.symbol.market.last;
+        order.queue_time = order.create_time;
+        position.orders.insert(order.id.clone(), order.clone());
+        let response = order_json(&order, mode);
+        state.order_update(&order, "NEW", None);
+
+        if order.r#type.is_conditional() {
+            return Ok(response);
+        }
+        let marketable = order.r#type == TradeType::Market
+            || !last.is_zero()
+                && if order.is_buy() {
+                    order.price >= last
+                } else {
+                    order.price <= last
+                };
+        match (marketable, order.time_in_force) {
+            (true, TimeInForce::Gtx) => {
+                let mut order = state
+                    .position_mut(&symbol)?
+                    .orders
+                    .remove(&order.id)
+                    .unwrap();
+                order.status = OrderStatus::Rejected;
+                state.order_finish(order, "EXPIRED");
+            }
+            (true, _) => self.fill(state, &symbol, &order.id, last, false),
+            (false, TimeInForce::Ioc | TimeInForce::Fok) => {
+                let mut order = state
+                    .position_mut(&symbol)?
+                    .orders
+                    .remove(&order.id)
+                    .unwrap();
+                order.status = OrderStatus::Canceled;
+                state.order_finish(order, "EXPIRED");
+            }
+            (false, _) => {}
+        }
+        Ok(response)
+    }
+
+    /// 按价格区间撮合交易对的挂单: 条件单触发后按触发价格成交或转为限价单, 限价单以限价成交
+    fn cross(&self, state: &mut MockState, symbol: &str, low: Decimal, high: Decimal) {
+        let Some(position) = state.account.positions.get(symbol) else {
+            return;
+        };
+        let mut orders = position.orders.values().cloned().collect::<Vec<Order>>();
+        orders.sort_by_key(|order| (order.queue_time, order.id.clone()));
+
+        for mut order in orders {
+            if order.status == OrderStatus::Created {
+                if order.stop_price < low || order.stop_price > high {
+                    continue;
+                }
+                order.status = OrderStatus::Submitted;
+                order.queue_time = Utc::now();
+                state
+                    .account
+                    .positions
+                    .get_mut(symbol)
+                    .unwrap()
+                    .orders
+                    .insert(order.id.clone(), order.clone());
+                if !order.r#type.is_limit() {
+                    self.fill(state, symbol, &order.id, order.stop_price, false);
+                    continue;
+                }
+                state.order_update(&order, "NEW", None);
+            }
+            let crossed = if order.is_buy() {
+                low <= order.price
+            } else {
+                high >= order.price
+            };
+            if crossed {
+                self.fill(state, symbol, &order.id, order.price, true);
+            }
+        }
+    }
+
+    /// 订单按价格成交剩余数量, 推送账户更新与成交
+    fn fill(&self, state: &mut MockState, symbol: &str, id: &str, price: Decimal, maker: bool) {
+        let position = state.account.positions.get_mut(symbol).unwrap();
+        let Some(mut order) = position.orders.remove(id) else {
+            return;
+        };
+        let remaining = if order.close_position {
+            position.position_mut(order.side).size
+        } else {
+            order.size - order.deal_size
+        };
+        let size = position.fill_size(&order, remaining);
+        if size.is_zero() {
+            order.status = OrderStatus::Canceled;
+            state.order_finish(order, "EXPIRED");
+            return;
+        }
+        let fee = size
+            * price
+            * if maker {
+                self.config.fee_rate_maker
+            } else {
+                self.config.fee_rate_taker
+            };
+        let pnl = position.fill(&mut order, price, size, fee);
+        if order.close_position {
+            order.status = OrderStatus::Filled;
+        }
+        state.account.cash += pnl - fee;
+        state.account_update(symbol, "ORDER", Decimal::ZERO);
+        let trade = MockTrade {
+            price,
+            size,
+            fee,
+            pnl,
+            maker,
+        };
+        if order.status == OrderStatus::Filled {
+            state.order_finish_trade(order, trade);
+        } else {
+            state.order_update(&order, "TRADE", Some(&trade));
+            state
+                .account
+                .positions
+                .get_mut(symbol)
+                .unwrap()
+                .orders
+                .insert(order.id.clone(), order);
+        }
+    }
+}
+
+/// 单次成交
+struct MockTrade {
+    price: Decimal,
+    size: Decimal,
+    fee: Decimal,
+    pnl: Decimal,
+    maker: bool,
+}
+
+impl MockState {
+    fn server_time(&self) -> i64 {
+        Utc::now().timestamp_millis() + self.time_offset
+    }
+
+    fn position(&self, symbol: &str) -> Result<&SymbolPosition, MockReject> {
+        self.account
+            .positions
+            .get(symbol)
+            .ok_or_else(|| reject(-1121, "Invalid symbol."))
+    }
+
+    fn position_mut(&mut self, symbol: &str) -> Result<&mut SymbolPosition, MockReject> {
+        self.account
+            .positions
+            .get_mut(symbol)
+            .ok_or_else(|| reject(-1121, "Invalid symbol."))
+    }
+
+    fn order(&self, symbol: &str, id: &str) -> Option<Order> {
+        self.account
+            .positions
+            .get(symbol)
+            .and_then(|position| position.orders.get(id))
+            .or_else(|| {
+                self.orders_finished
+                    .get(symbol)
+                    .and_then(|orders| orders.iter().rev().find(|order| order.id == id))
+            })
+            .cloned()
+    }
+
+    fn order_finished_exists(&self, symbol: &str, id: &str) -> bool {
+        self.orders_finished
+            .get(symbol)
+            .is_some_and(|orders| orders.iter().any(|order| order.id == id))
+    }
+
+    /// 推送数据流消息, 移除已断开的连接
+    fn publish(&mut self, stream: &str, data: Value) {
+        let text = json!({ "stream": stream, "data": data }).to_string();
+        self.clients.retain(|client| {
+            !client.streams.iter().any(|item| item == stream)
+                || client.tx.send(Message::text(text.clone())).is_ok()
+        });
+    }
+
+    /// 推送用户数据流消息
+    fn publish_user(&mut self, data: Value) {
+        if let Some(listen_key) = self.listen_key.clone() {
+            self.publish(&listen_key, data);
+        }
+    }
+
+    fn order_update(&mut self, order: &Order, execution_type: &str, trade: Option<&MockTrade>) {
+        let mode = self.position_mode;
+        let now = Utc::now().timestamp_millis();
+        let (type_current, type_orig) = order_types(order);
+        let mut update = json!({
+            "s": order.symbol,
+            "c": order.id,
+            "S": if order.is_buy() { "BUY" } else { "SELL" },
+            "o": type_current,
+            "ot": type_orig,
+            "f": time_in_force_to_binance(order.time_in_force),
+            "q": decimal_str(order.size),
+            "p": decimal_str(order.price),
+            "ap": decimal_str(order.deal_price),
+            "sp": decimal_str(order.stop_price),
+            "x": execution_type,
+            "X": order_status(order, execution_type),
+            "i": 0,
+            "l": "0",
+            "z": decimal_str(order.deal_size),
+            "L": "0",
+            "N": "USDT",
+            "n": "0",
+            "T": now,
+            "t": 0,
+            "m": false,
+            "R": order.reduce_only && mode == PositionMode::OneWay,
+            "wt": working_type_to_binance(order.working_type),
+            "ps": position_side_to_binance(mode, order.side),
+            "cp": order.close_position,
+            "rp": "0",
+        });
+        if let Some(trade) = trade {
+            update["l"] = json!(decimal_str(trade.size));
+            update["L"] = json!(decimal_str(trade.price));
+            update["n"] = json!(decimal_str(trade.fee));
+            update["m"] = json!(trade.maker);
+            update["rp"] = json!(decimal_str(trade.pnl));
+        }
+        self.publish_user(json!({
+            "e": "ORDER_TRADE_UPDATE",
+            "E": now,
+            "T": now,
+            "o": update,
+        }));
+    }
+
+    /// 归档已完成的订单并推送
+    fn order_finish(&mut self, order: Order, execution_type: &str) {
+        self.order_update(&order, execution_type, None);
+        self.orders_finished
+            .entry(order.symbol.clone())
+            .or_default()
+            .push(order);
+    }
+
+    fn order_finish_trade(&mut self, order: Order, trade: MockTrade) {
+        self.order_update(&order, "TRADE", Some(&trade));
+        self.orders_finished
+            .entry(order.symbol.clone())
+            .or_default()
+            .push(order);
+    }
+
+    /// 推送账户更新: 资金与交易对的持仓, 全仓的资金费用更新与币安相同不推送持仓
+    fn account_update(&mut self, symbol: &str, reason: &str, balance_change: Decimal) {
+        let Some(position) = self.account.positions.get(symbol) else {
+            return;
+        };
+        let positions = if reason == "FUNDING_FEE" && !position.is_isolated() {
+            Vec::new()
+        } else {
+            position_items(position)
+                .into_iter()
+                .map(|(position_side, amount, price)| {
+                    json!({
+                        "s": symbol,
+                        "pa": decimal_str(amount),
+                        "ep": decimal_str(price),
+                        "cr": "0",
+                        "up": "0",
+                        "mt": if position.is_isolated() { "isolated" } else { "cross" },
+                        "iw": decimal_str(isolated_wallet(position, position_side)),
+                        "ps": position_side,
+                    })
+                })
+                .collect::<Vec<Value>>()
+        };
+        let now = Utc::now().timestamp_millis();
+        let cash = decimal_str(self.account.cash);
+        self.publish_user(json!({
+            "e": "ACCOUNT_UPDATE",
+            "E": now,
+            "T": now,
+            "a": {
+                "m": reason,
+                "B": [{
+                    "a": "USDT",
+                    "wb": cash,
+                    "cw": cash,
+                    "bc": decimal_str(balance_change),
+                }],
+                "P": positions,
+            },
+        }));
+    }
+
+    fn exchange_info(&self) -> Value {
+        let mut symbols = self
+            .account
+            .positions
+            .values()
+            .collect::<Vec<&SymbolPosition>>();
+        symbols.sort_by_key(|position| position.symbol.symbol.clone());
+        json!({
+            "timezone": "UTC",
+            "serverTime": self.server_time(),
+            "symbols": symbols
+                .into_iter()
+                .map(|position| {
+                    let rule = &position.symbol.rule;
+                    json!({
+                        "symbol": position.symbol.symbol,
+                        "status": if position.symbol.enable { "TRADING" } else { "BREAK" },
+                        "filters": [
+                            {
+                                "filterType": "PRICE_FILTER",
+                                "minPrice": decimal_str(rule.price_min),
+                                "maxPrice": decimal_str(rule.price_max),
+                                "tickSize": decimal_str(rule.price_tick),
+                            },
+                            {
+                                "filterType": "LOT_SIZE",
+                                "minQty": decimal_str(rule.size_min),
+                                "maxQty": decimal_str(rule.size_max),
+                                "stepSize": decimal_str(rule.size_tick),
+                            },
+                            {
+                                "filterType": "MIN_NOTIONAL",
+                                "notional": decimal_str(rule.amount_min),
+                            },
+                            {
+                                "filterType": "MAX_NUM_ORDERS",
+                                "limit": rule.order_max,
+                            },
+                        ],
+                    })
+                })
+                .collect::<Vec<Value>>(),
+        })
+    }
+
+    fn leverage_brackets(&self) -> Value {
+        Value::Array(
+            self.account
+                .positions
+                .values()
+                .map(|position| {
+                    json!({
+                        "symbol": position.symbol.symbol,
+                        "brackets": position
+                            .symbol
+                            .rule
+                            .margin_tiers
+                            .iter()
+                            .enumerate()
+                            .map(|(i, tier)| {
+                                json!({
+                                    "bracket": i + 1,
+                                    "initialLeverage": tier.leverage_max,
+                                    "notionalCap": tier.notional_max.to_f64(),
+                                    "maintMarginRatio": tier.maintenance_rate.to_f64(),
+                                    "cum": tier.maintenance_amount.to_f64(),
+                                })
+                            })
+                            .collect::<Vec<Value>>(),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn premium_indexes(&self) -> Value {
+        Value::Array(
+            self.account
+                .positions
+                .values()
+                .map(|position| {
+                    let market = &position.symbol.market;
+                    json!({
+                        "symbol": position.symbol.symbol,
+                        "markPrice": decimal_str(market.mark),
+                        "indexPrice": decimal_str(market.index),
+                        "lastFundingRate": decimal_str(market.settlement),
+                        "nextFundingTime": market.settlement_time.timestamp_millis(),
+                        "time": self.server_time(),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn account_info(&self) -> Value {
+        let positions = self
+            .account
+            .positions
+            .values()
+            .flat_map(|position| {
+                position_items(position)
+                    .into_iter()
+                    .map(|(position_side, amount, price)| {
+                        json!({
+                            "symbol": position.symbol.symbol,
+                            "positionSide": position_side,
+                            "positionAmt": decimal_str(amount),
+                            "entryPrice": decimal_str(price),
+                            "leverage": position.leverage.to_string(),
+                            "isolated": position.is_isolated(),
+                            "isolatedWallet": decimal_str(isolated_wallet(position, position_side)),
+                            "unrealizedProfit": decimal_str(position.pnl()),
+                        })
+                    })
+                    .collect::<Vec<Value>>()
+            })
+            .collect::<Vec<Value>>();
+        json!({
+            "assets": [{
+                "asset": "USDT",
+                "walletBalance": decimal_str(self.account.cash),
+                "availableBalance": decimal_str(self.account.cash_available()),
+            }],
+            "positions": positions,
+        })
+    }
+}
+
+/// 按持仓模式拆分持仓: (持仓方向, 持仓数量(做空为负), 持仓均价)
+fn position_items(position: &SymbolPosition) -> Vec<(&'static str, Decimal, Decimal)> {
+    match position.position_mode {
+        PositionMode::Hedge => vec![
+            ("LONG", position.long.size, position.long.price),
+            ("SHORT", -position.short.size, position.short.price),
+        ],
+        PositionMode::OneWay => {
+            let amount = position.long.size - position.short.size;
+            let price = if position.long.size.is_zero() {
+                position.short.price
+            } else {
+                position.long.price
+            };
+            vec![("BOTH", amount, price)]
+        }
+    }
+}
+
+/// 持仓方向的逐仓钱包余额, 单向持仓为多空两个方向之和
+fn isolated_wallet(position: &SymbolPosition, position_side: &str) -> Decimal {
+    match position_side {
+        "LONG" => position.long.margin_isolated,
+        "SHORT" => position.short.margin_isolated,
+        _ => position.margin_isolated(),
+    }
+}
+
+/// 当前订单类型与原始订单类型, 条件单触发后当前类型为市价或限价
+fn order_types(order: &Order) -> (&'static str, &'static str) {
+    let orig = trade_type_to_binance(order.r#type);
+    if order.r#type.is_conditional() && order.status != OrderStatus::Created {
+        if order.r#type.is_limit() {
+            return ("LIMIT", orig);
+        }
+        return ("MARKET", orig);
+    }
+    (orig, orig)
+}
+
+fn order_status(order: &Order, execution_type: &str) -> &'static str {
+    match order.status {
+        OrderStatus::PartialFilled => "PARTIALLY_FILLED",
+        OrderStatus::Filled => "FILLED",
+        OrderStatus::Canceled if execution_type == "EXPIRED" => "EXPIRED",
+        OrderStatus::Canceled => "CANCELED",
+        OrderStatus::Rejected => "EXPIRED",
+        _ => "NEW",
+    }
+}
+
+fn working_type_to_binance(working_type: WorkingType) -> &'static str {
+    match working_type {
+        WorkingType::Last => "CONTRACT_PRICE",
+        WorkingType::Mark => "MARK_PRICE",
+    }
+}
+
+/// REST接口的订单格式
+fn order_json(order: &Order, mode: PositionMode) -> Value {
+    let (type_current, type_orig) = order_types(order);
+    json!({
+        "symbol": order.symbol,
+        "clientOrderId": order.id,
+        "status": order_status(order, ""),
+        "type": type_current,
+        "origType": type_orig,
+        "side": if order.is_buy() { "BUY" } else { "SELL" },
+        "positionSide": position_side_to_binance(mode, order.side),
+        "reduceOnly": order.reduce_only && mode == PositionMode::OneWay,
+        "closePosition": order.close_position,
+        "timeInForce": time_in_force_to_binance(order.time_in_force),
+        "price": decimal_str(order.price),
+        "origQty": decimal_str(order.size),
+        "executedQty": decimal_str(order.deal_size),
+        "avgPrice": decimal_str(order.deal_price),
+        "stopPrice": decimal_str(order.stop_price),
+        "workingType": working_type_to_binance(order.working_type),
+        "time": order.create_time.timestamp_millis(),
+        "updateTime": order.queue_time.timestamp_millis(),
+    })
+}
+
+fn decimal_str(value: Decimal) -> String {
+    value.normalize().to_string()
+}
+
+fn decimal_param(params: &HashMap<String, String>, key: &str) -> Result<Decimal, MockReject> {
+    params
+        .get(key)
+        .and_then(|value| Decimal::from_str_exact(value).ok())
+        .ok_or_else(|| {
+            reject(
+                -1102,
+                &format!("Mandatory parameter '{}' was not sent.", key),
+            )
+        })
+}
+
+fn url_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                match std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(byte) => {
+                        result.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    None => result.push(b'%'),
+                }
+            }
+            b'+' => result.push(b' '),
+            byte => result.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&result).to_string()
+}
+
+/// 数据流连接: 按请求路径中的streams参数订阅, 转发推送直到连接断开或被[`MockExchange::disconnect`]
+async fn ws_serve(exchange: Weak<MockExchange>, stream: TcpStream) {
+    let mut path = String::new();
+    #[allow(clippy::result_large_err)]
+    let callback = |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+        path = request.uri().to_string();
+        Ok(response)
+    };
+    let Ok(ws) = tokio_tungstenite::accept_hdr_async(stream, callback).await else {
+        return;
+    };
+    let streams = path
+        .split_once("streams=")
+        .map(|(_, streams)| streams.split('/').map(str::to_string).collect())
+        .unwrap_or_default();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    {
+        let Some(exchange) = exchange.upgrade() else {
+            return;
+        };
+        let mut state = exchange.state.lock();
+        state.connections += 1;
+        state.clients.push(MockClient { streams, tx });
+    }
+
+    let (mut sink, mut source) = ws.split();
+    loop {
+        tokio::select! {
+            message = rx.recv() => match message {
+                Some(message) => {
+                    if sink.send(message).await.is_err() {
+                        return;
+                    }
+                }
+                None => {
+                    let _ = sink.send(Message::Close(None)).await;
+                    return;
+                }
+            },
+            message = source.next() => match message {
+                Some(Ok(Message::Ping(payload))) => {
+                    let _ = sink.send(Message::Pong(payload)).await;
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
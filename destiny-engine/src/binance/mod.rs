@@ -0,0 +1,10 @@
+pub mod api;
+pub mod client;
+pub mod engine;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod rules;
+
+pub use client::*;
+pub use engine::*;
+pub use rules::*;
@@ -0,0 +1,112 @@
+use super::{api::*, client::*, engine::BINANCE_REST_MAINNET};
+use anyhow::{anyhow, ensure, Result};
+use chrono::{DateTime, Utc};
+use destiny_helpers::prelude::*;
+use destiny_types::prelude::*;
+use reqwest::Method;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Bound,
+    path::{Path, PathBuf},
+    time::Duration as StdDuration,
+};
+
+/// 交易规则快照, 由币安`/fapi/v1/exchangeInfo`的响应文件加载, 可按日期保存多个历史版本
+/// <br> 文件: 单个版本, 对全部时间生效
+/// <br> 目录: 每个以日期命名的文件(如`20240101.json`)为一个版本, 自该日起生效, 其他文件忽略
+/// <br> exchangeInfo不包含维持保证金档位, 档位使用[`SymbolRule::default`]
+#[derive(Debug, Clone)]
+pub struct SymbolRules {
+    versions: BTreeMap<DateTime<Utc>, HashMap<String, SymbolRule>>,
+}
+
+impl SymbolRules {
+    /// 缓存目录下的交易规则快照目录
+    pub fn cache_dir() -> Result<PathBuf> {
+        Ok(PathBuf::cache()?.join("exchangeInfo"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut versions = BTreeMap::new();
+        if path.is_dir() {
+            for entry in std::fs::read_dir(path)? {
+                let file = entry?.path();
+                if file.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                    continue;
+                }
+                let Some(date) = file
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .filter(|stem| stem.len() == 8)
+                    .and_then(|stem| stem.to_date().ok())
+                else {
+                    continue;
+                };
+                versions.insert(date, Self::parse(&file)?);
+            }
+        } else {
+            versions.insert(DateTime::<Utc>::MIN_UTC, Self::parse(path)?);
+        }
+        ensure!(
+            !versions.is_empty(),
+            "交易规则快照不存在: {}",
+            path.display()
+        );
+        Ok(Self { versions })
+    }
+
+    fn parse(path: &Path) -> Result<HashMap<String, SymbolRule>> {
+        let exchange_info =
+            serde_json::from_str::<BinanceExchangeInfo>(&std::fs::read_to_string(path)?)
+                .map_err(|err| anyhow!("交易规则快照解析失败({}): {}", path.display(), err))?;
+        Ok(exchange_info
+            .symbols
+            .iter()
+            .map(|info| (info.symbol.clone(), info.rule(SymbolRule::default())))
+            .collect())
+    }
+
+    /// 交易对在指定时间生效的交易规则: 不晚于该时间且包含该交易对的最新版本
+    /// <br> 不存在时使用包含该交易对的最早版本, 即上架前按上架时的规则
+    pub fn rule(&self, symbol: &str, time: DateTime<Utc>) -> Option<SymbolRule> {
+        self.versions
+            .range(..=time)
+            .rev()
+            .chain(
+                self.versions
+                    .range((Bound::Excluded(time), Bound::Unbounded)),
+            )
+            .find_map(|(_, rules)| rules.get(symbol))
+            .cloned()
+    }
+
+    /// 版本的生效日期, 升序排列
+    pub fn dates(&self) -> Vec<DateTime<Utc>> {
+        self.versions.keys().copied().collect()
+    }
+
+    /// 下载币安当前的exchangeInfo, 保存为目录下当日的版本, 返回文件路径
+    pub async fn sync(dir: &Path) -> Result<PathBuf> {
+        let client = BinanceClient::new(
+            BINANCE_REST_MAINNET,
+            "",
+            "",
+            5000,
+            StdDuration::from_secs(10),
+            3,
+        )?;
+        let exchange_info = client
+            .request::<serde_json::Value>(
+                Method::GET,
+                "/fapi/v1/exchangeInfo",
+                &[],
+                BinanceSecurity::None,
+            )
+            .await?;
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.json", Utc::now().str_ymd()));
+        std::fs::write(&path, serde_json::to_string(&exchange_info)?)?;
+        tracing::info!("交易规则快照已保存: {}", path.display());
+        Ok(path)
+    }
+}
@@ -0,0 +1,75 @@
+use destiny_types::prelude::*;
+use std::fmt;
+
+/// 订单错误
+/// <br> 通过[`anyhow::Error::downcast_ref`]区分具体的错误类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    /// 订单不存在
+    NotFound { symbol: String, id: String },
+    /// 订单已完成(已成交、已取消或已拒绝), 不能再操作
+    Finalized { id: String, status: OrderStatus },
+    /// 订单组不存在或已完成
+    GroupNotFound { symbol: String, id: String },
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::NotFound { symbol, id } => {
+                write!(f, "订单不存在: 交易对({}),订单({})", symbol, id)
+            }
+            OrderError::Finalized { id, status } => {
+                write!(f, "订单已完成: 订单({}),状态({})", id, status)
+            }
+            OrderError::GroupNotFound { symbol, id } => {
+                write!(f, "订单组不存在: 交易对({}),订单组({})", symbol, id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+/// 币安接口返回的错误
+/// <br> 通过[`anyhow::Error::downcast_ref`]获取HTTP状态码与币安错误码
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinanceError {
+    /// HTTP状态码
+    pub status: u16,
+    /// 币安错误码, 响应不是币安错误格式时为0
+    pub code: i64,
+    pub msg: String,
+}
+
+impl BinanceError {
+    /// 限流(429)、IP封禁(418)或服务端错误(5xx), 以及币安的断开连接(-1001)、限流(-1003)错误
+    pub fn is_retryable(&self) -> bool {
+        self.status == 418
+            || self.status == 429
+            || self.status >= 500
+            || matches!(self.code, -1001 | -1003)
+    }
+
+    /// 请求时间戳超出接收窗口(-1021), 需同步服务器时间
+    pub fn is_timestamp(&self) -> bool {
+        self.code == -1021
+    }
+
+    /// 执行状态未知(-1007): 请求已发送但未收到结果, 订单可能已成交
+    pub fn is_unknown(&self) -> bool {
+        self.code == -1007
+    }
+}
+
+impl fmt::Display for BinanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "币安接口错误: 状态码({}),错误码({}),信息({})",
+            self.status, self.code, self.msg
+        )
+    }
+}
+
+impl std::error::Error for BinanceError {}
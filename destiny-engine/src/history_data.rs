@@ -365,9 +365,7 @@ impl SyncHistoryMeta {
     }
 
     async fn sync0(&self) -> Result<()> {
-        let save_path = PathBuf::cache()?
-            .join("history_data")
-            .join(self.save_path());
+        let save_path = HistoryData::cache_dir()?.join(self.save_path());
         if !save_path.exists() {
             create_dir_all(&save_path).await?;
         }
@@ -504,6 +502,11 @@ pub trait DecodeCsvRecord {
 pub struct HistoryData;
 
 impl HistoryData {
+    /// 缓存目录下的历史数据目录, [`SyncHistoryData`]下载的数据保存于此
+    pub fn cache_dir() -> Result<PathBuf> {
+        Ok(PathBuf::cache()?.join("history_data"))
+    }
+
     pub async fn csv_read<D>(
         path: &PathBuf,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<D::T>> + Send>>>
@@ -656,6 +659,7 @@ impl DecodeCsvRecord for Kline {
             buy_size,
             buy_cash,
             trades,
+            interval: KlineInterval::M1,
         })
     }
 }
@@ -669,6 +673,7 @@ pub enum HistoryDataStreamType {
     PremiumIndexKlines,
 }
 
+/// 历史数据流, 按月读取`{目录}/{交易对}/{数据类型}/1m/{年月}.csv`(资金费率无`1m`层级)
 pub struct HistoryDataStream<D>
 where
     D: DecodeCsvRecord,
@@ -681,15 +686,33 @@ impl<D> HistoryDataStream<D>
 where
     D: DecodeCsvRecord,
 {
+    /// 从[`HistoryData::cache_dir`]加载
     pub fn new(
         symbol: String,
         r#type: HistoryDataStreamType,
         begin: DateTime<Utc>,
         end: DateTime<Utc>,
+    ) -> Result<Self> {
+        Ok(Self::with_path(
+            HistoryData::cache_dir()?,
+            symbol,
+            r#type,
+            begin,
+            end,
+        ))
+    }
+
+    /// 从指定的历史数据目录加载, 目录结构与[`HistoryData::cache_dir`]相同
+    pub fn with_path(
+        path: PathBuf,
+        symbol: String,
+        r#type: HistoryDataStreamType,
+        begin: DateTime<Utc>,
+        end: DateTime<Utc>,
     ) -> Self {
         let (tx, data_rx) = channel(10000);
         tokio::spawn(async move {
-            let base_path = PathBuf::cache()?.join("history_data").join(symbol);
+            let base_path = path.join(symbol);
             let base_path = match r#type {
                 HistoryDataStreamType::FundingRate => base_path.join("fundingRate"),
                 HistoryDataStreamType::IndexPriceKlines => {
@@ -729,6 +752,14 @@ where
         }
     }
 
+    /// 按时间顺序取出下一条数据
+    pub async fn next(&mut self) -> Option<D::T> {
+        match self.curr_data.take() {
+            Some(curr_data) => Some(curr_data),
+            None => self.data_rx.recv().await,
+        }
+    }
+
     pub async fn take(&mut self, date: DateTime<Utc>) -> Result<Option<D::T>> {
         if let Some(curr_data) = &self.curr_data {
             match curr_data.datetime().cmp(&date) {
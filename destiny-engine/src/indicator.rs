@@ -0,0 +1,451 @@
+use anyhow::{ensure, Result};
+use chrono::{DateTime, Utc};
+use destiny_types::prelude::*;
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+use std::collections::VecDeque;
+
+/// 指数移动平均(EMA)
+/// <br> 以前`period`个值的简单平均作为初始值, 之后按`2 / (period + 1)`平滑
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+#[derive(Debug, Clone)]
+pub struct Ema {
+    period: usize,
+    alpha: Decimal,
+    count: usize,
+    sum: Decimal,
+    value: Option<Decimal>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Result<Self> {
+        ensure!(period > 0, "指标周期必须大于0");
+        Ok(Self {
+            period,
+            alpha: Decimal::TWO / Decimal::from(period + 1),
+            count: 0,
+            sum: Decimal::ZERO,
+            value: None,
+        })
+    }
+}
+
+#[cfg_attr(feature = "python", pyo3::pymethods)]
+impl Ema {
+    #[cfg(feature = "python")]
+    #[new]
+    fn py_new(period: usize) -> Result<Self> {
+        Self::new(period)
+    }
+
+    /// 加入一个值, 返回最新的指标值
+    pub fn update(&mut self, value: Decimal) -> Option<Decimal> {
+        self.value = match self.value {
+            Some(ema) => Some(ema + self.alpha * (value - ema)),
+            None => {
+                self.count += 1;
+                self.sum += value;
+                (self.count == self.period).then(|| self.sum / Decimal::from(self.period))
+            }
+        };
+        self.value
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// 指标值, 加入的值不足`period`个时为空
+    pub fn value(&self) -> Option<Decimal> {
+        self.value
+    }
+}
+
+/// 相对强弱指数(RSI)
+/// <br> 采用Wilder平滑, 以前`period`个涨跌幅的简单平均作为初始值. 区间内无涨跌时为50
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    count: usize,
+    close: Option<Decimal>,
+    gain: Decimal,
+    loss: Decimal,
+    value: Option<Decimal>,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Result<Self> {
+        ensure!(period > 0, "指标周期必须大于0");
+        Ok(Self {
+            period,
+            count: 0,
+            close: None,
+            gain: Decimal::ZERO,
+            loss: Decimal::ZERO,
+            value: None,
+        })
+    }
+}
+
+#[cfg_attr(feature = "python", pyo3::pymethods)]
+impl Rsi {
+    #[cfg(feature = "python")]
+    #[new]
+    #[pyo3(signature = (period = 14))]
+    fn py_new(period: usize) -> Result<Self> {
+        Self::new(period)
+    }
+
+    /// 加入一个收盘价, 返回最新的指标值
+    pub fn update(&mut self, close: Decimal) -> Option<Decimal> {
+        let close_prev = self.close.replace(close)?;
+        let change = close - close_prev;
+        let gain = change.max(Decimal::ZERO);
+        let loss = (-change).max(Decimal::ZERO);
+        let period = Decimal::from(self.period);
+
+        if self.count < self.period {
+            self.count += 1;
+            self.gain += gain;
+            self.loss += loss;
+            if self.count < self.period {
+                return None;
+            }
+            self.gain /= period;
+            self.loss /= period;
+        } else {
+            self.gain = (self.gain * (period - Decimal::ONE) + gain) / period;
+            self.loss = (self.loss * (period - Decimal::ONE) + loss) / period;
+        }
+
+        let total = self.gain + self.loss;
+        self.value = Some(if total.is_zero() {
+            dec!(50)
+        } else {
+            dec!(100) * self.gain / total
+        });
+        self.value
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// 指标值(0~100), 加入的收盘价不足`period + 1`个时为空
+    pub fn value(&self) -> Option<Decimal> {
+        self.value
+    }
+}
+
+/// 平均真实波幅(ATR)
+/// <br> 真实波幅需要前一根K线的收盘价, 首根K线不计入. 采用Wilder平滑, 以前`period`个真实波幅的简单平均作为初始值
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    count: usize,
+    close: Option<Decimal>,
+    sum: Decimal,
+    value: Option<Decimal>,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Result<Self> {
+        ensure!(period > 0, "指标周期必须大于0");
+        Ok(Self {
+            period,
+            count: 0,
+            close: None,
+            sum: Decimal::ZERO,
+            value: None,
+        })
+    }
+}
+
+#[cfg_attr(feature = "python", pyo3::pymethods)]
+impl Atr {
+    #[cfg(feature = "python")]
+    #[new]
+    #[pyo3(signature = (period = 14))]
+    fn py_new(period: usize) -> Result<Self> {
+        Self::new(period)
+    }
+
+    /// 加入一根K线, 返回最新的指标值
+    pub fn update(&mut self, kline: &Kline) -> Option<Decimal> {
+        let close_prev = self.close.replace(kline.close)?;
+        let true_range = (kline.high - kline.low)
+            .max((kline.high - close_prev).abs())
+            .max((kline.low - close_prev).abs());
+        let period = Decimal::from(self.period);
+
+        self.value = match self.value {
+            Some(atr) => Some((atr * (period - Decimal::ONE) + true_range) / period),
+            None => {
+                self.count += 1;
+                self.sum += true_range;
+                (self.count == self.period).then(|| self.sum / period)
+            }
+        };
+        self.value
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// 指标值, 加入的K线不足`period + 1`根时为空
+    pub fn value(&self) -> Option<Decimal> {
+        self.value
+    }
+}
+
+/// 布林带指标值
+#[cfg_attr(feature = "python", pyo3::pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BollingerValue {
+    /// 上轨
+    pub upper: Decimal,
+    /// 中轨
+    pub middle: Decimal,
+    /// 下轨
+    pub lower: Decimal,
+}
+
+/// 布林带(Bollinger Bands)
+/// <br> 中轨为`period`个值的简单平均, 上下轨为中轨加减`multiplier`倍总体标准差
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    period: usize,
+    multiplier: Decimal,
+    values: VecDeque<Decimal>,
+    sum: Decimal,
+    sum_square: Decimal,
+    value: Option<BollingerValue>,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, multiplier: Decimal) -> Result<Self> {
+        ensure!(period > 0, "指标周期必须大于0");
+        ensure!(multiplier >= Decimal::ZERO, "标准差倍数必须大于等于0");
+        Ok(Self {
+            period,
+            multiplier,
+            values: VecDeque::with_capacity(period + 1),
+            sum: Decimal::ZERO,
+            sum_square: Decimal::ZERO,
+            value: None,
+        })
+    }
+}
+
+#[cfg_attr(feature = "python", pyo3::pymethods)]
+impl Bollinger {
+    #[cfg(feature = "python")]
+    #[new]
+    #[pyo3(signature = (period = 20, multiplier = Decimal::TWO))]
+    fn py_new(period: usize, multiplier: Decimal) -> Result<Self> {
+        Self::new(period, multiplier)
+    }
+
+    /// 加入一个值, 返回最新的指标值
+    pub fn update(&mut self, value: Decimal) -> Option<BollingerValue> {
+        self.values.push_back(value);
+        self.sum += value;
+        self.sum_square += value * value;
+        if self.values.len() > self.period {
+            if let Some(value) = self.values.pop_front() {
+                self.sum -= value;
+                self.sum_square -= value * value;
+            }
+        }
+        if self.values.len() < self.period {
+            return None;
+        }
+
+        let period = Decimal::from(self.period);
+        let middle = self.sum / period;
+        let variance = (self.sum_square / period - middle * middle).max(Decimal::ZERO);
+        let width = self.multiplier * variance.sqrt().unwrap_or_default();
+        self.value = Some(BollingerValue {
+            upper: middle + width,
+            middle,
+            lower: middle - width,
+        });
+        self.value
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    pub fn multiplier(&self) -> Decimal {
+        self.multiplier
+    }
+
+    /// 指标值, 加入的值不足`period`个时为空
+    pub fn value(&self) -> Option<BollingerValue> {
+        self.value
+    }
+}
+
+/// MACD指标值
+#[cfg_attr(feature = "python", pyo3::pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MacdValue {
+    /// 快线与慢线EMA之差(DIF)
+    pub macd: Decimal,
+    /// [`MacdValue::macd`]的EMA(DEA)
+    pub signal: Decimal,
+    /// 柱状图: `macd - signal`
+    pub histogram: Decimal,
+}
+
+/// 指数平滑异同移动平均(MACD)
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+    value: Option<MacdValue>,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Result<Self> {
+        ensure!(fast < slow, "快线周期必须小于慢线周期");
+        Ok(Self {
+            fast: Ema::new(fast)?,
+            slow: Ema::new(slow)?,
+            signal: Ema::new(signal)?,
+            value: None,
+        })
+    }
+}
+
+#[cfg_attr(feature = "python", pyo3::pymethods)]
+impl Macd {
+    #[cfg(feature = "python")]
+    #[new]
+    #[pyo3(signature = (fast = 12, slow = 26, signal = 9))]
+    fn py_new(fast: usize, slow: usize, signal: usize) -> Result<Self> {
+        Self::new(fast, slow, signal)
+    }
+
+    /// 加入一个值, 返回最新的指标值
+    pub fn update(&mut self, value: Decimal) -> Option<MacdValue> {
+        let fast = self.fast.update(value);
+        let slow = self.slow.update(value);
+        if let (Some(fast), Some(slow)) = (fast, slow) {
+            let macd = fast - slow;
+            if let Some(signal) = self.signal.update(macd) {
+                self.value = Some(MacdValue {
+                    macd,
+                    signal,
+                    histogram: macd - signal,
+                });
+            }
+        }
+        self.value
+    }
+
+    /// 指标值, 加入的值不足`slow + signal - 1`个时为空
+    pub fn value(&self) -> Option<MacdValue> {
+        self.value
+    }
+}
+
+/// 成交量加权平均价(VWAP)
+/// <br> 以成交额 / 成交量计算, 按[`Vwap::anchor`]周期重置, 为空时从首根K线起累计
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+#[derive(Debug, Clone)]
+pub struct Vwap {
+    anchor: Option<KlineInterval>,
+    open_time: Option<DateTime<Utc>>,
+    cash: Decimal,
+    size: Decimal,
+}
+
+impl Vwap {
+    pub fn new(anchor: Option<KlineInterval>) -> Self {
+        Self {
+            anchor,
+            open_time: None,
+            cash: Decimal::ZERO,
+            size: Decimal::ZERO,
+        }
+    }
+}
+
+#[cfg_attr(feature = "python", pyo3::pymethods)]
+impl Vwap {
+    #[cfg(feature = "python")]
+    #[new]
+    #[pyo3(signature = (anchor = Some(KlineInterval::D1)))]
+    fn py_new(anchor: Option<KlineInterval>) -> Self {
+        Self::new(anchor)
+    }
+
+    /// 加入一根K线, 返回最新的指标值
+    pub fn update(&mut self, kline: &Kline) -> Option<Decimal> {
+        let open_time = self.anchor.map(|anchor| anchor.open_time(kline.open_time));
+        if self.open_time != open_time {
+            self.open_time = open_time;
+            self.cash = Decimal::ZERO;
+            self.size = Decimal::ZERO;
+        }
+        self.cash += kline.cash;
+        self.size += kline.size;
+        self.value()
+    }
+
+    /// 重置周期
+    pub fn anchor(&self) -> Option<KlineInterval> {
+        self.anchor
+    }
+
+    /// 指标值, 当前周期内无成交时为空
+    pub fn value(&self) -> Option<Decimal> {
+        (!self.size.is_zero()).then(|| self.cash / self.size)
+    }
+}
+
+/// 可注册到引擎的指标, 见[`crate::traits::EngineInit::indicator_register`]
+/// <br> 引擎在每根已收盘K线通知前更新指标, 基于单个值的指标使用收盘价
+#[cfg_attr(feature = "python", derive(pyo3::FromPyObject, pyo3::IntoPyObject))]
+#[derive(Debug, Clone)]
+pub enum Indicator {
+    Ema(Ema),
+    Rsi(Rsi),
+    Atr(Atr),
+    Bollinger(Bollinger),
+    Macd(Macd),
+    Vwap(Vwap),
+}
+
+impl Indicator {
+    pub fn update(&mut self, kline: &Kline) {
+        match self {
+            Indicator::Ema(indicator) => {
+                indicator.update(kline.close);
+            }
+            Indicator::Rsi(indicator) => {
+                indicator.update(kline.close);
+            }
+            Indicator::Atr(indicator) => {
+                indicator.update(kline);
+            }
+            Indicator::Bollinger(indicator) => {
+                indicator.update(kline.close);
+            }
+            Indicator::Macd(indicator) => {
+                indicator.update(kline.close);
+            }
+            Indicator::Vwap(indicator) => {
+                indicator.update(kline);
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use crate::indicator::Indicator;
+use anyhow::{ensure, Result};
+use chrono::{DateTime, Duration, Utc};
+use destiny_types::prelude::*;
+use rust_decimal::prelude::ToPrimitive;
+use std::collections::{HashMap, VecDeque};
+
+/// K线聚合器: 由1分钟K线聚合出指定周期的K线
+pub struct KlineAggregator {
+    interval: KlineInterval,
+    current: Option<Kline>,
+}
+
+impl KlineAggregator {
+    pub fn new(interval: KlineInterval) -> Self {
+        Self {
+            interval,
+            current: None,
+        }
+    }
+
+    pub fn interval(&self) -> KlineInterval {
+        self.interval
+    }
+
+    /// 加入一根1分钟K线, 返回已收盘的K线
+    /// <br> 周期内最后一分钟的K线加入后即收盘; 数据缺失导致未收盘时, 在下一周期的首根K线加入时收盘
+    pub fn push(&mut self, kline: &Kline) -> Vec<Kline> {
+        let mut klines = Vec::new();
+        let open_time = self.interval.open_time(kline.open_time);
+
+        if let Some(current) = self.current.take() {
+            if current.open_time == open_time {
+                self.current = Some(current);
+            } else {
+                klines.push(current);
+            }
+        }
+
+        match self.current.as_mut() {
+            Some(current) => {
+                current.high = current.high.max(kline.high);
+                current.low = current.low.min(kline.low);
+                current.close = kline.close;
+                current.size += kline.size;
+                current.cash += kline.cash;
+                current.buy_size += kline.buy_size;
+                current.buy_cash += kline.buy_cash;
+                current.trades += kline.trades;
+            }
+            None => {
+                self.current = Some(Kline {
+                    open_time,
+                    interval: self.interval,
+                    ..kline.clone()
+                });
+            }
+        }
+
+        let close_time = self.interval.next_open_time(open_time);
+        if kline.open_time + Duration::minutes(1) >= close_time {
+            klines.extend(self.current.take());
+        }
+        klines
+    }
+}
+
+/// 已收盘K线的环形缓冲区, 超出容量时丢弃最早的K线
+pub struct KlineHistory {
+    capacity: usize,
+    klines: VecDeque<Kline>,
+}
+
+impl KlineHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            klines: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, kline: Kline) {
+        if self.klines.len() >= self.capacity {
+            self.klines.pop_front();
+        }
+        self.klines.push_back(kline);
+    }
+
+    /// 最近`limit`根K线, 按开盘时间升序
+    pub fn last(&self, limit: usize) -> Vec<Kline> {
+        let skip = self.klines.len().saturating_sub(limit);
+        self.klines.iter().skip(skip).cloned().collect()
+    }
+}
+
+/// 交易对的K线订阅、聚合、已收盘K线缓存与指标
+/// <br> 由1分钟K线驱动, 各引擎共用
+pub struct KlineFeed {
+    capacity: usize,
+    aggregators: HashMap<String, Vec<KlineAggregator>>,
+    histories: HashMap<(String, KlineInterval), KlineHistory>,
+    indicators: HashMap<(String, KlineInterval), HashMap<String, Indicator>>,
+}
+
+impl KlineFeed {
+    /// [`capacity`] : 每个交易对每个周期缓存的已收盘K线数量
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            aggregators: HashMap::new(),
+            histories: HashMap::new(),
+            indicators: HashMap::new(),
+        }
+    }
+
+    /// 交易对默认仅订阅1分钟K线
+    pub fn symbol_init(&mut self, symbol: &str) {
+        self.aggregators.insert(
+            symbol.to_string(),
+            vec![KlineAggregator::new(KlineInterval::M1)],
+        );
+    }
+
+    /// 替换交易对的订阅, 仍订阅的周期保留未收盘的K线
+    pub fn subscribe(&mut self, symbol: &str, intervals: &[KlineInterval]) -> Result<()> {
+        ensure!(!intervals.is_empty(), "K线周期不能为空");
+        let mut intervals = intervals.to_vec();
+        intervals.sort();
+        intervals.dedup();
+        let mut aggregators = self.aggregators.remove(symbol).unwrap_or_default();
+        let aggregators = intervals
+            .into_iter()
+            .map(|interval| {
+                aggregators
+                    .iter()
+                    .position(|aggregator| aggregator.interval() == interval)
+                    .map(|index| aggregators.swap_remove(index))
+                    .unwrap_or_else(|| KlineAggregator::new(interval))
+            })
+            .collect();
+        self.aggregators.insert(symbol.to_string(), aggregators);
+        Ok(())
+    }
+
+    /// 交易对已订阅的周期, 从小到大
+    pub fn intervals(&self, symbol: &str) -> Vec<KlineInterval> {
+        self.aggregators
+            .get(symbol)
+            .map(|aggregators| aggregators.iter().map(|a| a.interval()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn indicator_register(
+        &mut self,
+        symbol: &str,
+        interval: KlineInterval,
+        name: &str,
+        indicator: Indicator,
+    ) -> Result<()> {
+        ensure!(
+            self.intervals(symbol).contains(&interval),
+            "K线周期未订阅: {} {}",
+            symbol,
+            interval
+        );
+        let indicators = self
+            .indicators
+            .entry((symbol.to_string(), interval))
+            .or_default();
+        ensure!(
+            !indicators.contains_key(name),
+            "重复注册指标: {} {} {}",
+            symbol,
+            interval,
+            name
+        );
+        indicators.insert(name.to_string(), indicator);
+        Ok(())
+    }
+
+    /// 加入一根1分钟K线, 返回各订阅周期已收盘的K线, 按周期从小到大
+    /// <br> 已收盘的K线在返回前加入缓存并更新指标
+    pub fn push(&mut self, kline: &Kline) -> Vec<Kline> {
+        let klines = self
+            .aggregators
+            .get_mut(&kline.symbol)
+            .map(|aggregators| {
+                aggregators
+                    .iter_mut()
+                    .flat_map(|aggregator| aggregator.push(kline))
+                    .collect::<Vec<Kline>>()
+            })
+            .unwrap_or_default();
+        for kline in klines.iter() {
+            let key = (kline.symbol.clone(), kline.interval);
+            if let Some(indicators) = self.indicators.get_mut(&key) {
+                indicators
+                    .values_mut()
+                    .for_each(|indicator| indicator.update(kline));
+            }
+            self.histories
+                .entry(key)
+                .or_insert_with(|| KlineHistory::new(self.capacity))
+                .push(kline.clone());
+        }
+        klines
+    }
+
+    pub fn klines(&self, symbol: &str, interval: KlineInterval, limit: usize) -> Vec<Kline> {
+        self.histories
+            .get(&(symbol.to_string(), interval))
+            .map(|history| history.last(limit))
+            .unwrap_or_default()
+    }
+
+    pub fn indicator(
+        &self,
+        symbol: &str,
+        interval: KlineInterval,
+        name: &str,
+    ) -> Option<Indicator> {
+        self.indicators
+            .get(&(symbol.to_string(), interval))
+            .and_then(|indicators| indicators.get(name))
+            .cloned()
+    }
+}
+
+/// 按列存储的K线, 便于批量计算指标
+#[cfg_attr(feature = "python", pyo3::pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Default)]
+pub struct KlineColumns {
+    pub open_time: Vec<DateTime<Utc>>,
+    pub open: Vec<f64>,
+    pub high: Vec<f64>,
+    pub low: Vec<f64>,
+    pub close: Vec<f64>,
+    pub size: Vec<f64>,
+    pub cash: Vec<f64>,
+    pub buy_size: Vec<f64>,
+    pub buy_cash: Vec<f64>,
+    pub trades: Vec<i64>,
+}
+
+impl From<&[Kline]> for KlineColumns {
+    fn from(klines: &[Kline]) -> Self {
+        let mut columns = KlineColumns::default();
+        for kline in klines {
+            columns.open_time.push(kline.open_time);
+            columns.open.push(kline.open.to_f64().unwrap_or_default());
+            columns.high.push(kline.high.to_f64().unwrap_or_default());
+            columns.low.push(kline.low.to_f64().unwrap_or_default());
+            columns.close.push(kline.close.to_f64().unwrap_or_default());
+            columns.size.push(kline.size.to_f64().unwrap_or_default());
+            columns.cash.push(kline.cash.to_f64().unwrap_or_default());
+            columns
+                .buy_size
+                .push(kline.buy_size.to_f64().unwrap_or_default());
+            columns
+                .buy_cash
+                .push(kline.buy_cash.to_f64().unwrap_or_default());
+            columns.trades.push(kline.trades);
+        }
+        columns
+    }
+}
+
+#[cfg(feature = "python")]
+#[pyo3::pymethods]
+impl KlineColumns {
+    fn __len__(&self) -> usize {
+        self.open_time.len()
+    }
+}
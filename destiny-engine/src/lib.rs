@@ -1,4 +1,13 @@
 pub mod backtest;
+pub mod binance;
+pub mod error;
 pub mod history_data;
+pub mod indicator;
+pub mod kline;
+pub mod matching;
+pub mod paper;
 pub mod prelude;
+pub mod report;
+pub mod slippage;
+pub mod snapshot;
 pub mod traits;
@@ -0,0 +1,126 @@
+use destiny_types::prelude::*;
+use rust_decimal::Decimal;
+use strum::Display;
+
+/// 撮合模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+pub enum MatchMode {
+    /// 收盘价撮合: 仅使用K线收盘价判断限价单是否成交, 以收盘价成交
+    Close,
+    /// K线路径撮合: 按开高低收路径判断限价单是否被触及, 以限价成交
+    Ohlc,
+}
+
+/// K线内价格路径假设
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+pub enum KlinePath {
+    /// 开 -> 高 -> 低 -> 收
+    OpenHighLowClose,
+    /// 开 -> 低 -> 高 -> 收
+    OpenLowHighClose,
+    /// 距离开盘价较近的极值先到达
+    Nearest,
+}
+
+/// K线路径撮合器
+pub struct KlineMatcher {
+    points: [Decimal; 4],
+}
+
+impl KlineMatcher {
+    pub fn new(kline: &Kline, path: KlinePath) -> Self {
+        let high_first = match path {
+            KlinePath::OpenHighLowClose => true,
+            KlinePath::OpenLowHighClose => false,
+            KlinePath::Nearest => kline.high - kline.open <= kline.open - kline.low,
+        };
+        let points = if high_first {
+            [kline.open, kline.high, kline.low, kline.close]
+        } else {
+            [kline.open, kline.low, kline.high, kline.close]
+        };
+        Self { points }
+    }
+
+    /// 开盘价
+    pub fn open(&self) -> Decimal {
+        self.points[0]
+    }
+
+    /// 收盘价
+    pub fn close(&self) -> Decimal {
+        self.points[3]
+    }
+
+    /// 路径终点(收盘价)的位置
+    pub fn end(&self) -> Decimal {
+        self.points
+            .windows(2)
+            .map(|segment| (segment[1] - segment[0]).abs())
+            .sum()
+    }
+
+    /// 价格沿路径首次到达[`price`]的位置与成交价格
+    /// <br> [`rising`] 为真表示价格向上到达(卖出限价), 为假表示价格向下到达(买入限价)
+    /// <br> 位置为沿路径累计的价格变动量, 用于同一根K线内多个成交的先后排序
+    /// <br> 开盘即越过[`price`]时(跳空), 以开盘价成交, 位置为0
+    pub fn touch(&self, price: Decimal, rising: bool) -> Option<(Decimal, Decimal)> {
+        self.touch_from(price, rising, Decimal::ZERO)
+    }
+
+    /// 价格沿路径自位置[`from`]起首次到达[`price`]的位置与成交价格
+    /// <br> 位置[`from`]处的价格已越过[`price`]时, 以该处价格成交
+    pub fn touch_from(
+        &self,
+        price: Decimal,
+        rising: bool,
+        from: Decimal,
+    ) -> Option<(Decimal, Decimal)> {
+        let reached = |point: Decimal| {
+            if rising {
+                point >= price
+            } else {
+                point <= price
+            }
+        };
+
+        let mut distance = Decimal::ZERO;
+        for segment in self.points.windows(2) {
+            let (begin, end) = (segment[0], segment[1]);
+            let length = (end - begin).abs();
+            if distance + length < from {
+                distance += length;
+                continue;
+            }
+            let offset = (from - distance).max(Decimal::ZERO);
+            let start = if end >= begin {
+                begin + offset
+            } else {
+                begin - offset
+            };
+            if reached(start) {
+                return Some((distance + offset, start));
+            }
+            if reached(end) {
+                return Some((distance + (price - begin).abs(), price));
+            }
+            distance += length;
+        }
+        None
+    }
+
+    /// 限价单在K线内的成交位置与成交价格
+    pub fn touch_limit(&self, is_buy: bool, price: Decimal) -> Option<(Decimal, Decimal)> {
+        self.touch(price, !is_buy)
+    }
+
+    /// 限价单自位置[`from`]起的成交位置与成交价格
+    pub fn touch_limit_from(
+        &self,
+        is_buy: bool,
+        price: Decimal,
+        from: Decimal,
+    ) -> Option<(Decimal, Decimal)> {
+        self.touch_from(price, !is_buy, from)
+    }
+}
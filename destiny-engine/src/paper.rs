@@ -0,0 +1,634 @@
+use crate::{
+    backtest::*,
+    binance::{api::*, engine::*},
+    indicator::*,
+    snapshot::*,
+    traits::*,
+};
+use anyhow::{ensure, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use derive_builder::Builder;
+use destiny_types::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+    time::Duration as StdDuration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    sync::{mpsc, watch, Notify},
+    time::Instant,
+};
+
+/// 模拟盘行情来源
+#[derive(Debug, Clone)]
+pub enum PaperSource {
+    /// 币安U本位合约公开数据流(1分钟K线与标记价格), 地址为空时使用主网
+    /// <br> 以启动时间作为开始时间
+    Binance { ws_url: Option<String> },
+    /// 本地回放文件: 每行一条与币安组合数据流格式相同的消息
+    /// <br> 按文件顺序回放, 以已收盘K线的收盘时间作为引擎时间. [`begin`]为回放的开始时间, 初始化交易对时按此时间选择交易规则
+    Replay { path: PathBuf, begin: DateTime<Utc> },
+}
+
+/// 模拟盘配置
+#[derive(Builder)]
+#[builder(setter(into))]
+pub struct PaperConfig {
+    /// 行情来源
+    pub source: PaperSource,
+    /// 撮合、手续费、滑点、K线缓存、交易规则与定时事件参数, 含义与[`BacktestConfig`]相同
+    /// <br> 开始时间由行情来源决定, 结束时间、账本导出、权益曲线与历史数据目录不使用
+    #[builder(default = BacktestConfigBuilder::default())]
+    pub backtest: BacktestConfigBuilder,
+    /// 状态文件路径: 启动时恢复资金、持仓、订单与账本, 每分钟及停止时保存. 为空时不保存
+    /// <br> 从状态文件恢复时忽略初始资金
+    #[builder(default = None)]
+    pub state_path: Option<PathBuf>,
+    /// 数据流断开后的重连间隔
+    #[builder(default = StdDuration::from_secs(1))]
+    pub reconnect_interval: StdDuration,
+}
+
+/// 模拟盘状态
+#[derive(Serialize, Deserialize)]
+struct PaperState {
+    cash: Decimal,
+    positions: HashMap<String, PaperPosition>,
+    ledgers: HashMap<String, SymbolLedger>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PaperPosition {
+    leverage: u32,
+    position_mode: PositionMode,
+    margin_mode: MarginMode,
+    long: Position,
+    short: Position,
+    orders: Vec<Order>,
+    groups: Vec<OrderGroup>,
+}
+
+/// 模拟盘引擎: 实时行情, 与[`Backtest`]相同的撮合、手续费、资金费用与强平规则
+/// <br> 以1分钟K线收盘撮合订单, 未收盘的K线仅更新最新价格; 资金费用在标记价格推送的下次结算时间变化时按结算前的资金费率结算
+/// <br> 实时行情以系统时间作为引擎时间, 每分钟整点执行强平检查与定时事件
+pub struct Paper {
+    config: Arc<PaperConfig>,
+    backtest: Arc<Backtest>,
+    stopped: AtomicBool,
+    stop_notify: Notify,
+}
+
+impl Engine for Paper {
+    fn time(&self) -> DateTime<Utc> {
+        self.backtest.time()
+    }
+
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        self.stop_notify.notify_one();
+    }
+}
+
+impl Paper {
+    pub fn new(config: PaperConfig, strategy: Arc<dyn Strategy>) -> Result<Arc<Paper>> {
+        let begin = match &config.source {
+            PaperSource::Binance { .. } => Utc::now(),
+            PaperSource::Replay { begin, .. } => *begin,
+        }
+        .duration_trunc(Duration::minutes(1))?;
+        // 模拟盘持续运行直到停止, 没有结束时间
+        let backtest = Backtest::new(
+            config.backtest.clone().begin(begin).end(begin).build()?,
+            strategy,
+        )?;
+
+        let paper = Arc::new(Paper {
+            config: Arc::new(config),
+            backtest,
+            stopped: AtomicBool::new(false),
+            stop_notify: Notify::new(),
+        });
+        let engine: Weak<dyn Engine> = Arc::downgrade(&paper) as Weak<Paper>;
+        paper.backtest.engine_set(engine);
+        Ok(paper)
+    }
+
+    /// 运行直到[`Engine::stop`], 回放模式下回放完成后停止
+    pub async fn run(config: PaperConfig, strategy: Arc<dyn Strategy>) -> Result<()> {
+        Self::new(config, strategy)?.run0().await
+    }
+
+    pub async fn run0(self: &Arc<Self>) -> Result<()> {
+        let strategy = self.backtest.strategy.clone();
+        strategy.on_init(self.clone()).await?;
+        ensure!(
+            !self.backtest.account.lock().positions.is_empty(),
+            "未初始化交易对"
+        );
+        if let Some(path) = self.config.state_path.as_ref() {
+            if path.exists() {
+                self.state_load(path)?;
+            }
+        }
+
+        strategy.on_start(self.clone()).await?;
+        self.backtest.on_order_pending().await?;
+
+        let result = match &self.config.source {
+            PaperSource::Binance { ws_url } => {
+                let ws_url = ws_url.as_deref().unwrap_or(BINANCE_WS_MAINNET);
+                self.run_stream(ws_url.trim_end_matches('/')).await
+            }
+            PaperSource::Replay { path, .. } => self.run_replay(path).await,
+        };
+
+        self.backtest.on_stop().await?;
+        self.state_save()?;
+        result
+    }
+
+    async fn run_stream(self: &Arc<Self>, ws_url: &str) -> Result<()> {
+        let mut streams = self
+            .symbols()
+            .iter()
+            .flat_map(|symbol| {
+                let symbol = symbol.to_lowercase();
+                [
+                    format!("{}@kline_1m", symbol),
+                    format!("{}@markPrice@1s", symbol),
+                ]
+            })
+            .collect::<Vec<String>>();
+        streams.sort();
+        let (_url_tx, url_rx) =
+            watch::channel(format!("{}/stream?streams={}", ws_url, streams.join("/")));
+        let (event_tx, mut event_rx) = mpsc::channel(4096);
+        let stream = tokio::spawn(stream_run(url_rx, event_tx, self.config.reconnect_interval));
+
+        let mut minute = Utc::now().duration_trunc(Duration::minutes(1))? + Duration::minutes(1);
+        let result = loop {
+            if self.stopped.load(Ordering::Relaxed) {
+                break Ok(());
+            }
+            let minute_wait = (minute - Utc::now()).to_std().unwrap_or_default();
+            tokio::select! {
+                _ = self.stop_notify.notified() => {}
+                event = event_rx.recv() => match event {
+                    Some(StreamEvent::Connected) => {}
+                    Some(StreamEvent::Message(text)) => {
+                        *self.backtest.trade_time.lock() = Utc::now();
+                        if let Err(err) = self.on_message(&text, None).await {
+                            break Err(err);
+                        }
+                    }
+                    None => break Err(anyhow::anyhow!("数据流已关闭")),
+                },
+                _ = tokio::time::sleep_until(Instant::now() + minute_wait) => {
+                    *self.backtest.trade_time.lock() = minute;
+                    if let Err(err) = self.on_minute(minute).await {
+                        break Err(err);
+                    }
+                    minute += Duration::minutes(1);
+                }
+            }
+        };
+        stream.abort();
+        result
+    }
+
+    async fn run_replay(self: &Arc<Self>, path: &Path) -> Result<()> {
+        let mut lines = BufReader::new(tokio::fs::File::open(path).await?).lines();
+        let mut minute = None;
+        while let Some(line) = lines.next_line().await? {
+            if self.stopped.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            self.on_message(&line, Some(&mut minute)).await?;
+        }
+        if let Some(minute) = minute {
+            self.on_minute(minute).await?;
+        }
+        Ok(())
+    }
+
+    /// 处理行情消息, 回放模式下[`minute`]为当前分钟, 收到下一分钟的K线时先执行当前分钟的事件
+    async fn on_message(
+        self: &Arc<Self>,
+        text: &str,
+        minute: Option<&mut Option<DateTime<Utc>>>,
+    ) -> Result<()> {
+        let message = match serde_json::from_str::<BinanceStreamMessage>(text) {
+            Ok(message) => message,
+            Err(err) => {
+                tracing::warn!("无法解析的行情消息({}): {}", err, text);
+                return Ok(());
+            }
+        };
+        match message.data {
+            BinanceEvent::Kline { kline } => {
+                if !self
+                    .backtest
+                    .account
+                    .lock()
+                    .positions
+                    .contains_key(&kline.symbol)
+                {
+                    return Ok(());
+                }
+                let closed = kline.closed;
+                let kline = kline.to_kline();
+                if !closed {
+                    let mut account = self.backtest.account.lock();
+                    let market = &mut account
+                        .positions
+                        .get_mut(&kline.symbol)
+                        .unwrap()
+                        .symbol
+                        .market;
+                    market.last = kline.close;
+                    return Ok(());
+                }
+                if let Some(minute) = minute {
+                    let time = kline.open_time + Duration::minutes(1);
+                    if *minute != Some(time) {
+                        if let Some(previous) = minute.replace(time) {
+                            self.on_minute(previous).await?;
+                        }
+                    }
+                    *self.backtest.trade_time.lock() = time;
+                }
+                self.backtest.flush_kline(&kline, None).await?;
+                self.backtest.on_order_pending().await?;
+            }
+            BinanceEvent::MarkPrice {
+                symbol,
+                mark_price,
+                index_price,
+                funding_rate,
+                next_funding_time,
+            } => {
+                let next_funding_time = time_from_millis(next_funding_time);
+                let settlement = {
+                    let mut account = self.backtest.account.lock();
+                    let Some(position) = account.positions.get_mut(&symbol) else {
+                        return Ok(());
+                    };
+                    let market = &mut position.symbol.market;
+                    market.mark = mark_price;
+                    market.index = index_price;
+                    let settlement = (market.settlement_time != DateTime::<Utc>::default()
+                        && next_funding_time > market.settlement_time)
+                        .then_some(market.settlement);
+                    market.settlement = funding_rate;
+                    market.settlement_time = next_funding_time;
+                    settlement
+                };
+                if let Some(rate) = settlement {
+                    self.backtest.settle_funding(&symbol, rate).await?;
+                    self.backtest.on_order_pending().await?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// 每分钟整点: 强平检查、定时事件, 并保存状态
+    async fn on_minute(self: &Arc<Self>, time: DateTime<Utc>) -> Result<()> {
+        *self.backtest.trade_time.lock() = time;
+        self.backtest.liquidate().await?;
+        self.backtest.on_order_pending().await?;
+        self.backtest.flush_timer(time).await?;
+        self.state_save()
+    }
+
+    /// 从状态文件恢复已初始化交易对的持仓、订单与账本, 交易规则与行情不恢复
+    fn state_load(&self, path: &Path) -> Result<()> {
+        let state = serde_json::from_str::<PaperState>(&std::fs::read_to_string(path)?)?;
+        let mut account = self.backtest.account.lock();
+        let mut ledgers = self.backtest.ledgers.lock();
+        account.cash = state.cash;
+        for (symbol, saved) in state.positions {
+            let Some(position) = account.positions.get_mut(&symbol) else {
+                tracing::warn!("状态文件中的交易对未初始化, 已忽略: {}", symbol);
+                continue;
+            };
+            position.leverage = saved.leverage;
+            position.position_mode = saved.position_mode;
+            position.margin_mode = saved.margin_mode;
+            position.long = saved.long;
+            position.short = saved.short;
+            position.orders = saved
+                .orders
+                .into_iter()
+                .map(|order| (order.id.clone(), order))
+                .collect();
+            position.groups = saved
+                .groups
+                .into_iter()
+                .map(|group| (group.id.clone(), group))
+                .collect();
+        }
+        for (symbol, ledger) in state.ledgers {
+            if let Some(item) = ledgers.get_mut(&symbol) {
+                *item = ledger;
+            }
+        }
+        tracing::info!("模拟盘状态已恢复: {}", path.display());
+        Ok(())
+    }
+
+    /// 保存状态, 先写入临时文件再替换, 避免中断时损坏状态文件
+    fn state_save(&self) -> Result<()> {
+        let Some(path) = self.config.state_path.as_ref() else {
+            return Ok(());
+        };
+        let json = {
+            let account = self.backtest.account.lock();
+            let ledgers = self.backtest.ledgers.lock();
+            serde_json::to_string(&PaperState {
+                cash: account.cash,
+                positions: account
+                    .positions
+                    .iter()
+                    .map(|(symbol, position)| {
+                        (
+                            symbol.clone(),
+                            PaperPosition {
+                                leverage: position.leverage,
+                                position_mode: position.position_mode,
+                                margin_mode: position.margin_mode,
+                                long: position.long.clone(),
+                                short: position.short.clone(),
+                                orders: position.orders.values().cloned().collect(),
+                                groups: position.groups.values().cloned().collect(),
+                            },
+                        )
+                    })
+                    .collect(),
+                ledgers: ledgers.clone(),
+            })?
+        };
+        let temp = path.with_extension("tmp");
+        std::fs::write(&temp, json)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+}
+
+impl EngineInit for Paper {
+    fn symbol_init(&self, symbol: &str) -> Result<()> {
+        self.backtest.symbol_init(symbol)
+    }
+    fn symbol_init_with_rule(&self, symbol: &str, rule: SymbolRule) -> Result<()> {
+        self.backtest.symbol_init_with_rule(symbol, rule)
+    }
+    fn kline_subscribe(&self, symbol: &str, intervals: &[KlineInterval]) -> Result<()> {
+        self.backtest.kline_subscribe(symbol, intervals)
+    }
+    fn indicator_register(
+        &self,
+        symbol: &str,
+        interval: KlineInterval,
+        name: &str,
+        indicator: Indicator,
+    ) -> Result<()> {
+        self.backtest
+            .indicator_register(symbol, interval, name, indicator)
+    }
+}
+
+impl EngineAccount for Paper {
+    fn snapshot(&self) -> AccountSnapshot {
+        self.backtest.snapshot()
+    }
+    fn order(&self, symbol: &str, id: &str) -> Option<Order> {
+        self.backtest.order(symbol, id)
+    }
+    fn orders(&self, symbol: &str) -> Vec<Order> {
+        self.backtest.orders(symbol)
+    }
+    fn orders_long(&self, symbol: &str) -> Vec<Order> {
+        self.backtest.orders_long(symbol)
+    }
+    fn orders_long_open(&self, symbol: &str) -> Vec<Order> {
+        self.backtest.orders_long_open(symbol)
+    }
+    fn orders_long_close(&self, symbol: &str) -> Vec<Order> {
+        self.backtest.orders_long_close(symbol)
+    }
+    fn orders_short(&self, symbol: &str) -> Vec<Order> {
+        self.backtest.orders_short(symbol)
+    }
+    fn orders_short_open(&self, symbol: &str) -> Vec<Order> {
+        self.backtest.orders_short_open(symbol)
+    }
+    fn orders_short_close(&self, symbol: &str) -> Vec<Order> {
+        self.backtest.orders_short_close(symbol)
+    }
+    fn leverage(&self, symbol: &str) -> u32 {
+        self.backtest.leverage(symbol)
+    }
+    fn cash(&self) -> Decimal {
+        self.backtest.cash()
+    }
+    fn cash_available(&self) -> Decimal {
+        self.backtest.cash_available()
+    }
+    fn cash_frozen(&self) -> Decimal {
+        self.backtest.cash_frozen()
+    }
+    fn margin(&self) -> Decimal {
+        self.backtest.margin()
+    }
+    fn pnl(&self) -> Decimal {
+        self.backtest.pnl()
+    }
+    fn long_price(&self, symbol: &str) -> Decimal {
+        self.backtest.long_price(symbol)
+    }
+    fn long_size(&self, symbol: &str) -> Decimal {
+        self.backtest.long_size(symbol)
+    }
+    fn long_size_available(&self, symbol: &str) -> Decimal {
+        self.backtest.long_size_available(symbol)
+    }
+    fn long_size_frozen(&self, symbol: &str) -> Decimal {
+        self.backtest.long_size_frozen(symbol)
+    }
+    fn long_margin(&self, symbol: &str) -> Decimal {
+        self.backtest.long_margin(symbol)
+    }
+    fn long_pnl(&self, symbol: &str) -> Decimal {
+        self.backtest.long_pnl(symbol)
+    }
+    fn short_price(&self, symbol: &str) -> Decimal {
+        self.backtest.short_price(symbol)
+    }
+    fn short_size(&self, symbol: &str) -> Decimal {
+        self.backtest.short_size(symbol)
+    }
+    fn short_size_available(&self, symbol: &str) -> Decimal {
+        self.backtest.short_size_available(symbol)
+    }
+    fn short_size_frozen(&self, symbol: &str) -> Decimal {
+        self.backtest.short_size_frozen(symbol)
+    }
+    fn short_margin(&self, symbol: &str) -> Decimal {
+        self.backtest.short_margin(symbol)
+    }
+    fn short_pnl(&self, symbol: &str) -> Decimal {
+        self.backtest.short_pnl(symbol)
+    }
+    fn symbols(&self) -> Vec<String> {
+        self.backtest.symbols()
+    }
+    fn symbol_pnl(&self, symbol: &str) -> Decimal {
+        self.backtest.symbol_pnl(symbol)
+    }
+    fn symbol_margin(&self, symbol: &str) -> Decimal {
+        self.backtest.symbol_margin(symbol)
+    }
+    fn fundings(&self, symbol: &str) -> Vec<Funding> {
+        self.backtest.fundings(symbol)
+    }
+    fn funding_fee(&self, symbol: &str) -> Decimal {
+        self.backtest.funding_fee(symbol)
+    }
+    fn orders_history(&self, symbol: &str, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Order> {
+        self.backtest.orders_history(symbol, begin, end)
+    }
+    fn trades(&self, symbol: &str, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Trade> {
+        self.backtest.trades(symbol, begin, end)
+    }
+    fn liquidation_price(&self, symbol: &str, side: TradeSide) -> Decimal {
+        self.backtest.liquidation_price(symbol, side)
+    }
+    fn position_mode(&self, symbol: &str) -> PositionMode {
+        self.backtest.position_mode(symbol)
+    }
+    fn margin_mode(&self, symbol: &str) -> MarginMode {
+        self.backtest.margin_mode(symbol)
+    }
+    fn margin_isolated(&self, symbol: &str) -> Decimal {
+        self.backtest.margin_isolated(symbol)
+    }
+    fn order_group(&self, symbol: &str, id: &str) -> Option<OrderGroup> {
+        self.backtest.order_group(symbol, id)
+    }
+    fn order_groups(&self, symbol: &str) -> Vec<OrderGroup> {
+        self.backtest.order_groups(symbol)
+    }
+}
+
+impl EngineMarket for Paper {
+    fn price_mark(&self, symbol: &str) -> Decimal {
+        self.backtest.price_mark(symbol)
+    }
+    fn price_last(&self, symbol: &str) -> Decimal {
+        self.backtest.price_last(symbol)
+    }
+    fn price_index(&self, symbol: &str) -> Decimal {
+        self.backtest.price_index(symbol)
+    }
+    fn price_settlement(&self, symbol: &str) -> Decimal {
+        self.backtest.price_settlement(symbol)
+    }
+    fn time_settlement(&self, symbol: &str) -> DateTime<Utc> {
+        self.backtest.time_settlement(symbol)
+    }
+    fn rule_price_min(&self, symbol: &str) -> Decimal {
+        self.backtest.rule_price_min(symbol)
+    }
+    fn rule_price_max(&self, symbol: &str) -> Decimal {
+        self.backtest.rule_price_max(symbol)
+    }
+    fn rule_price_tick(&self, symbol: &str) -> Decimal {
+        self.backtest.rule_price_tick(symbol)
+    }
+    fn rule_size_min(&self, symbol: &str) -> Decimal {
+        self.backtest.rule_size_min(symbol)
+    }
+    fn rule_size_max(&self, symbol: &str) -> Decimal {
+        self.backtest.rule_size_max(symbol)
+    }
+    fn rule_size_tick(&self, symbol: &str) -> Decimal {
+        self.backtest.rule_size_tick(symbol)
+    }
+    fn rule_amount_min(&self, symbol: &str) -> Decimal {
+        self.backtest.rule_amount_min(symbol)
+    }
+    fn rule_order_max(&self, symbol: &str) -> i64 {
+        self.backtest.rule_order_max(symbol)
+    }
+    fn klines(&self, symbol: &str, interval: KlineInterval, limit: usize) -> Vec<Kline> {
+        self.backtest.klines(symbol, interval, limit)
+    }
+    fn indicator(&self, symbol: &str, interval: KlineInterval, name: &str) -> Option<Indicator> {
+        self.backtest.indicator(symbol, interval, name)
+    }
+}
+
+#[async_trait]
+impl EngineExchange for Paper {
+    async fn order_place(&self, request: OrderRequest) -> Result<String> {
+        self.backtest.order_place(request).await
+    }
+    async fn order_amend(
+        &self,
+        symbol: &str,
+        id: &str,
+        price: Decimal,
+        size: Decimal,
+    ) -> Result<()> {
+        self.backtest.order_amend(symbol, id, price, size).await
+    }
+    async fn order_place_oco(&self, requests: Vec<OrderRequest>) -> Result<String> {
+        self.backtest.order_place_oco(requests).await
+    }
+    async fn order_place_bracket(
+        &self,
+        entry: OrderRequest,
+        stop_loss: Option<OrderRequest>,
+        take_profit: Option<OrderRequest>,
+    ) -> Result<String> {
+        self.backtest
+            .order_place_bracket(entry, stop_loss, take_profit)
+            .await
+    }
+    async fn order_group_cancel(&self, symbol: &str, id: &str) -> Result<()> {
+        self.backtest.order_group_cancel(symbol, id).await
+    }
+    async fn order_close(&self, symbol: &str, id: &str) -> Result<()> {
+        self.backtest.order_close(symbol, id).await
+    }
+    async fn order_cancel_many(&self, symbol: &str, ids: &[String]) -> Result<()> {
+        self.backtest.order_cancel_many(symbol, ids).await
+    }
+    async fn leverage_set(&self, symbol: &str, leverage: u32) -> Result<()> {
+        self.backtest.leverage_set(symbol, leverage).await
+    }
+    async fn position_mode_set(&self, symbol: &str, mode: PositionMode) -> Result<()> {
+        self.backtest.position_mode_set(symbol, mode).await
+    }
+    async fn margin_mode_set(&self, symbol: &str, mode: MarginMode) -> Result<()> {
+        self.backtest.margin_mode_set(symbol, mode).await
+    }
+    async fn margin_add(&self, symbol: &str, side: TradeSide, amount: Decimal) -> Result<()> {
+        self.backtest.margin_add(symbol, side, amount).await
+    }
+    async fn margin_remove(&self, symbol: &str, side: TradeSide, amount: Decimal) -> Result<()> {
+        self.backtest.margin_remove(symbol, side, amount).await
+    }
+}
@@ -1,4 +1,7 @@
-pub use crate::{backtest::*, history_data::*, traits::*};
+pub use crate::{
+    backtest::*, binance::*, error::*, history_data::*, indicator::*, kline::*, matching::*,
+    paper::*, report::*, slippage::*, snapshot::*, traits::*,
+};
 pub use anyhow::{anyhow, bail, ensure, Result};
 pub use async_trait::async_trait;
 pub use chrono::{DateTime, Duration, DurationRound, Utc};
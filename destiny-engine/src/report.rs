@@ -0,0 +1,365 @@
+use chrono::{serde::ts_milliseconds as serde_chrono, DateTime, Duration, Utc};
+use destiny_types::prelude::*;
+use rust_decimal::{prelude::*, serde::float as serde_decimal};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 一年的分钟数, 加密货币市场全年无休
+const MINUTES_PER_YEAR: f64 = 365.0 * 24.0 * 60.0;
+
+/// 权益曲线采样点
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all, frozen))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquityPoint {
+    /// 时间
+    #[serde(with = "serde_chrono")]
+    pub time: DateTime<Utc>,
+    /// 资金
+    #[serde(with = "serde_decimal")]
+    pub cash: Decimal,
+    /// 未实现盈亏
+    #[serde(with = "serde_decimal")]
+    pub pnl: Decimal,
+    /// 权益: 资金 + 未实现盈亏
+    #[serde(with = "serde_decimal")]
+    pub equity: Decimal,
+}
+
+/// 回测报告
+/// <br> 比率类指标无法计算时(如无波动、无亏损)为0
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all, frozen))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    /// 开始时间
+    #[serde(with = "serde_chrono")]
+    pub begin: DateTime<Utc>,
+    /// 结束时间
+    #[serde(with = "serde_chrono")]
+    pub end: DateTime<Utc>,
+    /// 初始权益
+    #[serde(with = "serde_decimal")]
+    pub equity_begin: Decimal,
+    /// 结束权益
+    #[serde(with = "serde_decimal")]
+    pub equity_end: Decimal,
+    /// 总收益率
+    pub total_return: f64,
+    /// 年化收益率
+    pub cagr: f64,
+    /// 最大回撤比例
+    pub max_drawdown: f64,
+    /// 最大回撤持续时间(分钟): 从回撤前高点到恢复至该高点(或回测结束)
+    pub max_drawdown_duration: i64,
+    /// 夏普比率(年化, 无风险利率为0)
+    pub sharpe: f64,
+    /// 索提诺比率(年化)
+    pub sortino: f64,
+    /// 卡玛比率: 年化收益率 / 最大回撤
+    pub calmar: f64,
+    /// 平仓成交笔数
+    pub trade_count: u64,
+    /// 胜率: 盈利的平仓成交笔数 / 平仓成交笔数
+    pub win_rate: f64,
+    /// 盈亏比: 平仓盈利合计 / 平仓亏损合计
+    pub profit_factor: f64,
+    /// 平仓成交的平均已实现盈亏, 不含手续费
+    #[serde(with = "serde_decimal")]
+    pub trade_avg: Decimal,
+    /// 持仓时间占比
+    pub exposure: f64,
+    /// 手续费合计, 含强平手续费
+    #[serde(with = "serde_decimal")]
+    pub fee_total: Decimal,
+    /// 资金费用合计, 正数为收入, 负数为支出
+    #[serde(with = "serde_decimal")]
+    pub funding_total: Decimal,
+    /// 权益曲线
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+impl BacktestReport {
+    /// 根据权益曲线与账本计算回测报告
+    /// <br> [`equity_curve`] : 按固定间隔采样的权益曲线, 至少包含开始与结束两个采样点
+    /// <br> [`interval`] : 权益曲线的采样间隔, 夏普比率与索提诺比率按该间隔年化
+    /// <br> [`exposure`] : 持仓时间占比
+    /// <br> [`ledgers`] : 各交易对账本
+    pub fn new(
+        equity_curve: Vec<EquityPoint>,
+        interval: Duration,
+        exposure: f64,
+        ledgers: &HashMap<String, SymbolLedger>,
+    ) -> Self {
+        let (begin, equity_begin) = equity_curve
+            .first()
+            .map(|point| (point.time, point.equity))
+            .unwrap_or_default();
+        let (end, equity_end) = equity_curve
+            .last()
+            .map(|point| (point.time, point.equity))
+            .unwrap_or_default();
+
+        let total_return = ratio(equity_end - equity_begin, equity_begin);
+        let years = (end - begin).num_minutes() as f64 / MINUTES_PER_YEAR;
+        let cagr = if years <= 0.0 || equity_begin <= Decimal::ZERO {
+            0.0
+        } else if equity_end <= Decimal::ZERO {
+            -1.0
+        } else {
+            (1.0 + total_return).powf(1.0 / years) - 1.0
+        };
+
+        let (max_drawdown, max_drawdown_duration) = max_drawdown(&equity_curve);
+        let (sharpe, sortino) = sharpe_sortino(&equity_curve, interval);
+        let calmar = if max_drawdown > 0.0 {
+            cagr / max_drawdown
+        } else {
+            0.0
+        };
+
+        // 带有已实现盈亏的成交视为平仓成交
+        let closes = ledgers
+            .values()
+            .flat_map(|ledger| ledger.trades.iter())
+            .filter(|trade| trade.reduce_only || !trade.pnl.is_zero())
+            .map(|trade| trade.pnl)
+            .collect::<Vec<Decimal>>();
+        let trade_count = closes.len() as u64;
+        let wins = closes.iter().filter(|pnl| **pnl > Decimal::ZERO).count();
+        let profit = closes
+            .iter()
+            .filter(|pnl| **pnl > Decimal::ZERO)
+            .sum::<Decimal>();
+        let loss = closes
+            .iter()
+            .filter(|pnl| **pnl < Decimal::ZERO)
+            .sum::<Decimal>()
+            .abs();
+        let win_rate = if trade_count > 0 {
+            wins as f64 / trade_count as f64
+        } else {
+            0.0
+        };
+        let profit_factor = ratio(profit, loss);
+        let trade_avg = if trade_count > 0 {
+            closes.iter().sum::<Decimal>() / Decimal::from(trade_count)
+        } else {
+            Decimal::ZERO
+        };
+
+        let fee_total = ledgers
+            .values()
+            .map(|ledger| {
+                ledger.trades.iter().map(|trade| trade.fee).sum::<Decimal>()
+                    + ledger
+                        .liquidations
+                        .iter()
+                        .map(|liquidation| liquidation.fee)
+                        .sum::<Decimal>()
+            })
+            .sum::<Decimal>();
+        let funding_total = ledgers
+            .values()
+            .map(|ledger| ledger.funding_fee())
+            .sum::<Decimal>();
+
+        Self {
+            begin,
+            end,
+            equity_begin,
+            equity_end,
+            total_return,
+            cagr,
+            max_drawdown,
+            max_drawdown_duration,
+            sharpe,
+            sortino,
+            calmar,
+            trade_count,
+            win_rate,
+            profit_factor,
+            trade_avg,
+            exposure,
+            fee_total,
+            funding_total,
+            equity_curve,
+        }
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pyo3::pymethods]
+impl BacktestReport {
+    #[pyo3(name = "to_json")]
+    fn py_to_json(&self) -> anyhow::Result<String> {
+        self.to_json()
+    }
+}
+
+/// 回测过程记录
+pub(crate) struct BacktestRecorder {
+    begin: DateTime<Utc>,
+    interval: Duration,
+    equity_curve: Vec<EquityPoint>,
+    minutes: u64,
+    minutes_exposed: u64,
+    /// 上次记录的时间
+    last: DateTime<Utc>,
+}
+
+impl BacktestRecorder {
+    pub fn new(begin: DateTime<Utc>, interval: Duration) -> Self {
+        Self {
+            begin,
+            interval,
+            equity_curve: Vec::new(),
+            minutes: 0,
+            minutes_exposed: 0,
+            last: begin - Duration::minutes(1),
+        }
+    }
+
+    /// 时钟推进的每个时间记录一次, 按采样间隔记录权益
+    pub fn record(&mut self, time: DateTime<Utc>, account: &Account) {
+        self.minutes += 1;
+        if exposed(account) {
+            self.minutes_exposed += 1;
+        }
+        if (time - self.begin).num_minutes() % self.interval.num_minutes() == 0 {
+            self.sample(time, account);
+        }
+        self.last = time;
+    }
+
+    /// 补充上次记录到[`time`]之间(不含)时钟跳过的分钟, 其间账户不变
+    pub fn skip_to(&mut self, time: DateTime<Utc>, account: &Account) {
+        let skipped = (time - self.last).num_minutes() - 1;
+        if skipped <= 0 {
+            return;
+        }
+        self.minutes += skipped as u64;
+        if exposed(account) {
+            self.minutes_exposed += skipped as u64;
+        }
+        let interval = self.interval.num_minutes();
+        let mut offset =
+            ((self.last - self.begin).num_minutes().div_euclid(interval) + 1) * interval;
+        while self.begin + Duration::minutes(offset) < time {
+            self.sample(self.begin + Duration::minutes(offset), account);
+            offset += interval;
+        }
+        self.last = time - Duration::minutes(1);
+    }
+
+    fn sample(&mut self, time: DateTime<Utc>, account: &Account) {
+        let pnl = account.pnl();
+        self.equity_curve.push(EquityPoint {
+            time,
+            cash: account.cash,
+            pnl,
+            equity: account.cash + pnl,
+        });
+    }
+
+    /// 结束记录, 补充结束时间的采样点并生成报告
+    pub fn finish(
+        mut self,
+        time: DateTime<Utc>,
+        account: &Account,
+        ledgers: &HashMap<String, SymbolLedger>,
+    ) -> BacktestReport {
+        if self.equity_curve.last().map(|point| point.time) != Some(time) {
+            self.sample(time, account);
+        }
+        let exposure = if self.minutes > 0 {
+            self.minutes_exposed as f64 / self.minutes as f64
+        } else {
+            0.0
+        };
+        BacktestReport::new(self.equity_curve, self.interval, exposure, ledgers)
+    }
+}
+
+/// 账户存在持仓
+fn exposed(account: &Account) -> bool {
+    account
+        .positions
+        .values()
+        .any(|position| !position.long.size.is_zero() || !position.short.size.is_zero())
+}
+
+fn ratio(numerator: Decimal, denominator: Decimal) -> f64 {
+    if denominator.is_zero() {
+        return 0.0;
+    }
+    (numerator / denominator).to_f64().unwrap_or_default()
+}
+
+/// 最大回撤比例与持续时间(分钟)
+fn max_drawdown(equity_curve: &[EquityPoint]) -> (f64, i64) {
+    let mut max_drawdown = 0.0;
+    let mut max_drawdown_peak = 0;
+    let mut max_drawdown_trough = 0;
+    let mut peak = 0;
+    for (i, point) in equity_curve.iter().enumerate() {
+        if point.equity > equity_curve[peak].equity {
+            peak = i;
+        }
+        let drawdown = ratio(
+            equity_curve[peak].equity - point.equity,
+            equity_curve[peak].equity,
+        );
+        if drawdown > max_drawdown {
+            max_drawdown = drawdown;
+            max_drawdown_peak = peak;
+            max_drawdown_trough = i;
+        }
+    }
+    if max_drawdown <= 0.0 {
+        return (0.0, 0);
+    }
+
+    let peak = &equity_curve[max_drawdown_peak];
+    let recovery = equity_curve[max_drawdown_trough..]
+        .iter()
+        .find(|point| point.equity >= peak.equity)
+        .or(equity_curve.last())
+        .unwrap();
+    (max_drawdown, (recovery.time - peak.time).num_minutes())
+}
+
+/// 年化夏普比率与索提诺比率
+/// <br> 仅使用间隔等于采样间隔的收益率, 结束时补充的不足一个间隔的采样点不参与计算
+fn sharpe_sortino(equity_curve: &[EquityPoint], interval: Duration) -> (f64, f64) {
+    let returns = equity_curve
+        .windows(2)
+        .filter(|points| points[1].time - points[0].time == interval)
+        .filter(|points| points[0].equity > Decimal::ZERO)
+        .map(|points| ratio(points[1].equity - points[0].equity, points[0].equity))
+        .collect::<Vec<f64>>();
+    if returns.len() < 2 || interval <= Duration::zero() {
+        return (0.0, 0.0);
+    }
+
+    let annualize = (MINUTES_PER_YEAR / interval.num_minutes() as f64).sqrt();
+
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let std = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    let downside = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+
+    let sharpe = if std > 0.0 {
+        mean / std * annualize
+    } else {
+        0.0
+    };
+    let sortino = if downside > 0.0 {
+        mean / downside * annualize
+    } else {
+        0.0
+    };
+    (sharpe, sortino)
+}
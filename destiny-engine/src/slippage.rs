@@ -0,0 +1,67 @@
+use destiny_types::prelude::*;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+/// 滑点
+pub trait Slippage: Send + Sync {
+    /// 计算单位滑点(价格偏移量,非负),由调用方按买卖方向施加到成交价格上
+    /// <br> [`rate`] : 滑点率
+    /// <br> [`is_buy`] : 是否为买入
+    /// <br> [`price`] : 成交价格
+    /// <br> [`size`] : 成交数量
+    /// <br> [`kline`] : 成交所在的K线
+    fn slippage(
+        &self,
+        rate: Decimal,
+        is_buy: bool,
+        price: Decimal,
+        size: Decimal,
+        kline: &Kline,
+    ) -> Decimal;
+}
+
+/// 滑点模型
+#[derive(Clone)]
+pub enum SlippageModel {
+    /// 固定比例: 成交价格 * 滑点率
+    Fixed,
+    /// 成交量比例: 成交价格 * 滑点率 * min(1, 成交数量 / 同方向主动成交量)
+    /// <br> 买入参考[`Kline::buy_size`],卖出参考[`Kline::size`] - [`Kline::buy_size`]
+    Volume,
+    /// 价差: (最高价 - 最低价) * 滑点率 / 2
+    /// <br> 以K线振幅的一部分作为买卖价差的估计,按半个价差计算滑点
+    Spread,
+    /// 自定义
+    Custom(Arc<dyn Slippage>),
+}
+
+impl Slippage for SlippageModel {
+    fn slippage(
+        &self,
+        rate: Decimal,
+        is_buy: bool,
+        price: Decimal,
+        size: Decimal,
+        kline: &Kline,
+    ) -> Decimal {
+        let slippage = match self {
+            SlippageModel::Fixed => price * rate,
+            SlippageModel::Volume => {
+                let volume = if is_buy {
+                    kline.buy_size
+                } else {
+                    kline.size - kline.buy_size
+                };
+                let participation = if volume > Decimal::ZERO {
+                    (size / volume).min(Decimal::ONE)
+                } else {
+                    Decimal::ONE
+                };
+                price * rate * participation
+            }
+            SlippageModel::Spread => (kline.high - kline.low) * rate / Decimal::TWO,
+            SlippageModel::Custom(slippage) => slippage.slippage(rate, is_buy, price, size, kline),
+        };
+        slippage.max(Decimal::ZERO)
+    }
+}
@@ -0,0 +1,320 @@
+use chrono::{DateTime, Utc};
+use destiny_types::prelude::*;
+use rayon::prelude::*;
+use rust_decimal::Decimal;
+
+/// 账户快照
+/// <br> 一次加锁复制的账户、持仓、订单与行情状态, 读取任意数量的字段均保持一致且无需再加锁
+/// <br> 创建快照时完整复制账户, 开销随交易对与挂单数量增长, 应在需要一致读取多个字段时使用
+/// <br> 方法与[`crate::traits::EngineAccount`]、[`crate::traits::EngineMarket`]的同名方法含义相同
+#[cfg_attr(feature = "python", pyo3::pyclass(frozen))]
+#[derive(Debug, Clone)]
+pub struct AccountSnapshot {
+    time: DateTime<Utc>,
+    account: Account,
+}
+
+impl AccountSnapshot {
+    pub fn new(time: DateTime<Utc>, account: Account) -> Self {
+        Self { time, account }
+    }
+
+    /// 账户
+    pub fn account(&self) -> &Account {
+        &self.account
+    }
+
+    /// 交易对持仓
+    pub fn position(&self, symbol: &str) -> Option<&SymbolPosition> {
+        self.account.positions.get(symbol)
+    }
+
+    fn orders_filter(&self, symbol: &str, filter: impl Fn(&Order) -> bool + Sync) -> Vec<Order> {
+        self.position(symbol)
+            .map(|position| {
+                position
+                    .orders
+                    .par_iter()
+                    .filter(|(_, order)| filter(order))
+                    .map(|(_, order)| order.to_owned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg_attr(feature = "python", pyo3::pymethods)]
+impl AccountSnapshot {
+    /// 快照时间
+    pub fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+
+    pub fn order(&self, symbol: &str, id: &str) -> Option<Order> {
+        self.position(symbol)
+            .and_then(|position| position.orders.get(id))
+            .cloned()
+    }
+
+    pub fn orders(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |_| true)
+    }
+
+    pub fn orders_long(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| order.side == TradeSide::Long)
+    }
+
+    pub fn orders_long_open(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| {
+            order.side == TradeSide::Long && !order.reduce_only
+        })
+    }
+
+    pub fn orders_long_close(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| {
+            order.side == TradeSide::Long && order.reduce_only
+        })
+    }
+
+    pub fn orders_short(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| order.side == TradeSide::Short)
+    }
+
+    pub fn orders_short_open(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| {
+            order.side == TradeSide::Short && !order.reduce_only
+        })
+    }
+
+    pub fn orders_short_close(&self, symbol: &str) -> Vec<Order> {
+        self.orders_filter(symbol, |order| {
+            order.side == TradeSide::Short && order.reduce_only
+        })
+    }
+
+    pub fn order_group(&self, symbol: &str, id: &str) -> Option<OrderGroup> {
+        self.position(symbol)
+            .and_then(|position| position.groups.get(id))
+            .cloned()
+    }
+
+    pub fn order_groups(&self, symbol: &str) -> Vec<OrderGroup> {
+        self.position(symbol)
+            .map(|position| position.groups.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn leverage(&self, symbol: &str) -> u32 {
+        self.position(symbol)
+            .map(|position| position.leverage)
+            .unwrap_or(1)
+    }
+
+    pub fn cash(&self) -> Decimal {
+        self.account.cash
+    }
+
+    pub fn cash_available(&self) -> Decimal {
+        self.account.cash_available()
+    }
+
+    pub fn cash_frozen(&self) -> Decimal {
+        self.account.cash_frozen()
+    }
+
+    pub fn margin(&self) -> Decimal {
+        self.account.margin()
+    }
+
+    pub fn pnl(&self) -> Decimal {
+        self.account.pnl()
+    }
+
+    pub fn long_price(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.long.price)
+            .unwrap_or_default()
+    }
+
+    pub fn long_size(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.long.size)
+            .unwrap_or_default()
+    }
+
+    pub fn long_size_available(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.long_size_available())
+            .unwrap_or_default()
+    }
+
+    pub fn long_size_frozen(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.long_size_frozen())
+            .unwrap_or_default()
+    }
+
+    pub fn long_margin(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.margin_long())
+            .unwrap_or_default()
+    }
+
+    pub fn long_pnl(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.long_pnl())
+            .unwrap_or_default()
+    }
+
+    pub fn short_price(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.short.price)
+            .unwrap_or_default()
+    }
+
+    pub fn short_size(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.short.size)
+            .unwrap_or_default()
+    }
+
+    pub fn short_size_available(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.short_size_available())
+            .unwrap_or_default()
+    }
+
+    pub fn short_size_frozen(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.short_size_frozen())
+            .unwrap_or_default()
+    }
+
+    pub fn short_margin(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.margin_short())
+            .unwrap_or_default()
+    }
+
+    pub fn short_pnl(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.short_pnl())
+            .unwrap_or_default()
+    }
+
+    pub fn symbols(&self) -> Vec<String> {
+        self.account.positions.keys().cloned().collect()
+    }
+
+    pub fn symbol_pnl(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.pnl())
+            .unwrap_or_default()
+    }
+
+    pub fn symbol_margin(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.margin())
+            .unwrap_or_default()
+    }
+
+    pub fn liquidation_price(&self, symbol: &str, side: TradeSide) -> Decimal {
+        self.account.liquidation_price(symbol, side)
+    }
+
+    pub fn position_mode(&self, symbol: &str) -> PositionMode {
+        self.position(symbol)
+            .map(|position| position.position_mode)
+            .unwrap_or(PositionMode::Hedge)
+    }
+
+    pub fn margin_mode(&self, symbol: &str) -> MarginMode {
+        self.position(symbol)
+            .map(|position| position.margin_mode)
+            .unwrap_or(MarginMode::Cross)
+    }
+
+    pub fn margin_isolated(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.margin_isolated())
+            .unwrap_or_default()
+    }
+
+    pub fn price_mark(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.symbol.market.mark)
+            .unwrap_or_default()
+    }
+
+    pub fn price_last(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.symbol.market.last)
+            .unwrap_or_default()
+    }
+
+    pub fn price_index(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.symbol.market.index)
+            .unwrap_or_default()
+    }
+
+    pub fn price_settlement(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.symbol.market.settlement)
+            .unwrap_or_default()
+    }
+
+    pub fn time_settlement(&self, symbol: &str) -> DateTime<Utc> {
+        self.position(symbol)
+            .map(|position| position.symbol.market.settlement_time)
+            .unwrap_or_default()
+    }
+
+    pub fn rule_price_min(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.symbol.rule.price_min)
+            .unwrap_or_default()
+    }
+
+    pub fn rule_price_max(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.symbol.rule.price_max)
+            .unwrap_or_default()
+    }
+
+    pub fn rule_price_tick(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.symbol.rule.price_tick)
+            .unwrap_or_default()
+    }
+
+    pub fn rule_size_min(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.symbol.rule.size_min)
+            .unwrap_or_default()
+    }
+
+    pub fn rule_size_max(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.symbol.rule.size_max)
+            .unwrap_or_default()
+    }
+
+    pub fn rule_size_tick(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.symbol.rule.size_tick)
+            .unwrap_or_default()
+    }
+
+    pub fn rule_amount_min(&self, symbol: &str) -> Decimal {
+        self.position(symbol)
+            .map(|position| position.symbol.rule.amount_min)
+            .unwrap_or_default()
+    }
+
+    pub fn rule_order_max(&self, symbol: &str) -> i64 {
+        self.position(symbol)
+            .map(|position| position.symbol.rule.order_max)
+            .unwrap_or_default()
+    }
+}
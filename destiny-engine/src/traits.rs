@@ -1,3 +1,4 @@
+use crate::{indicator::Indicator, snapshot::AccountSnapshot};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -12,9 +13,27 @@ pub trait Engine: EngineInit + EngineAccount + EngineMarket + EngineExchange + S
 
 pub trait EngineInit: Send + Sync {
     fn symbol_init(&self, symbol: &str) -> Result<()>;
+    /// 以自定义交易规则初始化交易对, 覆盖交易规则快照, 历史版本切换时保持不变
+    /// <br> 实盘的交易规则以交易所为准, 不支持自定义
+    fn symbol_init_with_rule(&self, symbol: &str, rule: SymbolRule) -> Result<()>;
+    /// 订阅交易对的K线周期, 替换已有订阅, 需在初始化交易对后调用. 默认仅订阅1分钟K线
+    /// <br> 各周期K线收盘后通过[`Strategy::on_kline`]通知, 同一时间按周期从小到大通知
+    fn kline_subscribe(&self, symbol: &str, intervals: &[KlineInterval]) -> Result<()>;
+    /// 注册指标, 在该周期每根已收盘K线通知前更新
+    /// <br> [`interval`]需已订阅, 同一交易对同一周期下[`name`]不能重复
+    fn indicator_register(
+        &self,
+        symbol: &str,
+        interval: KlineInterval,
+        name: &str,
+        indicator: Indicator,
+    ) -> Result<()>;
 }
 
 pub trait EngineAccount: Send + Sync {
+    /// 一次加锁获取账户快照, 读取多个字段时保持一致
+    /// <br> 快照完整复制账户, 仅读取单个字段时直接使用对应方法
+    fn snapshot(&self) -> AccountSnapshot;
     fn order(&self, symbol: &str, id: &str) -> Option<Order>;
     fn orders(&self, symbol: &str) -> Vec<Order>;
     fn orders_long(&self, symbol: &str) -> Vec<Order>;
@@ -44,6 +63,16 @@ pub trait EngineAccount: Send + Sync {
     fn symbols(&self) -> Vec<String>;
     fn symbol_pnl(&self, symbol: &str) -> Decimal;
     fn symbol_margin(&self, symbol: &str) -> Decimal;
+    fn fundings(&self, symbol: &str) -> Vec<Funding>;
+    fn funding_fee(&self, symbol: &str) -> Decimal;
+    fn orders_history(&self, symbol: &str, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Order>;
+    fn trades(&self, symbol: &str, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Trade>;
+    fn liquidation_price(&self, symbol: &str, side: TradeSide) -> Decimal;
+    fn position_mode(&self, symbol: &str) -> PositionMode;
+    fn margin_mode(&self, symbol: &str) -> MarginMode;
+    fn margin_isolated(&self, symbol: &str) -> Decimal;
+    fn order_group(&self, symbol: &str, id: &str) -> Option<OrderGroup>;
+    fn order_groups(&self, symbol: &str) -> Vec<OrderGroup>;
 }
 
 pub trait EngineMarket: Send + Sync {
@@ -60,28 +89,121 @@ pub trait EngineMarket: Send + Sync {
     fn rule_size_tick(&self, symbol: &str) -> Decimal;
     fn rule_amount_min(&self, symbol: &str) -> Decimal;
     fn rule_order_max(&self, symbol: &str) -> i64;
+    /// 最近`limit`根已收盘K线, 按开盘时间升序
+    /// <br> 仅保留已订阅周期的K线, 数量不超过引擎的K线缓存容量
+    fn klines(&self, symbol: &str, interval: KlineInterval, limit: usize) -> Vec<Kline>;
+    /// 已注册指标的当前状态, 见[`EngineInit::indicator_register`]
+    fn indicator(&self, symbol: &str, interval: KlineInterval, name: &str) -> Option<Indicator>;
 }
 
 #[async_trait]
 pub trait EngineExchange: Send + Sync {
-    async fn long_market_open(&self, symbol: &str, size: Decimal) -> Result<String>;
-    async fn long_limit_open(&self, symbol: &str, size: Decimal, price: Decimal) -> Result<String>;
-    async fn long_market_close(&self, symbol: &str, size: Decimal) -> Result<String>;
-    async fn long_limit_close(&self, symbol: &str, size: Decimal, price: Decimal)
-        -> Result<String>;
-    async fn short_market_open(&self, symbol: &str, size: Decimal) -> Result<String>;
-    async fn short_limit_open(&self, symbol: &str, size: Decimal, price: Decimal)
-        -> Result<String>;
-    async fn short_market_close(&self, symbol: &str, size: Decimal) -> Result<String>;
+    /// 下单, 返回订单ID
+    async fn order_place(&self, request: OrderRequest) -> Result<String>;
+    async fn long_market_open(&self, symbol: &str, size: Decimal) -> Result<String> {
+        self.order_place(OrderRequest::market(symbol, TradeSide::Long, false, size))
+            .await
+    }
+    async fn long_limit_open(&self, symbol: &str, size: Decimal, price: Decimal) -> Result<String> {
+        self.order_place(OrderRequest::limit(
+            symbol,
+            TradeSide::Long,
+            false,
+            size,
+            price,
+        ))
+        .await
+    }
+    async fn long_market_close(&self, symbol: &str, size: Decimal) -> Result<String> {
+        self.order_place(OrderRequest::market(symbol, TradeSide::Long, true, size))
+            .await
+    }
+    async fn long_limit_close(
+        &self,
+        symbol: &str,
+        size: Decimal,
+        price: Decimal,
+    ) -> Result<String> {
+        self.order_place(OrderRequest::limit(
+            symbol,
+            TradeSide::Long,
+            true,
+            size,
+            price,
+        ))
+        .await
+    }
+    async fn short_market_open(&self, symbol: &str, size: Decimal) -> Result<String> {
+        self.order_place(OrderRequest::market(symbol, TradeSide::Short, false, size))
+            .await
+    }
+    async fn short_limit_open(
+        &self,
+        symbol: &str,
+        size: Decimal,
+        price: Decimal,
+    ) -> Result<String> {
+        self.order_place(OrderRequest::limit(
+            symbol,
+            TradeSide::Short,
+            false,
+            size,
+            price,
+        ))
+        .await
+    }
+    async fn short_market_close(&self, symbol: &str, size: Decimal) -> Result<String> {
+        self.order_place(OrderRequest::market(symbol, TradeSide::Short, true, size))
+            .await
+    }
     async fn short_limit_close(
         &self,
         symbol: &str,
         size: Decimal,
         price: Decimal,
+    ) -> Result<String> {
+        self.order_place(OrderRequest::limit(
+            symbol,
+            TradeSide::Short,
+            true,
+            size,
+            price,
+        ))
+        .await
+    }
+    /// 修改限价订单的价格与数量, 订单ID不变, 校验规则与下单相同
+    /// <br> [`size`] : 修改后的订单总数量, 必须大于已成交数量
+    /// <br> 仅减少数量时保留排队优先级; 修改价格或增加数量时重新排队
+    /// <br> 重新排队的订单在下一根K线开盘即可成交时按吃单成交, 否则仍为挂单
+    async fn order_amend(
+        &self,
+        symbol: &str,
+        id: &str,
+        price: Decimal,
+        size: Decimal,
+    ) -> Result<()>;
+    /// 下单一组二选一(OCO)订单, 返回订单组ID
+    /// <br> 组内任意订单成交(含部分成交)或完成后, 撤销其余订单
+    async fn order_place_oco(&self, requests: Vec<OrderRequest>) -> Result<String>;
+    /// 下单入场订单并附带止损/止盈订单, 返回订单组ID
+    /// <br> 止损/止盈订单与入场订单同向只减仓, 数量忽略, 入场订单成交后按成交数量激活, 二者互斥
+    async fn order_place_bracket(
+        &self,
+        entry: OrderRequest,
+        stop_loss: Option<OrderRequest>,
+        take_profit: Option<OrderRequest>,
     ) -> Result<String>;
+    /// 撤销订单组内全部未完成订单
+    async fn order_group_cancel(&self, symbol: &str, id: &str) -> Result<()>;
     async fn order_close(&self, symbol: &str, id: &str) -> Result<()>;
     async fn order_cancel_many(&self, symbol: &str, ids: &[String]) -> Result<()>;
     async fn leverage_set(&self, symbol: &str, leverage: u32) -> Result<()>;
+    async fn position_mode_set(&self, symbol: &str, mode: PositionMode) -> Result<()>;
+    async fn margin_mode_set(&self, symbol: &str, mode: MarginMode) -> Result<()>;
+    /// 增加逐仓持仓指定方向的逐仓保证金
+    async fn margin_add(&self, symbol: &str, side: TradeSide, amount: Decimal) -> Result<()>;
+    /// 减少逐仓持仓指定方向的逐仓保证金
+    async fn margin_remove(&self, symbol: &str, side: TradeSide, amount: Decimal) -> Result<()>;
 }
 
 /// 策略
@@ -112,7 +234,17 @@ pub trait Strategy: Send + Sync {
     async fn on_order(&self, engine: Arc<dyn Engine>, order: Order) -> Result<()> {
         Ok(())
     }
-    async fn on_position(&self, engine: Arc<dyn Engine>, position: Position) -> Result<()> {
+    async fn on_position(&self, engine: Arc<dyn Engine>, event: PositionEvent) -> Result<()> {
+        Ok(())
+    }
+    async fn on_funding(&self, engine: Arc<dyn Engine>, funding: Funding) -> Result<()> {
+        Ok(())
+    }
+    async fn on_liquidation(
+        &self,
+        engine: Arc<dyn Engine>,
+        liquidation: Liquidation,
+    ) -> Result<()> {
         Ok(())
     }
 }
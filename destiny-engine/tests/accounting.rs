@@ -0,0 +1,503 @@
+mod common;
+
+use common::{backtest_config, order, step, symbol_position, HistoryDir, TestStrategy};
+use destiny_engine::prelude::*;
+
+fn fill(
+    positions: &mut SymbolPosition,
+    side: TradeSide,
+    reduce_only: bool,
+    price: Decimal,
+    size: Decimal,
+) -> Order {
+    let mut order = order(side, reduce_only, size);
+    let fee = price * size * dec!(0.001);
+    let pnl = positions.fill(&mut order, price, size, fee);
+    assert_eq!(pnl, order.deal_pnl);
+    assert_eq!(fee, order.deal_fee);
+    order
+}
+
+#[test]
+fn test_fill_long() {
+    let mut positions = symbol_position(PositionMode::Hedge, dec!(100));
+
+    // 开仓: 市价单以成交价格作为持仓均价
+    let order = fill(&mut positions, TradeSide::Long, false, dec!(100), dec!(1));
+    assert_eq!(order.status, OrderStatus::Filled);
+    assert_eq!(order.deal_pnl, Decimal::ZERO);
+    assert_eq!(positions.long.price, dec!(100));
+    assert_eq!(positions.long.size, dec!(1));
+
+    // 加仓
+    fill(&mut positions, TradeSide::Long, false, dec!(110), dec!(1));
+    assert_eq!(positions.long.price, dec!(105));
+    assert_eq!(positions.long.size, dec!(2));
+
+    // 部分平仓: 盈亏不含手续费
+    let order = fill(&mut positions, TradeSide::Long, true, dec!(120), dec!(1));
+    assert_eq!(order.deal_pnl, dec!(15));
+    assert_eq!(order.deal_fee, dec!(0.12));
+    assert_eq!(positions.long.price, dec!(105));
+    assert_eq!(positions.long.size, dec!(1));
+
+    // 全部平仓
+    let order = fill(&mut positions, TradeSide::Long, true, dec!(90), dec!(1));
+    assert_eq!(order.deal_pnl, dec!(-15));
+    assert_eq!(positions.long.price, Decimal::ZERO);
+    assert_eq!(positions.long.size, Decimal::ZERO);
+    assert_eq!(positions.short.size, Decimal::ZERO);
+}
+
+#[test]
+fn test_fill_short() {
+    let mut positions = symbol_position(PositionMode::Hedge, dec!(100));
+
+    fill(&mut positions, TradeSide::Short, false, dec!(100), dec!(1));
+    assert_eq!(positions.short.price, dec!(100));
+    assert_eq!(positions.short.size, dec!(1));
+
+    fill(&mut positions, TradeSide::Short, false, dec!(110), dec!(1));
+    assert_eq!(positions.short.price, dec!(105));
+    assert_eq!(positions.short.size, dec!(2));
+
+    let order = fill(&mut positions, TradeSide::Short, true, dec!(90), dec!(1));
+    assert_eq!(order.deal_pnl, dec!(15));
+    assert_eq!(positions.short.price, dec!(105));
+    assert_eq!(positions.short.size, dec!(1));
+
+    let order = fill(&mut positions, TradeSide::Short, true, dec!(120), dec!(1));
+    assert_eq!(order.deal_pnl, dec!(-15));
+    assert_eq!(positions.short.price, Decimal::ZERO);
+    assert_eq!(positions.short.size, Decimal::ZERO);
+    assert_eq!(positions.long.size, Decimal::ZERO);
+}
+
+#[test]
+fn test_fill_reduce_only_exceeds_position() {
+    let mut positions = symbol_position(PositionMode::Hedge, dec!(100));
+    fill(&mut positions, TradeSide::Long, false, dec!(100), dec!(1));
+
+    let order = fill(&mut positions, TradeSide::Long, true, dec!(110), dec!(2));
+    assert_eq!(order.status, OrderStatus::PartialFilled);
+    assert_eq!(order.deal_size, dec!(1));
+    assert_eq!(order.deal_pnl, dec!(10));
+    assert_eq!(positions.long.size, Decimal::ZERO);
+}
+
+#[test]
+fn test_fill_one_way() {
+    let mut positions = symbol_position(PositionMode::OneWay, dec!(100));
+    fill(&mut positions, TradeSide::Long, false, dec!(100), dec!(1));
+
+    // 卖出先平掉多头, 超出部分开空
+    let order = fill(&mut positions, TradeSide::Short, false, dec!(110), dec!(3));
+    assert_eq!(order.status, OrderStatus::Filled);
+    assert_eq!(order.deal_pnl, dec!(10));
+    assert_eq!(positions.long.size, Decimal::ZERO);
+    assert_eq!(positions.long.price, Decimal::ZERO);
+    assert_eq!(positions.short.size, dec!(2));
+    assert_eq!(positions.short.price, dec!(110));
+}
+
+#[test]
+fn test_fill_partial() {
+    let mut positions = symbol_position(PositionMode::Hedge, dec!(100));
+    let mut order = order(TradeSide::Long, false, dec!(3));
+
+    positions.fill(&mut order, dec!(100), dec!(1), dec!(0.1));
+    assert_eq!(order.status, OrderStatus::PartialFilled);
+    assert_eq!(order.deal_size, dec!(1));
+
+    // 成交价格按成交量加权平均, 手续费按每次成交累计
+    positions.fill(&mut order, dec!(103), dec!(2), dec!(0.2));
+    assert_eq!(order.status, OrderStatus::Filled);
+    assert_eq!(order.deal_size, dec!(3));
+    assert_eq!(order.deal_price, dec!(102));
+    assert_eq!(order.deal_fee, dec!(0.3));
+    assert_eq!(positions.long.price, dec!(102));
+    assert_eq!(positions.long.size, dec!(3));
+}
+
+#[test]
+fn test_position_events() {
+    let mut positions = symbol_position(PositionMode::OneWay, dec!(100));
+    fill(&mut positions, TradeSide::Long, false, dec!(100), dec!(1));
+
+    let snapshot = positions.position_snapshot();
+    fill(&mut positions, TradeSide::Short, false, dec!(110), dec!(2));
+    let events = positions.position_events(snapshot, PositionReason::Trade, Default::default());
+
+    // 单向持仓反手: 多头平仓与空头开仓各产生一个事件
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].before.side, TradeSide::Long);
+    assert_eq!(events[0].before.size, dec!(1));
+    assert_eq!(events[0].after.size, Decimal::ZERO);
+    assert_eq!(events[0].margin_before, dec!(10));
+    assert_eq!(events[0].margin_after, Decimal::ZERO);
+    assert_eq!(events[1].before.side, TradeSide::Short);
+    assert_eq!(events[1].after.size, dec!(1));
+    assert_eq!(events[1].after.price, dec!(110));
+
+    let snapshot = positions.position_snapshot();
+    assert!(positions
+        .position_events(snapshot, PositionReason::Trade, Default::default())
+        .is_empty());
+}
+
+/// 将订单加入订单组并挂单
+fn group_order(
+    positions: &mut SymbolPosition,
+    id: &str,
+    reduce_only: bool,
+    group_id: &str,
+    active: bool,
+) -> Order {
+    let mut order = order(TradeSide::Long, reduce_only, dec!(2));
+    order.id = id.to_string();
+    order.group_id = Some(group_id.to_string());
+    if active {
+        positions.orders.insert(order.id.clone(), order.clone());
+    }
+    order
+}
+
+#[test]
+fn test_group_oco() {
+    let mut positions = symbol_position(PositionMode::Hedge, dec!(100));
+    fill(&mut positions, TradeSide::Long, false, dec!(100), dec!(2));
+    let mut stop_loss = group_order(&mut positions, "sl", true, "oco", true);
+    group_order(&mut positions, "tp", true, "oco", true);
+    positions.groups.insert(
+        "oco".to_string(),
+        OrderGroup {
+            id: "oco".to_string(),
+            symbol: "ETHUSDT".to_string(),
+            r#type: OrderGroupType::Oco,
+            entry_id: None,
+            order_ids: vec!["sl".to_string(), "tp".to_string()],
+            orders_inactive: Vec::new(),
+            create_time: Default::default(),
+        },
+    );
+
+    // 未成交的订单不影响订单组
+    assert!(positions.group_update(&stop_loss).is_empty());
+
+    // 部分成交即撤销同组其余订单
+    positions.orders.remove("sl");
+    positions.fill(&mut stop_loss, dec!(90), dec!(1), Decimal::ZERO);
+    let canceled = positions.group_update(&stop_loss);
+    assert_eq!(canceled.len(), 1);
+    assert_eq!(canceled[0].id, "tp");
+    assert_eq!(canceled[0].status, OrderStatus::Canceled);
+    assert!(positions.orders.is_empty());
+    assert!(positions.groups.is_empty());
+}
+
+#[test]
+fn test_group_bracket() {
+    let mut positions = symbol_position(PositionMode::Hedge, dec!(100));
+    let mut entry = group_order(&mut positions, "entry", false, "bracket", true);
+    let stop_loss = group_order(&mut positions, "sl", true, "bracket", false);
+    let take_profit = group_order(&mut positions, "tp", true, "bracket", false);
+    positions.groups.insert(
+        "bracket".to_string(),
+        OrderGroup {
+            id: "bracket".to_string(),
+            symbol: "ETHUSDT".to_string(),
+            r#type: OrderGroupType::Bracket,
+            entry_id: Some("entry".to_string()),
+            order_ids: vec!["sl".to_string(), "tp".to_string()],
+            orders_inactive: vec![stop_loss, take_profit],
+            create_time: Default::default(),
+        },
+    );
+
+    // 入场订单部分成交后按成交数量激活止损与止盈订单
+    positions.fill(&mut entry, dec!(100), dec!(1), Decimal::ZERO);
+    assert!(positions.group_update(&entry).is_empty());
+    assert!(positions.groups["bracket"].orders_inactive.is_empty());
+    assert_eq!(positions.orders["sl"].size, dec!(1));
+    assert_eq!(positions.orders["tp"].size, dec!(1));
+
+    // 入场订单继续成交, 止损与止盈订单数量随之增加
+    positions.fill(&mut entry, dec!(100), dec!(1), Decimal::ZERO);
+    positions.orders.remove("entry");
+    assert!(positions.group_update(&entry).is_empty());
+    assert_eq!(positions.orders["sl"].size, dec!(2));
+
+    // 止盈订单取消时撤销止损订单
+    let canceled = positions.order_finish("tp", OrderStatus::Canceled);
+    assert_eq!(canceled.len(), 2);
+    assert_eq!(canceled[0].id, "tp");
+    assert_eq!(canceled[1].id, "sl");
+    assert!(positions.orders.is_empty());
+    assert!(positions.groups.is_empty());
+}
+
+/// 第1根K线收盘后挂出的买入限价单(数量2, 价格90)
+fn amend_order(engine: &Arc<dyn Engine>) -> Order {
+    engine.orders("ETHUSDT").remove(0)
+}
+
+#[tokio::test]
+async fn test_order_amend_queue_time() -> Result<()> {
+    let history = HistoryDir::new("amend_queue_time");
+    history.market_series_write("ETHUSDT", &[["100"; 4]; 5])?;
+    let strategy = TestStrategy::new(vec![
+        step(|engine| async move {
+            engine.long_limit_open("ETHUSDT", dec!(2), dec!(90)).await?;
+            Ok(())
+        }),
+        step(|engine| async move {
+            // 仅减少数量时保留排队时间
+            let order = amend_order(&engine);
+            engine
+                .order_amend("ETHUSDT", &order.id, dec!(90), dec!(1))
+                .await?;
+            let amended = amend_order(&engine);
+            assert_eq!(amended.size, dec!(1));
+            assert_eq!(amended.queue_time, order.queue_time);
+            assert!(amended.queue_time < engine.time());
+            Ok(())
+        }),
+        step(|engine| async move {
+            // 增加数量时重新排队
+            let order = amend_order(&engine);
+            engine
+                .order_amend("ETHUSDT", &order.id, dec!(90), dec!(3))
+                .await?;
+            let amended = amend_order(&engine);
+            assert_eq!(amended.size, dec!(3));
+            assert_eq!(amended.queue_time, engine.time());
+            assert_eq!(amended.create_time, order.create_time);
+            Ok(())
+        }),
+        step(|engine| async move {
+            // 修改价格时重新排队
+            let order = amend_order(&engine);
+            engine
+                .order_amend("ETHUSDT", &order.id, dec!(91), dec!(3))
+                .await?;
+            let amended = amend_order(&engine);
+            assert_eq!(amended.price, dec!(91));
+            assert_eq!(amended.queue_time, engine.time());
+            Ok(())
+        }),
+    ]);
+    Backtest::run(backtest_config(&history).build()?, strategy.clone()).await?;
+
+    // 每次修改通知一次订单事件
+    let orders = strategy.orders.lock();
+    assert_eq!(orders.len(), 3);
+    assert!(orders
+        .iter()
+        .all(|order| order.status == OrderStatus::Submitted));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_order_amend_invalid() -> Result<()> {
+    let history = HistoryDir::new("amend_invalid");
+    history.market_series_write("ETHUSDT", &[["100"; 4]; 3])?;
+    let strategy = TestStrategy::new(vec![
+        step(|engine| async move {
+            engine.long_limit_open("ETHUSDT", dec!(2), dec!(90)).await?;
+            Ok(())
+        }),
+        step(|engine| async move {
+            let order = amend_order(&engine);
+            let amend = |price: Decimal, size: Decimal| {
+                let engine = engine.clone();
+                let id = order.id.clone();
+                async move { engine.order_amend("ETHUSDT", &id, price, size).await }
+            };
+            // 数量必须大于已成交数量
+            assert!(amend(dec!(90), Decimal::ZERO).await.is_err());
+            // 价格低于最低价格
+            assert!(amend(Decimal::ZERO, dec!(2)).await.is_err());
+            // 保证金不足: 200 * 90 / 10 > 1000
+            assert!(amend(dec!(90), dec!(200)).await.is_err());
+            // 交易对不存在
+            assert!(engine
+                .order_amend("BTCUSDT", &order.id, dec!(90), dec!(2))
+                .await
+                .is_err());
+
+            // 校验失败时订单保持不变
+            let unchanged = amend_order(&engine);
+            assert_eq!(unchanged.price, order.price);
+            assert_eq!(unchanged.size, order.size);
+            assert_eq!(unchanged.queue_time, order.queue_time);
+            assert_eq!(engine.cash_frozen(), order.margin(dec!(100), 10));
+            Ok(())
+        }),
+    ]);
+    Backtest::run(backtest_config(&history).build()?, strategy.clone()).await?;
+    assert!(strategy.orders.lock().is_empty());
+    Ok(())
+}
+
+/// 挂单后在第2根K线收盘后修改, 第3根K线收盘价89时成交
+async fn amend_fill(price: Decimal, size: Decimal) -> Result<Trade> {
+    let history = HistoryDir::new("amend_fill");
+    history.market_series_write("ETHUSDT", &[["100"; 4], ["100"; 4], ["89"; 4]])?;
+    let strategy = TestStrategy::new(vec![
+        step(|engine| async move {
+            engine.long_limit_open("ETHUSDT", dec!(2), dec!(90)).await?;
+            Ok(())
+        }),
+        step(move |engine| async move {
+            let order = amend_order(&engine);
+            engine
+                .order_amend("ETHUSDT", &order.id, price, size)
+                .await?;
+            Ok(())
+        }),
+    ]);
+    Backtest::run(backtest_config(&history).build()?, strategy.clone()).await?;
+    let trades = strategy.engine().trades(
+        "ETHUSDT",
+        DateTime::<Utc>::MIN_UTC,
+        DateTime::<Utc>::MAX_UTC,
+    );
+    assert_eq!(trades.len(), 1);
+    Ok(trades[0].clone())
+}
+
+#[tokio::test]
+async fn test_order_amend_maker() -> Result<()> {
+    // 仅减少数量保留排队, 按挂单成交
+    let trade = amend_fill(dec!(90), dec!(1)).await?;
+    assert!(trade.is_maker);
+    assert_eq!(trade.size, dec!(1));
+    // 增加数量或修改价格后与新订单相同, 下一根K线成交时按吃单成交
+    let trade = amend_fill(dec!(90), dec!(3)).await?;
+    assert!(!trade.is_maker);
+    assert_eq!(trade.size, dec!(3));
+    let trade = amend_fill(dec!(91), dec!(2)).await?;
+    assert!(!trade.is_maker);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_snapshot_consistency() -> Result<()> {
+    let history = HistoryDir::new("snapshot");
+    history.market_series_write("ETHUSDT", &[["100"; 4]; 3])?;
+    let strategy = TestStrategy::new(vec![
+        step(|engine| async move {
+            engine.long_market_open("ETHUSDT", dec!(2)).await?;
+            Ok(())
+        }),
+        step(|engine| async move {
+            engine
+                .long_limit_close("ETHUSDT", dec!(1), dec!(110))
+                .await?;
+            engine.long_limit_open("ETHUSDT", dec!(1), dec!(90)).await?;
+
+            // 快照与引擎查询一致
+            let snapshot = engine.snapshot();
+            assert_eq!(snapshot.time(), engine.time());
+            assert_eq!(snapshot.cash(), engine.cash());
+            assert_eq!(snapshot.cash_available(), engine.cash_available());
+            assert_eq!(snapshot.cash_frozen(), engine.cash_frozen());
+            assert_eq!(snapshot.margin(), engine.margin());
+            assert_eq!(snapshot.long_size("ETHUSDT"), dec!(2));
+            assert_eq!(
+                snapshot.long_size_available("ETHUSDT"),
+                engine.long_size_available("ETHUSDT")
+            );
+            assert_eq!(snapshot.long_size_frozen("ETHUSDT"), dec!(1));
+            assert_eq!(snapshot.orders("ETHUSDT").len(), 2);
+
+            // 下单校验与快照的可用资金与可用持仓一致
+            let cash_available = snapshot.cash_available();
+            assert!(engine
+                .long_limit_open("ETHUSDT", cash_available / dec!(9) + dec!(0.01), dec!(90))
+                .await
+                .is_err());
+            assert!(engine
+                .long_market_close("ETHUSDT", snapshot.long_size_available("ETHUSDT") + dec!(1))
+                .await
+                .is_err());
+            engine
+                .long_limit_open("ETHUSDT", cash_available / dec!(9), dec!(90))
+                .await?;
+
+            // 快照是下单前的副本, 不随之后的订单变化
+            assert_eq!(snapshot.orders("ETHUSDT").len(), 2);
+            assert_eq!(snapshot.cash_available(), cash_available);
+            assert_eq!(engine.orders("ETHUSDT").len(), 3);
+            assert!(engine.cash_available() < cash_available);
+            Ok(())
+        }),
+    ]);
+    Backtest::run(backtest_config(&history).build()?, strategy).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_partial_fill_across_klines() -> Result<()> {
+    let history = HistoryDir::new("partial_fill");
+    history.market_series_write(
+        "ETHUSDT",
+        &[["100"; 4], ["100"; 4], ["110"; 4], ["120"; 4], ["120"; 4]],
+    )?;
+    let strategy = TestStrategy::new(vec![
+        step(|engine| async move {
+            engine.long_market_open("ETHUSDT", dec!(3)).await?;
+            Ok(())
+        }),
+        step(|engine| async move {
+            // 参与率限制每根K线成交1, 剩余部分保留在订单列表
+            assert_eq!(engine.long_size("ETHUSDT"), dec!(1));
+            assert_eq!(engine.orders("ETHUSDT")[0].deal_size, dec!(1));
+            Ok(())
+        }),
+        step(|engine| async move {
+            assert_eq!(engine.long_size("ETHUSDT"), dec!(2));
+            assert_eq!(engine.orders("ETHUSDT")[0].deal_size, dec!(2));
+            Ok(())
+        }),
+        step(|engine| async move {
+            assert_eq!(engine.long_size("ETHUSDT"), dec!(3));
+            assert!(engine.orders("ETHUSDT").is_empty());
+            Ok(())
+        }),
+    ]);
+    Backtest::run(
+        backtest_config(&history)
+            .participation_rate(Some(dec!(0.1)))
+            .build()?,
+        strategy.clone(),
+    )
+    .await?;
+
+    // 每次部分成交均通知订单事件, 成交数量与成交均价跨K线累计
+    let orders = strategy.orders.lock();
+    assert_eq!(
+        orders
+            .iter()
+            .map(|order| (order.status, order.deal_size, order.deal_price))
+            .collect::<Vec<(OrderStatus, Decimal, Decimal)>>(),
+        vec![
+            (OrderStatus::PartialFilled, dec!(1), dec!(100)),
+            (OrderStatus::PartialFilled, dec!(2), dec!(105)),
+            (OrderStatus::Filled, dec!(3), dec!(110)),
+        ]
+    );
+    let engine = strategy.engine();
+    assert_eq!(
+        engine
+            .trades(
+                "ETHUSDT",
+                DateTime::<Utc>::MIN_UTC,
+                DateTime::<Utc>::MAX_UTC
+            )
+            .len(),
+        3
+    );
+    assert_eq!(engine.long_price("ETHUSDT"), dec!(110));
+    Ok(())
+}
@@ -0,0 +1,420 @@
+use destiny_engine::{binance::mock::*, prelude::*};
+use reqwest::Method;
+use std::collections::VecDeque;
+
+/// 策略在收到K线时执行的操作
+enum Action {
+    Place(OrderRequest),
+    Cancel(String),
+}
+
+#[derive(Default)]
+struct TestStrategy {
+    actions: Mutex<VecDeque<Action>>,
+    klines: Mutex<Vec<Kline>>,
+    orders: Mutex<Vec<Order>>,
+    fundings: Mutex<Vec<Funding>>,
+    errors: Mutex<Vec<anyhow::Error>>,
+}
+
+impl TestStrategy {
+    fn push(&self, action: Action) {
+        self.actions.lock().push_back(action);
+    }
+}
+
+#[async_trait]
+impl Strategy for TestStrategy {
+    async fn on_init(&self, engine: Arc<dyn Engine>) -> Result<()> {
+        engine.symbol_init("BTCUSDT")
+    }
+    async fn on_kline(&self, engine: Arc<dyn Engine>, kline: Kline) -> Result<()> {
+        self.klines.lock().push(kline);
+        loop {
+            let action = self.actions.lock().pop_front();
+            let result = match action {
+                Some(Action::Place(request)) => engine.order_place(request).await.map(|_| ()),
+                Some(Action::Cancel(id)) => engine.order_cancel_many("BTCUSDT", &[id]).await,
+                None => return Ok(()),
+            };
+            if let Err(err) = result {
+                self.errors.lock().push(err);
+            }
+        }
+    }
+    async fn on_order(&self, _engine: Arc<dyn Engine>, order: Order) -> Result<()> {
+        self.orders.lock().push(order);
+        Ok(())
+    }
+    async fn on_funding(&self, _engine: Arc<dyn Engine>, funding: Funding) -> Result<()> {
+        self.fundings.lock().push(funding);
+        Ok(())
+    }
+}
+
+fn rule() -> SymbolRule {
+    SymbolRule {
+        price_tick: dec!(0.1),
+        size_min: dec!(0.001),
+        size_tick: dec!(0.001),
+        amount_min: dec!(100),
+        ..Default::default()
+    }
+}
+
+fn limit(id: &str, price: Decimal) -> Action {
+    Action::Place(
+        OrderRequest::limit("BTCUSDT", TradeSide::Long, false, dec!(0.01), price)
+            .with_client_id(id),
+    )
+}
+
+fn market(id: &str) -> Action {
+    Action::Place(
+        OrderRequest::market("BTCUSDT", TradeSide::Long, false, dec!(0.01)).with_client_id(id),
+    )
+}
+
+async fn mock_start() -> Result<Arc<MockExchange>> {
+    let mock = MockExchange::start(MockExchangeConfigBuilder::default().build()?).await?;
+    mock.symbol_add("BTCUSDT", rule());
+    mock.mark_price_push(
+        "BTCUSDT",
+        dec!(50000),
+        dec!(0.0001),
+        Utc::now() + Duration::hours(8),
+    );
+    Ok(mock)
+}
+
+fn engine_new(
+    mock: &MockExchange,
+    strategy: Arc<TestStrategy>,
+    timeout: StdDuration,
+) -> Result<Arc<Binance>> {
+    Binance::new(
+        BinanceConfigBuilder::default()
+            .run_mode(RunMode::Testnet)
+            .api_key("mock-api-key")
+            .api_secret("mock-api-secret")
+            .rest_url(Some(mock.rest_url()))
+            .ws_url(Some(mock.ws_url()))
+            .timeout(timeout)
+            .reconnect_interval(StdDuration::from_millis(50))
+            .build()?,
+        strategy,
+    )
+}
+
+/// 等待条件成立, 最多5秒
+async fn wait_until(f: impl Fn() -> bool) -> Result<()> {
+    tokio::time::timeout(StdDuration::from_secs(5), async {
+        while !f() {
+            tokio::time::sleep(StdDuration::from_millis(10)).await;
+        }
+    })
+    .await
+    .map_err(|_| anyhow!("等待超时"))
+}
+
+fn request_count(mock: &MockExchange, method: &str, path: &str) -> usize {
+    mock.requests()
+        .iter()
+        .filter(|request| request.method == method && request.path == path)
+        .count()
+}
+
+#[tokio::test]
+async fn test_binance() -> Result<()> {
+    let mock = mock_start().await?;
+    let strategy = Arc::new(TestStrategy::default());
+    let engine = engine_new(&mock, strategy.clone(), StdDuration::from_secs(10))?;
+    let handle = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.run0().await }
+    });
+    wait_until(|| mock.connections() == 1).await?;
+
+    // 交易规则、账户与标记价格
+    assert_eq!(engine.rule_price_tick("BTCUSDT"), dec!(0.1));
+    assert_eq!(engine.rule_size_min("BTCUSDT"), dec!(0.001));
+    assert_eq!(engine.rule_amount_min("BTCUSDT"), dec!(100));
+    assert_eq!(engine.leverage("BTCUSDT"), 20);
+    assert_eq!(engine.position_mode("BTCUSDT"), PositionMode::Hedge);
+    assert_eq!(engine.price_mark("BTCUSDT"), dec!(50000));
+    assert_eq!(engine.cash(), dec!(10000));
+
+    // 市价单按最新价格吃单成交, 限价单挂单等待
+    let begin = "202401010000".to_date()?;
+    strategy.push(limit("limit-open", dec!(49900)));
+    strategy.push(market("market-open"));
+    mock.price_path("BTCUSDT", begin, &[dec!(50000)]);
+    wait_until(|| engine.long_size("BTCUSDT") == dec!(0.01)).await?;
+    assert_eq!(engine.orders("BTCUSDT").len(), 1);
+    assert_eq!(engine.cash(), dec!(10000) - dec!(0.25));
+
+    // 价格触及限价后以限价挂单成交
+    mock.price_path("BTCUSDT", begin + Duration::minutes(1), &[dec!(49800)]);
+    wait_until(|| engine.long_size("BTCUSDT") == dec!(0.02)).await?;
+    assert_eq!(engine.long_price("BTCUSDT"), dec!(49950));
+    assert_eq!(engine.cash(), mock.account().cash);
+    let trades = engine.trades("BTCUSDT", DateTime::<Utc>::MIN_UTC, Utc::now());
+    assert_eq!(trades.len(), 2);
+    assert!(!trades[0].is_maker);
+    assert!(trades[1].is_maker);
+    assert_eq!(trades[1].fee, dec!(0.0998));
+
+    // 全仓的资金费用更新不含持仓, 按本地持仓结算, 费用以余额变化为准
+    let cash = engine.cash();
+    mock.funding_settle("BTCUSDT");
+    wait_until(|| !strategy.fundings.lock().is_empty()).await?;
+    let funding = strategy.fundings.lock()[0].clone();
+    assert_eq!(funding.side, TradeSide::Long);
+    assert_eq!(funding.size, dec!(0.02));
+    assert_eq!(funding.rate, dec!(0.0001));
+    assert!(funding.fee < Decimal::ZERO);
+    assert_eq!(engine.cash(), cash + funding.fee);
+    assert_eq!(engine.cash(), mock.account().cash);
+    assert_eq!(engine.funding_fee("BTCUSDT"), funding.fee);
+    assert_eq!(engine.long_size("BTCUSDT"), dec!(0.02));
+
+    // 撤单以用户数据流推送为准
+    strategy.push(limit("cancel-me", dec!(40000)));
+    mock.price_path("BTCUSDT", begin + Duration::minutes(2), &[dec!(49900)]);
+    wait_until(|| engine.orders("BTCUSDT").len() == 1).await?;
+    strategy.push(Action::Cancel("cancel-me".to_string()));
+    mock.price_path("BTCUSDT", begin + Duration::minutes(3), &[dec!(49900)]);
+    wait_until(|| engine.orders("BTCUSDT").is_empty()).await?;
+    assert_eq!(
+        mock.order("BTCUSDT", "cancel-me").unwrap().status,
+        OrderStatus::Canceled
+    );
+
+    // 不符合交易规则的订单被拒绝
+    strategy.push(Action::Place(
+        OrderRequest::limit(
+            "BTCUSDT",
+            TradeSide::Long,
+            false,
+            dec!(0.01),
+            dec!(49900.05),
+        )
+        .with_client_id("bad-tick"),
+    ));
+    mock.price_path("BTCUSDT", begin + Duration::minutes(4), &[dec!(49900)]);
+    wait_until(|| !strategy.errors.lock().is_empty()).await?;
+    assert_eq!(strategy.errors.lock().len(), 1);
+    assert!(mock.order("BTCUSDT", "bad-tick").is_none());
+    engine.stop();
+    tokio::time::timeout(StdDuration::from_secs(5), handle).await???;
+
+    // 仅收盘的K线触发回调, 下单参数与签名
+    assert_eq!(strategy.klines.lock().len(), 5);
+    let params = mock
+        .requests()
+        .into_iter()
+        .find(|request| {
+            request.method == "POST"
+                && request.path == "/fapi/v1/order"
+                && request.params.get("newClientOrderId").map(String::as_str) == Some("limit-open")
+        })
+        .unwrap()
+        .params;
+    assert_eq!(params["side"], "BUY");
+    assert_eq!(params["positionSide"], "LONG");
+    assert_eq!(params["type"], "LIMIT");
+    assert_eq!(params["timeInForce"], "GTC");
+    assert_eq!(params["quantity"], "0.01");
+    assert_eq!(params["price"], "49900");
+    let statuses = strategy
+        .orders
+        .lock()
+        .iter()
+        .map(|order| (order.id.clone(), order.status))
+        .collect::<Vec<(String, OrderStatus)>>();
+    assert!(statuses.contains(&("market-open".to_string(), OrderStatus::Filled)));
+    assert!(statuses.contains(&("limit-open".to_string(), OrderStatus::Filled)));
+    assert!(statuses.contains(&("cancel-me".to_string(), OrderStatus::Canceled)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_binance_margin_isolated() -> Result<()> {
+    let mock = mock_start().await?;
+    let strategy = Arc::new(TestStrategy::default());
+    let engine = engine_new(&mock, strategy.clone(), StdDuration::from_secs(10))?;
+    let handle = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.run0().await }
+    });
+    wait_until(|| mock.connections() == 1).await?;
+
+    // 双向持仓的多空数量相同, 逐仓保证金按各方向的逐仓钱包余额记录
+    engine
+        .margin_mode_set("BTCUSDT", MarginMode::Isolated)
+        .await?;
+    strategy.push(market("long-open"));
+    strategy.push(Action::Place(
+        OrderRequest::market("BTCUSDT", TradeSide::Short, false, dec!(0.01))
+            .with_client_id("short-open"),
+    ));
+    mock.price_path("BTCUSDT", "202401010000".to_date()?, &[dec!(50000)]);
+    wait_until(|| engine.short_size("BTCUSDT") == dec!(0.01)).await?;
+    wait_until(|| engine.margin_isolated("BTCUSDT") == dec!(50)).await?;
+
+    // 调整保证金作用于指定方向, 而非数量较大的方向
+    engine
+        .margin_add("BTCUSDT", TradeSide::Short, dec!(10))
+        .await?;
+    engine
+        .margin_add("BTCUSDT", TradeSide::Long, dec!(10))
+        .await?;
+    engine
+        .margin_remove("BTCUSDT", TradeSide::Long, dec!(5))
+        .await?;
+    assert_eq!(engine.margin_isolated("BTCUSDT"), dec!(65));
+    let position = mock.account().positions["BTCUSDT"].clone();
+    assert_eq!(position.long.margin_isolated, dec!(30));
+    assert_eq!(position.short.margin_isolated, dec!(35));
+    let sides = mock
+        .requests()
+        .into_iter()
+        .filter(|request| request.method == "POST" && request.path == "/fapi/v1/positionMargin")
+        .map(|request| request.params["positionSide"].clone())
+        .collect::<Vec<String>>();
+    assert_eq!(sides, ["SHORT", "LONG", "LONG"]);
+
+    // 超出该方向可减少的保证金时被拒绝
+    assert!(engine
+        .margin_remove("BTCUSDT", TradeSide::Long, dec!(10))
+        .await
+        .is_err());
+    assert_eq!(engine.margin_isolated("BTCUSDT"), dec!(65));
+
+    engine.stop();
+    tokio::time::timeout(StdDuration::from_secs(5), handle).await???;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_binance_retry() -> Result<()> {
+    let mock = mock_start().await?;
+    mock.error_inject(
+        Method::GET,
+        "/fapi/v1/positionSide/dual",
+        MockError::ServerError,
+    );
+    mock.error_inject(Method::GET, "/fapi/v1/exchangeInfo", MockError::RateLimit);
+    let strategy = Arc::new(TestStrategy::default());
+    let engine = engine_new(&mock, strategy.clone(), StdDuration::from_millis(500))?;
+    let handle = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.run0().await }
+    });
+    wait_until(|| mock.connections() == 1).await?;
+
+    // 服务端错误与限流后重试
+    assert_eq!(request_count(&mock, "GET", "/fapi/v1/positionSide/dual"), 2);
+    assert_eq!(request_count(&mock, "GET", "/fapi/v1/exchangeInfo"), 2);
+
+    // 时间戳超出接收窗口时同步服务器时间后重试
+    mock.time_offset_set(10_000);
+    let begin = "202401010000".to_date()?;
+    strategy.push(market("time-sync"));
+    mock.price_path("BTCUSDT", begin, &[dec!(50000)]);
+    wait_until(|| engine.long_size("BTCUSDT") == dec!(0.01)).await?;
+    assert_eq!(request_count(&mock, "GET", "/fapi/v1/time"), 2);
+    assert_eq!(request_count(&mock, "POST", "/fapi/v1/order"), 2);
+
+    // 下单超时后按客户端订单ID查询, 不重复下单
+    mock.error_inject(
+        Method::POST,
+        "/fapi/v1/order",
+        MockError::Timeout(StdDuration::from_secs(2)),
+    );
+    strategy.push(market("timeout"));
+    mock.price_path("BTCUSDT", begin + Duration::minutes(1), &[dec!(50000)]);
+    wait_until(|| engine.long_size("BTCUSDT") == dec!(0.02)).await?;
+    wait_until(|| {
+        engine
+            .orders_history("BTCUSDT", DateTime::<Utc>::MIN_UTC, Utc::now())
+            .iter()
+            .any(|order| order.id == "timeout" && order.status == OrderStatus::Filled)
+    })
+    .await?;
+    assert_eq!(request_count(&mock, "POST", "/fapi/v1/order"), 3);
+    assert!(mock.requests().iter().any(|request| {
+        request.method == "GET"
+            && request.path == "/fapi/v1/order"
+            && request.params["origClientOrderId"] == "timeout"
+    }));
+
+    // 拒绝的请求不重试
+    mock.error_inject(
+        Method::POST,
+        "/fapi/v1/order",
+        MockError::Reject {
+            status: 400,
+            code: -2019,
+            msg: "Margin is insufficient.".to_string(),
+        },
+    );
+    strategy.push(market("rejected"));
+    mock.price_path("BTCUSDT", begin + Duration::minutes(2), &[dec!(50000)]);
+    wait_until(|| !strategy.errors.lock().is_empty()).await?;
+    assert_eq!(
+        strategy.errors.lock()[0]
+            .downcast_ref::<BinanceError>()
+            .unwrap()
+            .code,
+        -2019
+    );
+    assert_eq!(request_count(&mock, "POST", "/fapi/v1/order"), 4);
+    assert_eq!(engine.long_size("BTCUSDT"), dec!(0.02));
+
+    engine.stop();
+    tokio::time::timeout(StdDuration::from_secs(5), handle).await???;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_binance_reconnect() -> Result<()> {
+    let mock = mock_start().await?;
+    let strategy = Arc::new(TestStrategy::default());
+    let engine = engine_new(&mock, strategy.clone(), StdDuration::from_secs(10))?;
+    let handle = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.run0().await }
+    });
+    wait_until(|| mock.connections() == 1).await?;
+
+    let begin = "202401010000".to_date()?;
+    strategy.push(limit("resting", dec!(49000)));
+    mock.price_path("BTCUSDT", begin, &[dec!(50000)]);
+    wait_until(|| engine.orders("BTCUSDT").len() == 1).await?;
+
+    // 断线期间的成交在重连后通过账户与订单同步恢复
+    mock.disconnect();
+    mock.price_path("BTCUSDT", begin + Duration::minutes(1), &[dec!(48000)]);
+    wait_until(|| mock.connections() == 2).await?;
+    wait_until(|| engine.long_size("BTCUSDT") == dec!(0.01)).await?;
+    wait_until(|| engine.orders("BTCUSDT").is_empty()).await?;
+    assert_eq!(engine.cash(), mock.account().cash);
+
+    // listenKey失效后重新创建并以新地址重连
+    mock.listen_key_expire();
+    wait_until(|| mock.connections() == 3).await?;
+    assert_eq!(request_count(&mock, "POST", "/fapi/v1/listenKey"), 2);
+    strategy.push(market("after-expire"));
+    mock.price_path("BTCUSDT", begin + Duration::minutes(2), &[dec!(50000)]);
+    wait_until(|| engine.long_size("BTCUSDT") == dec!(0.02)).await?;
+    assert!(strategy
+        .orders
+        .lock()
+        .iter()
+        .any(|order| order.id == "after-expire" && order.status == OrderStatus::Filled));
+
+    engine.stop();
+    tokio::time::timeout(StdDuration::from_secs(5), handle).await???;
+    Ok(())
+}
@@ -0,0 +1,237 @@
+#![allow(dead_code)]
+
+use destiny_engine::prelude::*;
+use futures::future::BoxFuture;
+use std::{
+    future::Future,
+    io::Write,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// ETHUSDT持仓: 10倍杠杆, 全仓, 标记价格、指数价格与最新价格均为`price`
+pub fn symbol_position(position_mode: PositionMode, price: Decimal) -> SymbolPosition {
+    let mut position = SymbolPosition::new("ETHUSDT", SymbolRule::default());
+    position.leverage = 10;
+    position.position_mode = position_mode;
+    position.symbol.market.mark = price;
+    position.symbol.market.index = price;
+    position.symbol.market.last = price;
+    position
+}
+
+/// ETHUSDT市价单, 价格为0, 其余字段按需修改
+pub fn order(side: TradeSide, reduce_only: bool, size: Decimal) -> Order {
+    Order {
+        id: String::new(),
+        client_id: String::new(),
+        group_id: None,
+        symbol: "ETHUSDT".to_string(),
+        r#type: TradeType::Market,
+        side,
+        reduce_only,
+        close_position: false,
+        time_in_force: TimeInForce::Gtc,
+        stop_price: Decimal::ZERO,
+        working_type: WorkingType::Last,
+        callback_rate: Decimal::ZERO,
+        status: OrderStatus::Created,
+        price: Decimal::ZERO,
+        size,
+        deal_price: Decimal::ZERO,
+        deal_size: Decimal::ZERO,
+        deal_fee: Decimal::ZERO,
+        deal_slippage: Decimal::ZERO,
+        deal_pnl: Decimal::ZERO,
+        create_time: Default::default(),
+        queue_time: Default::default(),
+    }
+}
+
+const KLINE_HEADER: &str = "open_time,open,high,low,close,volume,close_time,quote_volume,count,taker_buy_volume,taker_buy_quote_volume,ignore";
+
+/// 临时历史数据目录, 目录结构与[`HistoryData::cache_dir`]相同, 离开作用域时删除
+pub struct HistoryDir(PathBuf);
+
+impl HistoryDir {
+    /// 同一测试进程内每次创建的目录均不相同
+    pub fn new(name: &str) -> Self {
+        static SEQ: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "destiny_history_{}_{}_{}",
+            name,
+            std::process::id(),
+            SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    fn file_create(&self, symbol: &str, kind: &str, month: &str) -> Result<std::fs::File> {
+        let mut dir = self.0.join(symbol).join(kind);
+        if kind != "fundingRate" {
+            dir = dir.join("1m");
+        }
+        std::fs::create_dir_all(&dir)?;
+        Ok(std::fs::File::create(dir.join(format!("{}.csv", month)))?)
+    }
+
+    /// 写入2024年1月的1分钟K线: (开盘时间, 收盘价), 开高低收相同
+    pub fn klines_write(&self, symbol: &str, kind: &str, klines: &[(&str, &str)]) -> Result<()> {
+        let klines = klines
+            .iter()
+            .map(|(open_time, close)| (*open_time, [*close; 4]))
+            .collect::<Vec<(&str, [&str; 4])>>();
+        self.klines_ohlc_write(symbol, kind, &klines)
+    }
+
+    /// 写入2024年1月的1分钟K线: (开盘时间, [开, 高, 低, 收])
+    pub fn klines_ohlc_write(
+        &self,
+        symbol: &str,
+        kind: &str,
+        klines: &[(&str, [&str; 4])],
+    ) -> Result<()> {
+        let mut file = self.file_create(symbol, kind, "202401")?;
+        writeln!(file, "{}", KLINE_HEADER)?;
+        for (open_time, [open, high, low, close]) in klines {
+            let open_time = open_time.to_date()?.timestamp_millis();
+            writeln!(
+                file,
+                "{},{},{},{},{},10,{},1000,10,5,500,0",
+                open_time,
+                open,
+                high,
+                low,
+                close,
+                open_time + 59_999
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 写入2024年1月的1分钟K线与相同的标记价格K线
+    pub fn market_write(&self, symbol: &str, klines: &[(&str, [&str; 4])]) -> Result<()> {
+        self.klines_ohlc_write(symbol, "klines", klines)?;
+        self.klines_ohlc_write(symbol, "markPriceKlines", klines)
+    }
+
+    /// 写入2024-01-01 00:00起每分钟连续的K线与相同的标记价格K线: [开, 高, 低, 收]
+    pub fn market_series_write(&self, symbol: &str, klines: &[[&str; 4]]) -> Result<()> {
+        let times = (0..klines.len())
+            .map(|minute| format!("2024010100{:02}", minute))
+            .collect::<Vec<String>>();
+        let klines = times
+            .iter()
+            .zip(klines)
+            .map(|(time, kline)| (time.as_str(), *kline))
+            .collect::<Vec<(&str, [&str; 4])>>();
+        self.market_write(symbol, &klines)
+    }
+
+    /// 写入2024年1月的资金费率: (结算时间, 费率)
+    pub fn funding_write(&self, symbol: &str, rates: &[(&str, &str)]) -> Result<()> {
+        let mut file = self.file_create(symbol, "fundingRate", "202401")?;
+        writeln!(file, "calc_time,funding_interval_hours,last_funding_rate")?;
+        for (time, rate) in rates {
+            writeln!(file, "{},8,{}", time.to_date()?.timestamp_millis(), rate)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for HistoryDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 测试步骤, 第n根K线收盘后执行第n个步骤
+pub type Step = Box<dyn Fn(Arc<dyn Engine>) -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+pub fn step<F, Fut>(f: F) -> Step
+where
+    F: Fn(Arc<dyn Engine>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    Box::new(move |engine| Box::pin(f(engine)))
+}
+
+/// 回测测试策略: 初始化ETHUSDT并设置10倍杠杆, 按K线依次执行测试步骤, 记录订单与资金费用事件
+#[derive(Default)]
+pub struct TestStrategy {
+    steps: Vec<Step>,
+    klines: Mutex<usize>,
+    engine: Mutex<Option<Arc<dyn Engine>>>,
+    pub orders: Mutex<Vec<Order>>,
+    pub fundings: Mutex<Vec<Funding>>,
+}
+
+impl TestStrategy {
+    pub fn new(steps: Vec<Step>) -> Arc<Self> {
+        Arc::new(Self {
+            steps,
+            ..Default::default()
+        })
+    }
+
+    /// 回测引擎, 回测结束后可查询账户与账本
+    pub fn engine(&self) -> Arc<dyn Engine> {
+        self.engine.lock().clone().expect("回测未初始化")
+    }
+
+    /// 订单的全部事件状态
+    pub fn order_statuses(&self, id: &str) -> Vec<OrderStatus> {
+        self.orders
+            .lock()
+            .iter()
+            .filter(|order| order.id == id)
+            .map(|order| order.status)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Strategy for TestStrategy {
+    async fn on_init(&self, engine: Arc<dyn Engine>) -> Result<()> {
+        engine.symbol_init("ETHUSDT")?;
+        engine.leverage_set("ETHUSDT", 10).await?;
+        *self.engine.lock() = Some(engine);
+        Ok(())
+    }
+    async fn on_kline(&self, engine: Arc<dyn Engine>, _kline: Kline) -> Result<()> {
+        let index = {
+            let mut klines = self.klines.lock();
+            *klines += 1;
+            *klines - 1
+        };
+        match self.steps.get(index) {
+            Some(step) => step(engine).await,
+            None => Ok(()),
+        }
+    }
+    async fn on_order(&self, _engine: Arc<dyn Engine>, order: Order) -> Result<()> {
+        self.orders.lock().push(order);
+        Ok(())
+    }
+    async fn on_funding(&self, _engine: Arc<dyn Engine>, funding: Funding) -> Result<()> {
+        self.fundings.lock().push(funding);
+        Ok(())
+    }
+}
+
+/// 2024-01-01 00:00至01:00的回测配置, 无滑点, 使用临时历史数据目录
+pub fn backtest_config(history: &HistoryDir) -> BacktestConfigBuilder {
+    let begin = "202401010000".to_date().unwrap();
+    let mut builder = BacktestConfigBuilder::default();
+    builder
+        .begin(begin)
+        .end(begin + Duration::hours(1))
+        .slippage_rate(Decimal::ZERO)
+        .history_path(history.path().to_owned());
+    builder
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::{backtest_config, step, HistoryDir, TestStrategy};
+use destiny_engine::prelude::*;
+
+#[tokio::test]
+async fn test_funding_settle() -> Result<()> {
+    let history = HistoryDir::new("funding");
+    history.market_write(
+        "ETHUSDT",
+        &[
+            ("202401010000", ["100", "100", "100", "100"]),
+            ("202401010001", ["100", "100", "100", "100"]),
+        ],
+    )?;
+    history.funding_write("ETHUSDT", &[("202401010100", "0.001")])?;
+
+    // 双向持仓: 多头2, 空头1
+    let strategy = TestStrategy::new(vec![step(|engine| async move {
+        engine.long_market_open("ETHUSDT", dec!(2)).await?;
+        engine.short_market_open("ETHUSDT", dec!(1)).await?;
+        Ok(())
+    })]);
+    Backtest::run(backtest_config(&history).build()?, strategy.clone()).await?;
+
+    // 资金费率为正时多头支付空头, 按标记价格结算
+    let time = "202401010100".to_date()?;
+    let summary = |fundings: &[Funding]| {
+        fundings
+            .iter()
+            .map(|funding| {
+                assert_eq!(funding.symbol, "ETHUSDT");
+                assert_eq!(funding.rate, dec!(0.001));
+                assert_eq!(funding.mark_price, dec!(100));
+                assert_eq!(funding.time, time);
+                (funding.side, funding.size, funding.fee)
+            })
+            .collect::<Vec<(TradeSide, Decimal, Decimal)>>()
+    };
+    let expected = vec![
+        (TradeSide::Long, dec!(2), dec!(-0.2)),
+        (TradeSide::Short, dec!(1), dec!(0.1)),
+    ];
+    assert_eq!(summary(&strategy.fundings.lock()), expected);
+
+    let engine = strategy.engine();
+    // 账本记录与通知一致
+    assert_eq!(summary(&engine.fundings("ETHUSDT")), expected);
+    assert_eq!(engine.funding_fee("ETHUSDT"), dec!(-0.1));
+    assert_eq!(engine.price_settlement("ETHUSDT"), dec!(0.001));
+    assert_eq!(engine.time_settlement("ETHUSDT"), time + Duration::hours(8));
+    // 初始资金 - 吃单手续费(300 * 0.0005) + 资金费用
+    assert_eq!(engine.cash(), dec!(999.75));
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use destiny_engine::prelude::*;
+
+/// StockCharts RSI教程中的示例收盘价, 参考值按TA-Lib算法计算
+const CLOSES: [&str; 33] = [
+    "44.34", "44.09", "44.15", "43.61", "44.33", "44.83", "45.10", "45.42", "45.84", "46.08",
+    "45.89", "46.03", "45.61", "46.28", "46.28", "46.00", "46.03", "46.41", "46.22", "45.64",
+    "46.21", "46.25", "45.71", "46.45", "45.78", "45.35", "44.03", "44.18", "44.22", "44.57",
+    "43.42", "42.66", "43.13",
+];
+
+fn closes() -> Vec<Decimal> {
+    CLOSES
+        .iter()
+        .map(|close| Decimal::from_str_exact(close).unwrap())
+        .collect()
+}
+
+/// 以前一收盘价为开盘价, 最高价与最低价在开收盘价外各0.1
+fn klines() -> Vec<Kline> {
+    let closes = closes();
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, close)| {
+            let open = closes[i.saturating_sub(1)];
+            Kline {
+                symbol: "ETHUSDT".to_string(),
+                open_time: Default::default(),
+                open,
+                high: open.max(*close) + dec!(0.1),
+                low: open.min(*close) - dec!(0.1),
+                close: *close,
+                size: Decimal::ZERO,
+                cash: Decimal::ZERO,
+                buy_size: Decimal::ZERO,
+                buy_cash: Decimal::ZERO,
+                trades: 0,
+                interval: KlineInterval::M1,
+            }
+        })
+        .collect()
+}
+
+fn assert_near(value: Option<Decimal>, expected: Decimal) {
+    let value = value.unwrap();
+    assert!(
+        (value - expected).abs() < dec!(0.0001),
+        "{value} != {expected}"
+    );
+}
+
+#[test]
+fn test_ema() -> Result<()> {
+    let mut ema = Ema::new(10)?;
+    let values = closes()
+        .into_iter()
+        .map(|close| ema.update(close))
+        .collect::<Vec<_>>();
+
+    // 前9个值不足周期
+    assert!(values[..9].iter().all(Option::is_none));
+    assert_near(values[9], dec!(44.779));
+    assert_near(values[10], dec!(44.981));
+    assert_near(values[11], dec!(45.1717));
+    assert_near(ema.value(), dec!(44.1193));
+    Ok(())
+}
+
+#[test]
+fn test_rsi() -> Result<()> {
+    let mut rsi = Rsi::new(14)?;
+    let values = closes()
+        .into_iter()
+        .map(|close| rsi.update(close))
+        .collect::<Vec<_>>();
+
+    assert!(values[..14].iter().all(Option::is_none));
+    let expected = [dec!(70.46), dec!(66.25), dec!(66.48), dec!(69.35)];
+    for (value, expected) in values[14..].iter().zip(expected) {
+        assert!((value.unwrap() - expected).abs() < dec!(0.01));
+    }
+    assert!((rsi.value().unwrap() - dec!(37.79)).abs() < dec!(0.01));
+
+    // 无涨跌时为50
+    let mut rsi = Rsi::new(2)?;
+    for _ in 0..3 {
+        rsi.update(dec!(100));
+    }
+    assert_eq!(rsi.value(), Some(dec!(50)));
+    Ok(())
+}
+
+#[test]
+fn test_atr() -> Result<()> {
+    let mut atr = Atr::new(14)?;
+    let values = klines()
+        .iter()
+        .map(|kline| atr.update(kline))
+        .collect::<Vec<_>>();
+
+    // 首根K线无前收盘价, 第15根K线起有值
+    assert!(values[..14].iter().all(Option::is_none));
+    assert_near(values[14], dec!(0.5386));
+    assert_near(atr.value(), dec!(0.6781));
+    Ok(())
+}
+
+#[test]
+fn test_bollinger() -> Result<()> {
+    let mut bollinger = Bollinger::new(20, dec!(2))?;
+    for close in closes() {
+        bollinger.update(close);
+    }
+    let value = bollinger.value().unwrap();
+    assert_near(Some(value.upper), dec!(47.6202));
+    assert_near(Some(value.middle), dec!(45.241));
+    assert_near(Some(value.lower), dec!(42.8618));
+
+    // 标准差为0时上下轨与中轨重合
+    let mut bollinger = Bollinger::new(3, dec!(2))?;
+    for _ in 0..3 {
+        bollinger.update(dec!(10));
+    }
+    let value = bollinger.value().unwrap();
+    assert_eq!(value.upper, dec!(10));
+    assert_eq!(value.lower, dec!(10));
+    Ok(())
+}
+
+#[test]
+fn test_macd() -> Result<()> {
+    assert!(Macd::new(26, 12, 9).is_err());
+
+    let mut macd = Macd::new(3, 6, 4)?;
+    let values = closes()
+        .into_iter()
+        .map(|close| macd.update(close))
+        .collect::<Vec<_>>();
+
+    // 慢线需6个值, 信号线再需4个MACD值
+    assert!(values[..8].iter().all(Option::is_none));
+    assert!(values[8].is_some());
+    let value = macd.value().unwrap();
+    assert_near(Some(value.macd), dec!(-0.4377));
+    assert_near(Some(value.signal), dec!(-0.4352));
+    assert_eq!(value.histogram, value.macd - value.signal);
+    Ok(())
+}
+
+#[test]
+fn test_vwap() -> Result<()> {
+    let begin = "202401010000".to_date()?;
+    let kline = |open_time: DateTime<Utc>, size: Decimal, cash: Decimal| Kline {
+        open_time,
+        size,
+        cash,
+        ..klines()[0].clone()
+    };
+
+    let mut vwap = Vwap::new(Some(KlineInterval::D1));
+    assert_eq!(vwap.update(&kline(begin, dec!(0), dec!(0))), None);
+    assert_eq!(
+        vwap.update(&kline(begin, dec!(1), dec!(100))),
+        Some(dec!(100))
+    );
+    assert_eq!(
+        vwap.update(&kline(begin + Duration::hours(1), dec!(3), dec!(330))),
+        Some(dec!(107.5))
+    );
+
+    // 新的一天重新累计
+    assert_eq!(
+        vwap.update(&kline(begin + Duration::days(1), dec!(2), dec!(240))),
+        Some(dec!(120))
+    );
+
+    // 不重置时从首根K线起累计
+    let mut vwap = Vwap::new(None);
+    vwap.update(&kline(begin, dec!(1), dec!(100)));
+    vwap.update(&kline(begin + Duration::days(1), dec!(1), dec!(200)));
+    assert_eq!(vwap.value(), Some(dec!(150)));
+    Ok(())
+}
+
+#[test]
+fn test_indicator() -> Result<()> {
+    let mut indicator = Indicator::Rsi(Rsi::new(14)?);
+    for kline in klines() {
+        indicator.update(&kline);
+    }
+    let Indicator::Rsi(rsi) = indicator else {
+        unreachable!()
+    };
+    assert!((rsi.value().unwrap() - dec!(37.79)).abs() < dec!(0.01));
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use destiny_engine::prelude::*;
+
+fn kline(open_time: DateTime<Utc>, open: Decimal, close: Decimal) -> Kline {
+    Kline {
+        symbol: "ETHUSDT".to_string(),
+        open_time,
+        open,
+        high: open.max(close),
+        low: open.min(close),
+        close,
+        size: dec!(1),
+        cash: close,
+        buy_size: Decimal::ZERO,
+        buy_cash: Decimal::ZERO,
+        trades: 1,
+        interval: KlineInterval::M1,
+    }
+}
+
+#[test]
+fn test_kline_interval_open_time() -> Result<()> {
+    let time = "202401031234".to_date()?;
+    assert_eq!(KlineInterval::M5.open_time(time), "202401031230".to_date()?);
+    assert_eq!(KlineInterval::H4.open_time(time), "202401031200".to_date()?);
+    assert_eq!(KlineInterval::D1.open_time(time), "20240103".to_date()?);
+    // 2024-01-01为周一
+    assert_eq!(KlineInterval::W1.open_time(time), "20240101".to_date()?);
+    assert_eq!(KlineInterval::Mo1.open_time(time), "20240101".to_date()?);
+    assert_eq!(
+        KlineInterval::Mo1.next_open_time("20241201".to_date()?),
+        "20250101".to_date()?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_kline_aggregator() -> Result<()> {
+    let begin = "202401010000".to_date()?;
+    let mut aggregator = KlineAggregator::new(KlineInterval::M5);
+
+    for i in 0..4 {
+        let open = Decimal::from(100 + i);
+        let klines = aggregator.push(&kline(begin + Duration::minutes(i), open, open + dec!(1)));
+        assert!(klines.is_empty());
+    }
+
+    // 周期内最后一分钟加入后收盘
+    let klines = aggregator.push(&kline(begin + Duration::minutes(4), dec!(104), dec!(90)));
+    assert_eq!(klines.len(), 1);
+    let kline5 = &klines[0];
+    assert_eq!(kline5.interval, KlineInterval::M5);
+    assert_eq!(kline5.open_time, begin);
+    assert_eq!(kline5.open, dec!(100));
+    assert_eq!(kline5.high, dec!(104));
+    assert_eq!(kline5.low, dec!(90));
+    assert_eq!(kline5.close, dec!(90));
+    assert_eq!(kline5.size, dec!(5));
+    assert_eq!(kline5.trades, 5);
+
+    // 数据缺失时在下一周期的首根K线加入时收盘
+    aggregator.push(&kline(begin + Duration::minutes(5), dec!(90), dec!(91)));
+    let klines = aggregator.push(&kline(begin + Duration::minutes(11), dec!(91), dec!(92)));
+    assert_eq!(klines.len(), 1);
+    assert_eq!(klines[0].open_time, begin + Duration::minutes(5));
+    assert_eq!(klines[0].close, dec!(91));
+    Ok(())
+}
+
+#[test]
+fn test_kline_history() -> Result<()> {
+    let begin = "202401010000".to_date()?;
+    let mut history = KlineHistory::new(3);
+    assert!(history.last(10).is_empty());
+
+    for i in 0..5 {
+        let open = Decimal::from(100 + i);
+        history.push(kline(begin + Duration::minutes(i), open, open + dec!(1)));
+    }
+
+    // 超出容量时丢弃最早的K线
+    let klines = history.last(10);
+    assert_eq!(klines.len(), 3);
+    assert_eq!(klines[0].open_time, begin + Duration::minutes(2));
+    assert_eq!(klines[2].open_time, begin + Duration::minutes(4));
+
+    let klines = history.last(2);
+    assert_eq!(klines.len(), 2);
+    assert_eq!(klines[0].open, dec!(103));
+
+    let columns = KlineColumns::from(klines.as_slice());
+    assert_eq!(columns.open, vec![103.0, 104.0]);
+    assert_eq!(columns.close, vec![104.0, 105.0]);
+    assert_eq!(columns.open_time[1], begin + Duration::minutes(4));
+    Ok(())
+}
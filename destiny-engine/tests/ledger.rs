@@ -0,0 +1,38 @@
+use destiny_engine::prelude::*;
+
+fn trade(time: DateTime<Utc>) -> Trade {
+    Trade {
+        symbol: "ETHUSDT".to_string(),
+        order_id: String::gen_id(),
+        side: TradeSide::Long,
+        reduce_only: false,
+        price: dec!(100),
+        size: dec!(1),
+        fee: dec!(0.05),
+        is_maker: false,
+        pnl: Decimal::ZERO,
+        time,
+    }
+}
+
+#[test]
+fn test_ledger_trades_between() -> Result<()> {
+    let begin = "20240101".to_date()?;
+    let ledger = SymbolLedger {
+        trades: (0..10)
+            .map(|i| trade(begin + Duration::minutes(i)))
+            .collect(),
+        ..Default::default()
+    };
+
+    // 包含开始时间, 不包含结束时间
+    let trades = ledger.trades_between(begin + Duration::minutes(2), begin + Duration::minutes(5));
+    assert_eq!(trades.len(), 3);
+    assert_eq!(trades[0].time, begin + Duration::minutes(2));
+
+    let json = serde_json::to_string(&ledger)?;
+    let ledger = serde_json::from_str::<SymbolLedger>(&json)?;
+    assert_eq!(ledger.trades.len(), 10);
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use common::{order, symbol_position};
+use destiny_engine::prelude::*;
+use std::collections::HashMap;
+
+fn account(cash: Decimal, mark: Decimal, long: Position, short: Position) -> Account {
+    let mut symbol_position = symbol_position(PositionMode::Hedge, mark);
+    symbol_position.long = long;
+    symbol_position.short = short;
+    Account {
+        cash,
+        positions: HashMap::from([("ETHUSDT".to_string(), symbol_position)]),
+    }
+}
+
+fn position(side: TradeSide, price: Decimal, size: Decimal) -> Position {
+    Position {
+        side,
+        price,
+        size,
+        margin_isolated: Decimal::ZERO,
+    }
+}
+
+#[test]
+fn test_liquidation_price_long() {
+    let mut account = account(
+        dec!(1000),
+        dec!(2000),
+        position(TradeSide::Long, dec!(2000), dec!(1)),
+        position(TradeSide::Short, Decimal::ZERO, Decimal::ZERO),
+    );
+
+    // (2000 - 1000) / (1 - 0.004)
+    let price = account.liquidation_price("ETHUSDT", TradeSide::Long);
+    assert_eq!(price.round_dp(4), dec!(1004.0161));
+    assert!(!account.is_liquidatable());
+
+    account
+        .positions
+        .get_mut("ETHUSDT")
+        .unwrap()
+        .symbol
+        .market
+        .mark = dec!(1010);
+    assert!(!account.is_liquidatable());
+
+    account
+        .positions
+        .get_mut("ETHUSDT")
+        .unwrap()
+        .symbol
+        .market
+        .mark = dec!(1000);
+    assert!(account.is_liquidatable());
+}
+
+#[test]
+fn test_liquidation_price_short() {
+    let account = account(
+        dec!(1000),
+        dec!(2000),
+        position(TradeSide::Long, Decimal::ZERO, Decimal::ZERO),
+        position(TradeSide::Short, dec!(2000), dec!(1)),
+    );
+
+    // (1000 + 2000) / (1 + 0.004)
+    let price = account.liquidation_price("ETHUSDT", TradeSide::Short);
+    assert_eq!(price.round_dp(4), dec!(2988.0478));
+}
+
+#[test]
+fn test_liquidation_price_hedged() {
+    let account = account(
+        dec!(1000),
+        dec!(2000),
+        position(TradeSide::Long, dec!(2000), dec!(1)),
+        position(TradeSide::Short, dec!(2000), dec!(1)),
+    );
+
+    // 多空对冲时价格下跌不会产生亏损, 仅在维持保证金随价格上涨超过权益时强平
+    // (1000 - 2000 + 2000) / (0.004 + 0.004)
+    let price = account.liquidation_price("ETHUSDT", TradeSide::Long);
+    assert_eq!(price, dec!(125000));
+    assert_eq!(
+        account.liquidation_price("ETHUSDT", TradeSide::Short),
+        price
+    );
+}
+
+#[test]
+fn test_liquidation_price_isolated() {
+    let mut account = account(
+        dec!(1000),
+        dec!(2000),
+        position(TradeSide::Long, dec!(2000), dec!(1)),
+        position(TradeSide::Short, Decimal::ZERO, Decimal::ZERO),
+    );
+    let symbol_position = account.positions.get_mut("ETHUSDT").unwrap();
+    symbol_position.margin_mode = MarginMode::Isolated;
+    symbol_position.long.margin_isolated = dec!(200);
+
+    // 逐仓仅以逐仓保证金承担亏损: (2000 - 200) / (1 - 0.004)
+    let price = account.liquidation_price("ETHUSDT", TradeSide::Long);
+    assert_eq!(price.round_dp(4), dec!(1807.2289));
+    assert_eq!(account.cash_available(), dec!(800));
+
+    let symbol_position = account.positions.get_mut("ETHUSDT").unwrap();
+    symbol_position.symbol.market.mark = dec!(1900);
+    assert!(!symbol_position.is_liquidatable_isolated(TradeSide::Long));
+
+    symbol_position.symbol.market.mark = dec!(1800);
+    assert!(symbol_position.is_liquidatable_isolated(TradeSide::Long));
+    assert!(!account.is_liquidatable());
+}
+
+#[test]
+fn test_liquidation_isolated_hedged() {
+    let mut account = account(
+        dec!(1000),
+        dec!(2000),
+        position(TradeSide::Long, dec!(2000), dec!(1)),
+        position(TradeSide::Short, dec!(2000), dec!(1)),
+    );
+    let symbol_position = account.positions.get_mut("ETHUSDT").unwrap();
+    symbol_position.margin_mode = MarginMode::Isolated;
+    symbol_position.long.margin_isolated = dec!(200);
+    symbol_position.short.margin_isolated = dec!(500);
+    assert_eq!(symbol_position.position_margin(TradeSide::Long), dec!(200));
+    assert_eq!(symbol_position.position_margin(TradeSide::Short), dec!(500));
+
+    // 各方向仅以该方向的逐仓保证金承担亏损, 对冲方向的盈利不计入
+    assert_eq!(
+        account
+            .liquidation_price("ETHUSDT", TradeSide::Long)
+            .round_dp(4),
+        dec!(1807.2289)
+    );
+    // (2000 + 500) / (1 + 0.004)
+    assert_eq!(
+        account
+            .liquidation_price("ETHUSDT", TradeSide::Short)
+            .round_dp(4),
+        dec!(2490.0398)
+    );
+
+    let symbol_position = account.positions.get_mut("ETHUSDT").unwrap();
+    symbol_position.symbol.market.mark = dec!(1800);
+    assert!(symbol_position.is_liquidatable_isolated(TradeSide::Long));
+    assert!(!symbol_position.is_liquidatable_isolated(TradeSide::Short));
+
+    // 平多仓一半释放多头一半的逐仓保证金, 空头不变
+    let mut close = order(TradeSide::Long, true, dec!(0.5));
+    symbol_position.fill(&mut close, dec!(1900), dec!(0.5), Decimal::ZERO);
+    assert_eq!(symbol_position.long.margin_isolated, dec!(100));
+    assert_eq!(symbol_position.short.margin_isolated, dec!(500));
+
+    let mut close = order(TradeSide::Long, true, dec!(0.5));
+    symbol_position.fill(&mut close, dec!(1900), dec!(0.5), Decimal::ZERO);
+    assert_eq!(symbol_position.long.margin_isolated, Decimal::ZERO);
+    assert_eq!(symbol_position.margin_isolated(), dec!(500));
+}
@@ -0,0 +1,208 @@
+mod common;
+
+use common::{backtest_config, step, HistoryDir, Step, TestStrategy};
+use destiny_engine::prelude::*;
+
+fn kline(open: Decimal, high: Decimal, low: Decimal, close: Decimal) -> Kline {
+    Kline {
+        symbol: "ETHUSDT".to_string(),
+        open_time: Default::default(),
+        open,
+        high,
+        low,
+        close,
+        size: Decimal::ZERO,
+        cash: Decimal::ZERO,
+        buy_size: Decimal::ZERO,
+        buy_cash: Decimal::ZERO,
+        trades: 0,
+        interval: KlineInterval::M1,
+    }
+}
+
+#[test]
+fn test_kline_matcher() {
+    let kline = kline(dec!(100), dec!(110), dec!(95), dec!(105));
+
+    let matcher = KlineMatcher::new(&kline, KlinePath::OpenHighLowClose);
+    // 买入限价在最高价之后的下跌中被触及
+    assert_eq!(
+        matcher.touch_limit(true, dec!(96)),
+        Some((dec!(24), dec!(96)))
+    );
+    // 卖出限价在上涨中被触及
+    assert_eq!(
+        matcher.touch_limit(false, dec!(108)),
+        Some((dec!(8), dec!(108)))
+    );
+    // 未被触及
+    assert_eq!(matcher.touch_limit(true, dec!(94)), None);
+    assert_eq!(matcher.touch_limit(false, dec!(111)), None);
+    // 跳空: 开盘即越过限价, 以开盘价成交
+    assert_eq!(
+        matcher.touch_limit(true, dec!(102)),
+        Some((Decimal::ZERO, dec!(100)))
+    );
+
+    let matcher = KlineMatcher::new(&kline, KlinePath::OpenLowHighClose);
+    assert_eq!(
+        matcher.touch_limit(true, dec!(96)),
+        Some((dec!(4), dec!(96)))
+    );
+    assert_eq!(
+        matcher.touch_limit(false, dec!(108)),
+        Some((dec!(18), dec!(108)))
+    );
+
+    // 自指定位置起到达: 位置12为最高价之后下跌中的108
+    let matcher = KlineMatcher::new(&kline, KlinePath::OpenHighLowClose);
+    assert_eq!(matcher.end(), dec!(35));
+    assert_eq!(
+        matcher.touch_from(dec!(108), true, dec!(12)),
+        Some((dec!(12), dec!(108)))
+    );
+    assert_eq!(matcher.touch_from(dec!(109), true, dec!(12)), None);
+    assert_eq!(
+        matcher.touch_limit_from(true, dec!(96), dec!(10)),
+        Some((dec!(24), dec!(96)))
+    );
+    // 位置0与touch相同
+    assert_eq!(
+        matcher.touch_from(dec!(108), true, Decimal::ZERO),
+        matcher.touch(dec!(108), true)
+    );
+
+    // 最低价距离开盘价更近, 先到达最低价
+    let matcher = KlineMatcher::new(&kline, KlinePath::Nearest);
+    assert_eq!(
+        matcher.touch_limit(true, dec!(96)),
+        Some((dec!(4), dec!(96)))
+    );
+}
+
+/// 按开高低收路径撮合的回测, 返回全部成交
+async fn ohlc_trades(name: &str, klines: &[[&str; 4]], steps: Vec<Step>) -> Result<Vec<Trade>> {
+    let history = HistoryDir::new(name);
+    history.market_series_write("ETHUSDT", klines)?;
+    let strategy = TestStrategy::new(steps);
+    Backtest::run(
+        backtest_config(&history)
+            .match_mode(MatchMode::Ohlc)
+            .kline_path(KlinePath::OpenHighLowClose)
+            .build()?,
+        strategy.clone(),
+    )
+    .await?;
+    Ok(strategy.engine().trades(
+        "ETHUSDT",
+        DateTime::<Utc>::MIN_UTC,
+        DateTime::<Utc>::MAX_UTC,
+    ))
+}
+
+#[tokio::test]
+async fn test_ohlc_stop_limit() -> Result<()> {
+    // 买入止损限价单在105触发, 此时限价106已可成交, 以触发价格成交而非开盘价
+    let trades = ohlc_trades(
+        "ohlc_stop_limit",
+        &[["100"; 4], ["100", "107", "99", "100"]],
+        vec![step(|engine| async move {
+            let request = OrderRequest::stop_limit(
+                "ETHUSDT",
+                TradeSide::Long,
+                false,
+                dec!(1),
+                dec!(106),
+                dec!(105),
+            );
+            engine.order_place(request).await?;
+            Ok(())
+        })],
+    )
+    .await?;
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].price, dec!(105));
+
+    // 限价104在触发之后的回落中到达
+    let trades = ohlc_trades(
+        "ohlc_stop_limit",
+        &[["100"; 4], ["100", "107", "99", "100"]],
+        vec![step(|engine| async move {
+            let request = OrderRequest::stop_limit(
+                "ETHUSDT",
+                TradeSide::Long,
+                false,
+                dec!(1),
+                dec!(104),
+                dec!(105),
+            );
+            engine.order_place(request).await?;
+            Ok(())
+        })],
+    )
+    .await?;
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].price, dec!(104));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ohlc_take_profit_limit() -> Result<()> {
+    // 卖出止盈限价单在110触发, 上涨途中先到达的限价108不能成交
+    let trades = ohlc_trades(
+        "ohlc_take_profit_limit",
+        &[["100"; 4], ["100"; 4], ["100", "115", "99", "100"]],
+        vec![
+            step(|engine| async move {
+                engine.long_market_open("ETHUSDT", dec!(1)).await?;
+                Ok(())
+            }),
+            step(|engine| async move {
+                let request = OrderRequest::take_profit_limit(
+                    "ETHUSDT",
+                    TradeSide::Long,
+                    true,
+                    dec!(1),
+                    dec!(108),
+                    dec!(110),
+                );
+                engine.order_place(request).await?;
+                Ok(())
+            }),
+        ],
+    )
+    .await?;
+    assert_eq!(trades.len(), 2);
+    assert_eq!(trades[1].price, dec!(110));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ohlc_trailing_stop() -> Result<()> {
+    // 回调5%的跟踪止损: 最高价120将触发价格移动到114, 在之后的回落中以114成交
+    let trades = ohlc_trades(
+        "ohlc_trailing_stop",
+        &[["100"; 4], ["100"; 4], ["100", "120", "100", "110"]],
+        vec![
+            step(|engine| async move {
+                engine.long_market_open("ETHUSDT", dec!(1)).await?;
+                Ok(())
+            }),
+            step(|engine| async move {
+                let request = OrderRequest::trailing_stop_market(
+                    "ETHUSDT",
+                    TradeSide::Long,
+                    true,
+                    dec!(1),
+                    dec!(0.05),
+                );
+                engine.order_place(request).await?;
+                Ok(())
+            }),
+        ],
+    )
+    .await?;
+    assert_eq!(trades.len(), 2);
+    assert_eq!(trades[1].price, dec!(114));
+    Ok(())
+}
@@ -0,0 +1,295 @@
+mod common;
+
+use common::{backtest_config, order, step, HistoryDir, TestStrategy};
+use destiny_engine::prelude::*;
+
+#[test]
+fn test_order_request() {
+    let request = OrderRequest::limit("ETHUSDT", TradeSide::Long, false, dec!(1), dec!(2000))
+        .with_time_in_force(TimeInForce::Gtx)
+        .with_client_id("my-order");
+    assert_eq!(request.r#type, TradeType::Limit);
+    assert_eq!(request.price, dec!(2000));
+    assert_eq!(request.time_in_force, TimeInForce::Gtx);
+    assert_eq!(request.client_id.as_deref(), Some("my-order"));
+    assert!(!request.reduce_only);
+
+    // 全部平仓订单总是只减仓
+    let request = OrderRequest::market("ETHUSDT", TradeSide::Short, false, Decimal::ZERO)
+        .with_close_position();
+    assert_eq!(request.r#type, TradeType::Market);
+    assert_eq!(request.time_in_force, TimeInForce::Gtc);
+    assert!(request.close_position);
+    assert!(request.reduce_only);
+}
+
+fn kline(high: Decimal, low: Decimal, close: Decimal) -> Kline {
+    Kline {
+        symbol: "ETHUSDT".to_string(),
+        open_time: Default::default(),
+        open: close,
+        high,
+        low,
+        close,
+        size: Decimal::ZERO,
+        cash: Decimal::ZERO,
+        buy_size: Decimal::ZERO,
+        buy_cash: Decimal::ZERO,
+        trades: 0,
+        interval: KlineInterval::M1,
+    }
+}
+
+/// 做多平仓条件单(卖出)
+fn order_conditional(r#type: TradeType, stop_price: Decimal, callback_rate: Decimal) -> Order {
+    let mut order = order(TradeSide::Long, true, dec!(1));
+    order.r#type = r#type;
+    order.stop_price = stop_price;
+    order.callback_rate = callback_rate;
+    order
+}
+
+#[test]
+fn test_order_trigger() {
+    // 卖出止损向下触发
+    let mut order = order_conditional(TradeType::StopMarket, dec!(1900), Decimal::ZERO);
+    assert!(order.is_pending_trigger());
+    assert!(!order.is_trigger_rising());
+    assert!(!order.trigger(&kline(dec!(2050), dec!(1950), dec!(2000))));
+    assert!(order.trigger(&kline(dec!(2000), dec!(1890), dec!(1950))));
+
+    // 卖出止盈向上触发
+    let mut order = order_conditional(TradeType::TakeProfitMarket, dec!(2100), Decimal::ZERO);
+    assert!(order.is_trigger_rising());
+    assert!(!order.trigger(&kline(dec!(2050), dec!(1950), dec!(2000))));
+    assert!(order.trigger(&kline(dec!(2100), dec!(1950), dec!(2000))));
+
+    // 跟踪止损触发价格随最高价上移, 不随价格回落下移
+    let mut order = order_conditional(TradeType::TrailingStopMarket, dec!(1980), dec!(0.01));
+    assert!(!order.trigger(&kline(dec!(2100), dec!(2050), dec!(2090))));
+    assert_eq!(order.stop_price, dec!(2079));
+    assert!(!order.trigger(&kline(dec!(2095), dec!(2080), dec!(2085))));
+    assert_eq!(order.stop_price, dec!(2079));
+    assert!(order.trigger(&kline(dec!(2090), dec!(2070), dec!(2075))));
+}
+
+fn order_error(err: anyhow::Error) -> OrderError {
+    err.downcast_ref::<OrderError>()
+        .cloned()
+        .expect("不是订单错误")
+}
+
+#[tokio::test]
+async fn test_order_cancel() -> Result<()> {
+    let history = HistoryDir::new("order_cancel");
+    history.market_series_write("ETHUSDT", &[["100"; 4]; 3])?;
+    let strategy = TestStrategy::new(vec![
+        step(|engine| async move {
+            // 客户端订单ID即订单ID, 未完成订单中不可重复
+            let request = OrderRequest::limit("ETHUSDT", TradeSide::Long, false, dec!(1), dec!(90))
+                .with_client_id("my-order");
+            assert_eq!(engine.order_place(request.clone()).await?, "my-order");
+            assert!(engine.order_place(request).await.is_err());
+            Ok(())
+        }),
+        step(|engine| async move {
+            let id = engine.orders("ETHUSDT")[0].id.clone();
+            assert_eq!(id, "my-order");
+            assert_eq!(
+                engine.order("ETHUSDT", &id).unwrap().status,
+                OrderStatus::Submitted
+            );
+            engine.order_close("ETHUSDT", &id).await?;
+            assert!(engine.order("ETHUSDT", &id).is_none());
+
+            // 已取消的订单不能再撤销或修改
+            let finalized = OrderError::Finalized {
+                id: id.clone(),
+                status: OrderStatus::Canceled,
+            };
+            let err = engine.order_close("ETHUSDT", &id).await.unwrap_err();
+            assert_eq!(order_error(err), finalized);
+            let err = engine
+                .order_amend("ETHUSDT", &id, dec!(95), dec!(1))
+                .await
+                .unwrap_err();
+            assert_eq!(order_error(err), finalized);
+
+            let err = engine.order_close("ETHUSDT", "missing").await.unwrap_err();
+            assert_eq!(
+                order_error(err),
+                OrderError::NotFound {
+                    symbol: "ETHUSDT".to_string(),
+                    id: "missing".to_string(),
+                }
+            );
+            Ok(())
+        }),
+    ]);
+    Backtest::run(backtest_config(&history).build()?, strategy.clone()).await?;
+
+    // 撤单产生已取消事件, 并归档到账本
+    let engine = strategy.engine();
+    let orders = engine.orders_history(
+        "ETHUSDT",
+        DateTime::<Utc>::MIN_UTC,
+        DateTime::<Utc>::MAX_UTC,
+    );
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].status, OrderStatus::Canceled);
+    assert_eq!(
+        strategy.order_statuses(&orders[0].id),
+        vec![OrderStatus::Canceled]
+    );
+    Ok(())
+}
+
+/// 第1根K线收盘后提交订单, 在第2根K线(价格100, 成交量10)撮合, 返回订单的最终状态与订单事件
+async fn order_cross(
+    name: &str,
+    request: OrderRequest,
+    participation_rate: Option<Decimal>,
+) -> Result<(Order, Vec<OrderStatus>)> {
+    let history = HistoryDir::new(name);
+    history.market_series_write("ETHUSDT", &[["100"; 4]; 3])?;
+    let strategy = TestStrategy::new(vec![step(move |engine| {
+        let request = request.clone();
+        async move {
+            engine.order_place(request).await?;
+            Ok(())
+        }
+    })]);
+    Backtest::run(
+        backtest_config(&history)
+            .participation_rate(participation_rate)
+            .build()?,
+        strategy.clone(),
+    )
+    .await?;
+
+    let engine = strategy.engine();
+    let mut orders = engine.orders("ETHUSDT");
+    orders.extend(engine.orders_history(
+        "ETHUSDT",
+        DateTime::<Utc>::MIN_UTC,
+        DateTime::<Utc>::MAX_UTC,
+    ));
+    assert_eq!(orders.len(), 1);
+    let statuses = strategy.order_statuses(&orders[0].id);
+    Ok((orders.remove(0), statuses))
+}
+
+#[tokio::test]
+async fn test_order_ioc() -> Result<()> {
+    // 参与率限制每根K线只能成交1, 部分成交后取消剩余
+    let request = OrderRequest::limit("ETHUSDT", TradeSide::Long, false, dec!(3), dec!(100))
+        .with_time_in_force(TimeInForce::Ioc);
+    let (order, statuses) = order_cross("order_ioc", request, Some(dec!(0.1))).await?;
+    assert_eq!(order.status, OrderStatus::Canceled);
+    assert_eq!(order.deal_size, dec!(1));
+    assert_eq!(statuses, vec![OrderStatus::Canceled]);
+
+    // 未成交则取消
+    let request = OrderRequest::limit("ETHUSDT", TradeSide::Long, false, dec!(3), dec!(99))
+        .with_time_in_force(TimeInForce::Ioc);
+    let (order, statuses) = order_cross("order_ioc", request, None).await?;
+    assert_eq!(order.status, OrderStatus::Canceled);
+    assert_eq!(order.deal_size, Decimal::ZERO);
+    assert_eq!(statuses, vec![OrderStatus::Canceled]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_order_fok() -> Result<()> {
+    // 不能全部成交时整单取消, 不产生成交
+    let request = OrderRequest::limit("ETHUSDT", TradeSide::Long, false, dec!(3), dec!(100))
+        .with_time_in_force(TimeInForce::Fok);
+    let (order, statuses) = order_cross("order_fok", request.clone(), Some(dec!(0.1))).await?;
+    assert_eq!(order.status, OrderStatus::Canceled);
+    assert_eq!(order.deal_size, Decimal::ZERO);
+    assert_eq!(statuses, vec![OrderStatus::Canceled]);
+
+    let (order, statuses) = order_cross("order_fok", request, None).await?;
+    assert_eq!(order.status, OrderStatus::Filled);
+    assert_eq!(order.deal_size, dec!(3));
+    assert_eq!(statuses, vec![OrderStatus::Filled]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_order_gtx() -> Result<()> {
+    // 提交后首次撮合即可成交的只做挂单被拒绝
+    let request = OrderRequest::limit("ETHUSDT", TradeSide::Long, false, dec!(1), dec!(101))
+        .with_time_in_force(TimeInForce::Gtx);
+    let (order, statuses) = order_cross("order_gtx", request, None).await?;
+    assert_eq!(order.status, OrderStatus::Rejected);
+    assert_eq!(order.deal_size, Decimal::ZERO);
+    assert_eq!(statuses, vec![OrderStatus::Rejected]);
+
+    // 不能成交时正常挂单
+    let request = OrderRequest::limit("ETHUSDT", TradeSide::Long, false, dec!(1), dec!(99))
+        .with_time_in_force(TimeInForce::Gtx);
+    let (order, statuses) = order_cross("order_gtx", request, None).await?;
+    assert_eq!(order.status, OrderStatus::Submitted);
+    assert!(statuses.is_empty());
+
+    // 市价单不支持只做挂单
+    let request = OrderRequest::market("ETHUSDT", TradeSide::Long, false, dec!(1))
+        .with_time_in_force(TimeInForce::Gtx);
+    assert!(order_cross("order_gtx", request, None).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_order_close_position() -> Result<()> {
+    let history = HistoryDir::new("order_close_position");
+    history.market_series_write(
+        "ETHUSDT",
+        &[
+            ["100"; 4],
+            ["100"; 4],
+            ["100"; 4],
+            ["100"; 4],
+            ["100", "100", "90", "90"],
+        ],
+    )?;
+    // 持仓2时提交全部平仓止损单, 之后加仓1, 触发时按当时的持仓数量平仓
+    let strategy = TestStrategy::new(vec![
+        step(|engine| async move {
+            engine.long_market_open("ETHUSDT", dec!(2)).await?;
+            Ok(())
+        }),
+        step(|engine| async move {
+            let request = OrderRequest::stop_market(
+                "ETHUSDT",
+                TradeSide::Long,
+                true,
+                Decimal::ZERO,
+                dec!(95),
+            )
+            .with_close_position();
+            engine.order_place(request).await?;
+            assert_eq!(engine.orders("ETHUSDT")[0].size, dec!(2));
+            Ok(())
+        }),
+        step(|engine| async move {
+            engine.long_market_open("ETHUSDT", dec!(1)).await?;
+            Ok(())
+        }),
+    ]);
+    Backtest::run(backtest_config(&history).build()?, strategy.clone()).await?;
+
+    let engine = strategy.engine();
+    assert_eq!(engine.long_size("ETHUSDT"), Decimal::ZERO);
+    let orders = engine.orders_history(
+        "ETHUSDT",
+        DateTime::<Utc>::MIN_UTC,
+        DateTime::<Utc>::MAX_UTC,
+    );
+    let order = orders.iter().find(|order| order.close_position).unwrap();
+    assert_eq!(order.status, OrderStatus::Filled);
+    assert_eq!(order.size, dec!(3));
+    assert_eq!(order.deal_size, dec!(3));
+    assert_eq!(order.deal_price, dec!(90));
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use destiny_engine::prelude::*;
+use std::io::Write;
+
+fn kline_line(open_time: DateTime<Utc>, close: &str, closed: bool) -> String {
+    format!(
+        r#"{{"stream":"btcusdt@kline_1m","data":{{"e":"kline","E":0,"s":"BTCUSDT","k":{{"t":{},"s":"BTCUSDT","i":"1m","o":"100","c":"{}","h":"101","l":"94","v":"10","n":10,"x":{},"q":"1000","V":"5","Q":"500"}}}}}}"#,
+        open_time.timestamp_millis(),
+        close,
+        closed
+    )
+}
+
+fn mark_price_line(mark: &str, next_funding_time: DateTime<Utc>) -> String {
+    format!(
+        r#"{{"stream":"btcusdt@markPrice@1s","data":{{"e":"markPriceUpdate","E":0,"s":"BTCUSDT","p":"{0}","i":"{0}","P":"{0}","r":"0.0001","T":{1}}}}}"#,
+        mark,
+        next_funding_time.timestamp_millis()
+    )
+}
+
+fn replay_file(name: &str, lines: &[String]) -> Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!(
+        "destiny_paper_{}_{}.jsonl",
+        name,
+        std::process::id()
+    ));
+    let mut file = std::fs::File::create(&path)?;
+    for line in lines {
+        writeln!(file, "{}", line)?;
+    }
+    Ok(path)
+}
+
+struct TestStrategy {
+    place: bool,
+    minutes: Mutex<Vec<DateTime<Utc>>>,
+    hours: Mutex<Vec<DateTime<Utc>>>,
+    orders: Mutex<Vec<(DateTime<Utc>, Order)>>,
+}
+
+impl TestStrategy {
+    fn new(place: bool) -> Arc<Self> {
+        Arc::new(Self {
+            place,
+            minutes: Mutex::new(Vec::new()),
+            hours: Mutex::new(Vec::new()),
+            orders: Mutex::new(Vec::new()),
+        })
+    }
+}
+
+#[async_trait]
+impl Strategy for TestStrategy {
+    async fn on_init(&self, engine: Arc<dyn Engine>) -> Result<()> {
+        engine.symbol_init("BTCUSDT")
+    }
+    async fn on_kline(&self, engine: Arc<dyn Engine>, _kline: Kline) -> Result<()> {
+        if self.place
+            && engine
+                .orders_history(
+                    "BTCUSDT",
+                    DateTime::<Utc>::MIN_UTC,
+                    DateTime::<Utc>::MAX_UTC,
+                )
+                .is_empty()
+            && engine.orders("BTCUSDT").is_empty()
+        {
+            engine.long_limit_open("BTCUSDT", dec!(1), dec!(95)).await?;
+        }
+        Ok(())
+    }
+    async fn on_hourly(&self, engine: Arc<dyn Engine>) -> Result<()> {
+        self.hours.lock().push(engine.time());
+        Ok(())
+    }
+    async fn on_minutely(&self, engine: Arc<dyn Engine>) -> Result<()> {
+        self.minutes.lock().push(engine.time());
+        Ok(())
+    }
+    async fn on_order(&self, engine: Arc<dyn Engine>, order: Order) -> Result<()> {
+        self.orders.lock().push((engine.time(), order));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_paper_replay() -> Result<()> {
+    let begin = "202401010000".to_date()?;
+    let state_path =
+        std::env::temp_dir().join(format!("destiny_paper_state_{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&state_path);
+
+    let replay = replay_file(
+        "first",
+        &[
+            mark_price_line("100", begin + Duration::hours(8)),
+            kline_line(begin, "100", true),
+            // 未收盘的K线仅更新最新价格
+            kline_line(begin + Duration::minutes(1), "90", false),
+            kline_line(begin + Duration::minutes(1), "95", true),
+            kline_line(begin + Duration::minutes(2), "96", true),
+            // 下次结算时间变化时按结算前的资金费率结算
+            mark_price_line("96", begin + Duration::hours(16)),
+        ],
+    )?;
+    let strategy = TestStrategy::new(true);
+    let paper = Paper::new(
+        PaperConfigBuilder::default()
+            .source(PaperSource::Replay {
+                path: replay.clone(),
+                begin,
+            })
+            .backtest(
+                BacktestConfigBuilder::default()
+                    .slippage_rate(Decimal::ZERO)
+                    .to_owned(),
+            )
+            .state_path(Some(state_path.clone()))
+            .build()?,
+        strategy.clone(),
+    )?;
+    paper.run0().await?;
+    let _ = std::fs::remove_file(&replay);
+
+    // 以已收盘K线的收盘时间作为引擎时间执行每分钟事件
+    assert_eq!(
+        *strategy.minutes.lock(),
+        vec![
+            begin + Duration::minutes(1),
+            begin + Duration::minutes(2),
+            begin + Duration::minutes(3),
+        ]
+    );
+
+    // 挂单按回测规则以收盘价撮合
+    let orders = strategy.orders.lock().clone();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].0, begin + Duration::minutes(2));
+    assert_eq!(orders[0].1.status, OrderStatus::Filled);
+    assert_eq!(orders[0].1.deal_price, dec!(95));
+    assert_eq!(orders[0].1.deal_fee, dec!(0.0475));
+    assert_eq!(paper.long_size("BTCUSDT"), dec!(1));
+    assert_eq!(paper.price_last("BTCUSDT"), dec!(96));
+    assert_eq!(paper.price_mark("BTCUSDT"), dec!(96));
+
+    let fundings = paper.fundings("BTCUSDT");
+    assert_eq!(fundings.len(), 1);
+    assert_eq!(fundings[0].fee, dec!(-0.0096));
+    let cash = paper.cash();
+    assert_eq!(cash, dec!(1000) - dec!(0.0475) - dec!(0.0096));
+    assert!(state_path.exists());
+
+    // 重启后从状态文件恢复资金、持仓与账本
+    let replay = replay_file(
+        "second",
+        &[kline_line(begin + Duration::minutes(3), "97", true)],
+    )?;
+    let strategy = TestStrategy::new(false);
+    let paper = Paper::new(
+        PaperConfigBuilder::default()
+            .source(PaperSource::Replay {
+                path: replay.clone(),
+                begin: begin + Duration::minutes(3),
+            })
+            .state_path(Some(state_path.clone()))
+            .build()?,
+        strategy.clone(),
+    )?;
+    paper.run0().await?;
+    assert_eq!(paper.cash(), cash);
+    assert_eq!(paper.long_size("BTCUSDT"), dec!(1));
+    assert_eq!(paper.long_price("BTCUSDT"), dec!(95));
+    assert_eq!(
+        paper
+            .trades(
+                "BTCUSDT",
+                DateTime::<Utc>::MIN_UTC,
+                DateTime::<Utc>::MAX_UTC
+            )
+            .len(),
+        1
+    );
+    assert_eq!(paper.fundings("BTCUSDT").len(), 1);
+    assert_eq!(paper.price_last("BTCUSDT"), dec!(97));
+
+    let _ = std::fs::remove_file(&state_path);
+    let _ = std::fs::remove_file(&replay);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_paper_timer_hourly() -> Result<()> {
+    let begin = "202401010058".to_date()?;
+    let replay = replay_file(
+        "hourly",
+        &[
+            kline_line(begin, "100", true),
+            kline_line(begin + Duration::minutes(1), "100", true),
+            kline_line(begin + Duration::minutes(2), "100", true),
+        ],
+    )?;
+    // 关闭每分钟事件后只在整点执行定时事件
+    let strategy = TestStrategy::new(false);
+    let paper = Paper::new(
+        PaperConfigBuilder::default()
+            .source(PaperSource::Replay {
+                path: replay.clone(),
+                begin,
+            })
+            .backtest(
+                BacktestConfigBuilder::default()
+                    .timer_minutely(false)
+                    .to_owned(),
+            )
+            .build()?,
+        strategy.clone(),
+    )?;
+    assert_eq!(paper.time(), begin);
+    paper.run0().await?;
+    let _ = std::fs::remove_file(&replay);
+
+    assert!(strategy.minutes.lock().is_empty());
+    assert_eq!(*strategy.hours.lock(), vec![begin + Duration::minutes(2)]);
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use destiny_engine::prelude::*;
+use std::collections::HashMap;
+
+fn equity_curve(begin: DateTime<Utc>, equities: &[Decimal]) -> Vec<EquityPoint> {
+    equities
+        .iter()
+        .enumerate()
+        .map(|(i, equity)| EquityPoint {
+            time: begin + Duration::hours(i as i64),
+            cash: *equity,
+            pnl: Decimal::ZERO,
+            equity: *equity,
+        })
+        .collect()
+}
+
+fn trade(pnl: Decimal) -> Trade {
+    Trade {
+        symbol: "ETHUSDT".to_string(),
+        order_id: String::gen_id(),
+        side: TradeSide::Long,
+        reduce_only: true,
+        price: dec!(100),
+        size: dec!(1),
+        fee: dec!(0.1),
+        is_maker: false,
+        pnl,
+        time: Default::default(),
+    }
+}
+
+#[test]
+fn test_backtest_report() -> Result<()> {
+    let begin = "20240101".to_date()?;
+    let ledgers = HashMap::from([(
+        "ETHUSDT".to_string(),
+        SymbolLedger {
+            trades: vec![trade(dec!(30)), trade(dec!(-10)), trade(dec!(20))],
+            ..Default::default()
+        },
+    )]);
+    let report = BacktestReport::new(
+        equity_curve(
+            begin,
+            &[dec!(100), dec!(120), dec!(90), dec!(110), dec!(130)],
+        ),
+        Duration::hours(1),
+        0.5,
+        &ledgers,
+    );
+
+    assert_eq!(report.equity_begin, dec!(100));
+    assert_eq!(report.equity_end, dec!(130));
+    assert_eq!(report.total_return, 0.3);
+
+    // 高点120回撤至90, 至130时恢复
+    assert_eq!(report.max_drawdown, 0.25);
+    assert_eq!(report.max_drawdown_duration, 180);
+    assert!(report.sharpe > 0.0);
+    assert!(report.sortino > 0.0);
+
+    assert_eq!(report.trade_count, 3);
+    assert_eq!(report.win_rate, 2.0 / 3.0);
+    assert_eq!(report.profit_factor, 5.0);
+    assert_eq!(report.trade_avg, dec!(40) / dec!(3));
+    assert_eq!(report.fee_total, dec!(0.3));
+    assert_eq!(report.exposure, 0.5);
+
+    let json = report.to_json()?;
+    let report = serde_json::from_str::<BacktestReport>(&json)?;
+    assert_eq!(report.equity_curve.len(), 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_backtest_report_sharpe_interval() -> Result<()> {
+    let begin = "20240101".to_date()?;
+    let equities = [dec!(100), dec!(120), dec!(90), dec!(110), dec!(130)];
+    let report = BacktestReport::new(
+        equity_curve(begin, &equities),
+        Duration::hours(1),
+        0.0,
+        &HashMap::new(),
+    );
+
+    // 结束时补充的不足一个采样间隔的采样点不参与夏普比率与索提诺比率
+    let mut curve = equity_curve(begin, &equities);
+    curve.push(EquityPoint {
+        time: begin + Duration::hours(4) + Duration::minutes(1),
+        cash: dec!(80),
+        pnl: Decimal::ZERO,
+        equity: dec!(80),
+    });
+    let truncated = BacktestReport::new(curve, Duration::hours(1), 0.0, &HashMap::new());
+    assert_eq!(truncated.sharpe, report.sharpe);
+    assert_eq!(truncated.sortino, report.sortino);
+    assert_eq!(truncated.equity_end, dec!(80));
+
+    // 采样点间隔与采样间隔不一致时不计算
+    let mismatched = BacktestReport::new(
+        equity_curve(begin, &equities),
+        Duration::hours(2),
+        0.0,
+        &HashMap::new(),
+    );
+    assert_eq!(mismatched.sharpe, 0.0);
+
+    // 按采样间隔年化: 间隔加倍时年化系数为原来的1/√2
+    let curve = equity_curve(begin, &equities)
+        .into_iter()
+        .map(|point| EquityPoint {
+            time: begin + (point.time - begin) * 2,
+            ..point
+        })
+        .collect::<Vec<EquityPoint>>();
+    let doubled = BacktestReport::new(curve, Duration::hours(2), 0.0, &HashMap::new());
+    assert!((doubled.sharpe * 2f64.sqrt() - report.sharpe).abs() < 1e-9);
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use destiny_engine::prelude::*;
+
+fn exchange_info(symbols: &[(&str, &str, &str)]) -> String {
+    let symbols = symbols
+        .iter()
+        .map(|(symbol, tick_size, notional)| {
+            format!(
+                r#"{{"symbol":"{}","status":"TRADING","filters":[
+                    {{"filterType":"PRICE_FILTER","minPrice":"0.10","maxPrice":"1000000","tickSize":"{}"}},
+                    {{"filterType":"LOT_SIZE","minQty":"0.001","maxQty":"1000","stepSize":"0.001"}},
+                    {{"filterType":"MIN_NOTIONAL","notional":"{}"}},
+                    {{"filterType":"MAX_NUM_ORDERS","limit":200}}
+                ]}}"#,
+                symbol, tick_size, notional
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+    format!(r#"{{"timezone":"UTC","symbols":[{}]}}"#, symbols)
+}
+
+fn rules_dir(name: &str) -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("destiny_rules_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(
+        dir.join("20240101.json"),
+        exchange_info(&[("BTCUSDT", "0.10", "100")]),
+    )?;
+    std::fs::write(
+        dir.join("20240301.json"),
+        exchange_info(&[("BTCUSDT", "0.01", "50"), ("SOLUSDT", "0.001", "5")]),
+    )?;
+    std::fs::write(dir.join("README.md"), "")?;
+    Ok(dir)
+}
+
+#[test]
+fn test_symbol_rules() -> Result<()> {
+    let dir = rules_dir("versions")?;
+    let rules = SymbolRules::load(&dir)?;
+    assert_eq!(
+        rules.dates(),
+        vec!["20240101".to_date()?, "20240301".to_date()?]
+    );
+
+    // 按时间使用生效的版本, 早于全部版本时使用最早的版本
+    let rule = rules.rule("BTCUSDT", "20231201".to_date()?).unwrap();
+    assert_eq!(rule.price_tick, dec!(0.1));
+    assert_eq!(rule.size_min, dec!(0.001));
+    assert_eq!(rule.amount_min, dec!(100));
+    assert_eq!(rule.order_max, 200);
+    assert!(!rule.margin_tiers.is_empty());
+    let rule = rules.rule("BTCUSDT", "20240229".to_date()?).unwrap();
+    assert_eq!(rule.price_tick, dec!(0.1));
+    let rule = rules.rule("BTCUSDT", "20240301".to_date()?).unwrap();
+    assert_eq!(rule.price_tick, dec!(0.01));
+    assert_eq!(rule.amount_min, dec!(50));
+
+    // 上架前使用上架时的版本
+    let rule = rules.rule("SOLUSDT", "20240101".to_date()?).unwrap();
+    assert_eq!(rule.price_tick, dec!(0.001));
+    assert!(rules.rule("ETHUSDT", "20240101".to_date()?).is_none());
+
+    // 单个文件对全部时间生效
+    let rules = SymbolRules::load(&dir.join("20240101.json"))?;
+    let rule = rules.rule("BTCUSDT", "20300101".to_date()?).unwrap();
+    assert_eq!(rule.price_tick, dec!(0.1));
+
+    assert!(SymbolRules::load(&dir.join("missing.json")).is_err());
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+struct TestStrategy {
+    custom: SymbolRule,
+}
+
+#[async_trait]
+impl Strategy for TestStrategy {
+    async fn on_init(&self, engine: Arc<dyn Engine>) -> Result<()> {
+        engine.symbol_init("BTCUSDT")?;
+        assert!(engine.symbol_init("ETHUSDT").is_err());
+        engine.symbol_init_with_rule("ETHUSDT", self.custom.clone())?;
+        // 初始化时使用回放开始时间生效的版本
+        assert_eq!(engine.rule_price_tick("BTCUSDT"), dec!(0.1));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_symbol_rules_engine() -> Result<()> {
+    let dir = rules_dir("engine")?;
+    let replay = dir.join("replay.jsonl");
+    let begin = "202402292359".to_date()?;
+    // 收盘时间为2024-03-01 00:00, 每日切换到当日生效的版本
+    std::fs::write(
+        &replay,
+        format!(
+            r#"{{"stream":"btcusdt@kline_1m","data":{{"e":"kline","E":0,"s":"BTCUSDT","k":{{"t":{},"s":"BTCUSDT","i":"1m","o":"100","c":"100","h":"100","l":"100","v":"1","n":1,"x":true,"q":"100","V":"0","Q":"0"}}}}}}"#,
+            begin.timestamp_millis()
+        ),
+    )?;
+    let custom = SymbolRule {
+        price_tick: dec!(0.5),
+        amount_min: dec!(20),
+        ..Default::default()
+    };
+    let paper = Paper::new(
+        PaperConfigBuilder::default()
+            .source(PaperSource::Replay {
+                path: replay,
+                begin,
+            })
+            .backtest(
+                BacktestConfigBuilder::default()
+                    .rule_path(Some(dir.clone()))
+                    .to_owned(),
+            )
+            .build()?,
+        Arc::new(TestStrategy { custom }),
+    )?;
+    paper.run0().await?;
+
+    assert_eq!(paper.rule_price_tick("BTCUSDT"), dec!(0.01));
+    assert_eq!(paper.rule_amount_min("BTCUSDT"), dec!(50));
+    // 自定义交易规则不随版本切换
+    assert_eq!(paper.rule_price_tick("ETHUSDT"), dec!(0.5));
+    assert_eq!(paper.rule_amount_min("ETHUSDT"), dec!(20));
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::HistoryDir;
+use destiny_engine::prelude::*;
+
+/// 收到K线时的引擎时间、交易对、收盘价与标记价格
+#[derive(Debug, PartialEq)]
+struct KlineRecord {
+    time: DateTime<Utc>,
+    symbol: String,
+    close: Decimal,
+    mark: Decimal,
+}
+
+#[derive(Default)]
+struct TestStrategy {
+    symbols: Vec<String>,
+    klines: Mutex<Vec<KlineRecord>>,
+    hours: Mutex<Vec<DateTime<Utc>>>,
+    minutes: Mutex<usize>,
+}
+
+#[async_trait]
+impl Strategy for TestStrategy {
+    async fn on_init(&self, engine: Arc<dyn Engine>) -> Result<()> {
+        for symbol in self.symbols.iter() {
+            engine.symbol_init(symbol)?;
+        }
+        Ok(())
+    }
+    async fn on_kline(&self, engine: Arc<dyn Engine>, kline: Kline) -> Result<()> {
+        self.klines.lock().push(KlineRecord {
+            time: engine.time(),
+            mark: engine.price_mark(&kline.symbol),
+            symbol: kline.symbol,
+            close: kline.close,
+        });
+        Ok(())
+    }
+    async fn on_hourly(&self, engine: Arc<dyn Engine>) -> Result<()> {
+        self.hours.lock().push(engine.time());
+        Ok(())
+    }
+    async fn on_minutely(&self, _engine: Arc<dyn Engine>) -> Result<()> {
+        *self.minutes.lock() += 1;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_scheduler() -> Result<()> {
+    let history = HistoryDir::new("scheduler");
+    let listed = "LISTEDUSDT".to_string();
+    let unlisted = "UNLISTEDUSDT".to_string();
+    history.klines_write(
+        &listed,
+        "klines",
+        &[
+            ("202401010000", "100"),
+            ("202401010001", "101"),
+            ("202401010002", "102"),
+        ],
+    )?;
+    history.klines_write(&listed, "markPriceKlines", &[("202401010000", "99")])?;
+    // 2点上架, 最后一根K线的开盘时间为结束时间
+    history.klines_write(
+        &unlisted,
+        "klines",
+        &[
+            ("202401010200", "50"),
+            ("202401010201", "51"),
+            ("202401010300", "52"),
+        ],
+    )?;
+
+    let strategy = Arc::new(TestStrategy {
+        symbols: vec![listed.clone(), unlisted.clone()],
+        ..Default::default()
+    });
+    let begin = "202401010000".to_date()?;
+    let report = Backtest::run(
+        BacktestConfigBuilder::default()
+            .begin(begin)
+            .end(begin + Duration::hours(3))
+            .timer_minutely(false)
+            .history_path(history.path().to_owned())
+            .build()?,
+        strategy.clone(),
+    )
+    .await?;
+
+    // K线在收盘时间通知, 同一时间的标记价格先于K线更新, 上架前不产生事件
+    let record = |minutes: i64, symbol: &str, close: Decimal, mark: Decimal| KlineRecord {
+        time: begin + Duration::minutes(minutes),
+        symbol: symbol.to_string(),
+        close,
+        mark,
+    };
+    assert_eq!(
+        *strategy.klines.lock(),
+        vec![
+            record(1, &listed, dec!(100), dec!(99)),
+            record(2, &listed, dec!(101), dec!(99)),
+            record(3, &listed, dec!(102), dec!(99)),
+            record(121, &unlisted, dec!(50), Decimal::ZERO),
+            record(122, &unlisted, dec!(51), Decimal::ZERO),
+            record(181, &unlisted, dec!(52), Decimal::ZERO),
+        ]
+    );
+
+    // 关闭每分钟事件后只在整点执行定时事件
+    assert_eq!(
+        *strategy.hours.lock(),
+        (0..=3)
+            .map(|hours| begin + Duration::hours(hours))
+            .collect::<Vec<DateTime<Utc>>>()
+    );
+    assert_eq!(*strategy.minutes.lock(), 0);
+
+    // 跳过的分钟仍按采样间隔记录权益, 最后一根K线收盘时补充采样
+    assert_eq!(
+        report
+            .equity_curve
+            .iter()
+            .map(|point| point.time)
+            .collect::<Vec<DateTime<Utc>>>(),
+        (0..=3)
+            .map(|hours| begin + Duration::hours(hours))
+            .chain([begin + Duration::minutes(181)])
+            .collect::<Vec<DateTime<Utc>>>()
+    );
+    Ok(())
+}
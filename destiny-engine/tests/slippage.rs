@@ -0,0 +1,97 @@
+mod common;
+
+use common::{backtest_config, step, HistoryDir, TestStrategy};
+use destiny_engine::prelude::*;
+
+/// 单位滑点固定为0.5
+struct ConstSlippage;
+
+impl Slippage for ConstSlippage {
+    fn slippage(
+        &self,
+        _rate: Decimal,
+        _is_buy: bool,
+        _price: Decimal,
+        _size: Decimal,
+        _kline: &Kline,
+    ) -> Decimal {
+        dec!(0.5)
+    }
+}
+
+/// 第1根K线收盘后市价开仓, 在第2根K线(开100, 高110, 低90, 收105, 成交量10, 主动买入5)以收盘价成交
+async fn market_fill(model: SlippageModel, side: TradeSide) -> Result<Trade> {
+    let history = HistoryDir::new("slippage");
+    history.market_write(
+        "ETHUSDT",
+        &[
+            ("202401010000", ["100", "100", "100", "100"]),
+            ("202401010001", ["100", "110", "90", "105"]),
+        ],
+    )?;
+    let strategy = TestStrategy::new(vec![step(move |engine| async move {
+        match side {
+            TradeSide::Long => engine.long_market_open("ETHUSDT", dec!(1)).await?,
+            TradeSide::Short => engine.short_market_open("ETHUSDT", dec!(1)).await?,
+        };
+        Ok(())
+    })]);
+    Backtest::run(
+        backtest_config(&history)
+            .slippage_rate(dec!(0.01))
+            .slippage_model(model)
+            .build()?,
+        strategy.clone(),
+    )
+    .await?;
+
+    let engine = strategy.engine();
+    let trades = engine.trades(
+        "ETHUSDT",
+        DateTime::<Utc>::MIN_UTC,
+        DateTime::<Utc>::MAX_UTC,
+    );
+    assert_eq!(trades.len(), 1);
+    assert!(!trades[0].is_maker);
+    Ok(trades[0].clone())
+}
+
+#[tokio::test]
+async fn test_slippage_fixed() -> Result<()> {
+    // 成交价格 * 滑点率, 买入加价, 卖出减价
+    let trade = market_fill(SlippageModel::Fixed, TradeSide::Long).await?;
+    assert_eq!(trade.price, dec!(106.05));
+    let trade = market_fill(SlippageModel::Fixed, TradeSide::Short).await?;
+    assert_eq!(trade.price, dec!(103.95));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_slippage_volume() -> Result<()> {
+    // 成交数量占同方向主动成交量的1/5
+    let trade = market_fill(SlippageModel::Volume, TradeSide::Long).await?;
+    assert_eq!(trade.price, dec!(105.21));
+    let trade = market_fill(SlippageModel::Volume, TradeSide::Short).await?;
+    assert_eq!(trade.price, dec!(104.79));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_slippage_spread() -> Result<()> {
+    // 半个价差: (110 - 90) * 0.01 / 2
+    let trade = market_fill(SlippageModel::Spread, TradeSide::Long).await?;
+    assert_eq!(trade.price, dec!(105.1));
+    let trade = market_fill(SlippageModel::Spread, TradeSide::Short).await?;
+    assert_eq!(trade.price, dec!(104.9));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_slippage_custom() -> Result<()> {
+    let model = SlippageModel::Custom(Arc::new(ConstSlippage));
+    let trade = market_fill(model.clone(), TradeSide::Long).await?;
+    assert_eq!(trade.price, dec!(105.5));
+    let trade = market_fill(model, TradeSide::Short).await?;
+    assert_eq!(trade.price, dec!(104.5));
+    Ok(())
+}
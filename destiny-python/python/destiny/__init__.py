@@ -1,6 +1,10 @@
 from .destiny import (
     TradeType,
     TradeSide,
+    PositionMode,
+    MarginMode,
+    TimeInForce,
+    WorkingType,
     OrderStatus,
     init_log,
     free_log,
@@ -10,13 +14,36 @@ from .destiny import (
     warn,
     error,
     print,
+    KlineInterval,
     Kline,
+    KlineColumns,
+    Ema,
+    Rsi,
+    Atr,
+    Bollinger,
+    BollingerValue,
+    Macd,
+    MacdValue,
+    Vwap,
     Order,
+    OrderRequest,
+    OrderGroupType,
+    OrderGroup,
     Position,
+    PositionReason,
+    PositionEvent,
+    Funding,
+    Liquidation,
+    Trade,
+    EquityPoint,
+    BacktestReport,
+    AccountSnapshot,
     API,
     download_history_data,
 )
 from .backtest import run_backtest, BacktestStrategy
+from .paper import run_paper
+from .live import run_live
 from .strategy import Strategy
 from decimal import Decimal
 from datetime import datetime
@@ -2,6 +2,7 @@ from .destiny import *
 from .strategy import Strategy
 from .backtest_strategy import BacktestStrategy
 from decimal import Decimal
+from typing import Literal, Optional
 import setproctitle
 
 
@@ -12,7 +13,17 @@ def run_backtest(
     cash: Decimal = Decimal("1000"),
     fee_rate_taker: Decimal = Decimal("0.0005"),
     fee_rate_maker: Decimal = Decimal("0.0005"),
-    slippage_rate: Decimal = Decimal("0.01"),
+    fee_rate_liquidation: Decimal = Decimal("0.0125"),
+    slippage_rate: Decimal = Decimal("0"),
+    slippage_model: Literal["fixed", "volume", "spread"] = "fixed",
+    match_mode: Literal["close", "ohlc"] = "close",
+    kline_path: Literal["ohlc", "olhc", "nearest"] = "nearest",
+    participation_rate: Optional[Decimal] = None,
+    ledger_path: Optional[str] = None,
+    rule_path: Optional[str] = None,
+    kline_history: int = 1000,
+    timer_minutely: bool = True,
+    history_path: Optional[str] = None,
 ) -> Strategy:
     """
     运行回测
@@ -21,7 +32,17 @@ def run_backtest(
     [`cash`] : 初始资金
     [`fee_rate_taker`] : 吃单手续费
     [`fee_rate_maker`] : 挂单手续费
-    [`slippage_rate`] : 滑点
+    [`fee_rate_liquidation`] : 强平手续费
+    [`slippage_rate`] : 滑点, 默认为0即不计滑点
+    [`slippage_model`] : 滑点模型
+    [`match_mode`] : 撮合模式. close: 收盘价撮合, ohlc: K线路径撮合
+    [`kline_path`] : K线内价格路径假设. ohlc: 开高低收, olhc: 开低高收, nearest: 较近的极值先到达
+    [`participation_rate`] : 成交量参与率. 每根K线成交数量不超过K线成交量 * 参与率, 为空时订单一次全部成交
+    [`ledger_path`] : 账本导出路径. 回测结束后以JSON格式写入订单、成交、资金费用与强平记录
+    [`rule_path`] : 交易规则快照路径, 文件或按日期命名的版本目录. 为空时使用缓存目录下的快照, 快照或其中的交易对不存在时不限制
+    [`kline_history`] : 每个交易对每个K线周期缓存的已收盘K线数量
+    [`timer_minutely`] : 是否执行每分钟事件. 关闭后定时事件只在整点调度, 跳过没有行情的分钟
+    [`history_path`] : 历史数据目录, 为空时使用下载历史数据的缓存目录
     [`on_init`] : 初始化事件
     [`on_start`] : 开始事件
     [`on_stop`] : 停止事件
@@ -31,17 +52,30 @@ def run_backtest(
     [`on_kline`] : K线事件
     [`on_order`] : 订单事件
     [`on_position`] : 持仓事件
+    [`on_funding`] : 资金费用事件
+    [`on_liquidation`] : 强平事件
+    返回的策略对象的 `report` 属性为回测报告
     """
 
     setproctitle.setproctitle(f"wealth-discovery-destiny-backtest")
     backtest_strategy = BacktestStrategy(strategy)
-    run_backtest__(
+    backtest_strategy.report = run_backtest__(
         begin,
         end,
         cash,
         fee_rate_taker,
         fee_rate_maker,
+        fee_rate_liquidation,
         slippage_rate,
+        slippage_model,
+        match_mode,
+        kline_path,
+        participation_rate,
+        ledger_path,
+        rule_path,
+        kline_history,
+        timer_minutely,
+        history_path,
         backtest_strategy.on_init,
         backtest_strategy.on_start,
         backtest_strategy.on_stop,
@@ -51,5 +85,7 @@ def run_backtest(
         backtest_strategy.on_kline,
         backtest_strategy.on_order,
         backtest_strategy.on_position,
+        backtest_strategy.on_funding,
+        backtest_strategy.on_liquidation,
     )
     return backtest_strategy
@@ -12,6 +12,8 @@ class BacktestStrategy(Strategy):
     daily_cash: np.ndarray
     daily_pnl: np.ndarray
     daily_seq: int
+    report: BacktestReport
+    """回测报告, 回测结束后可用"""
 
     def __init__(self, callback: Strategy):
         self.callback = callback
@@ -48,5 +50,11 @@ class BacktestStrategy(Strategy):
     def on_order(self, api: API, order: Order):
         self.callback.on_order(api, order)
 
-    def on_position(self, api: API, position: Position):
-        self.callback.on_position(api, position)
+    def on_position(self, api: API, event: PositionEvent):
+        self.callback.on_position(api, event)
+
+    def on_funding(self, api: API, funding: Funding):
+        self.callback.on_funding(api, funding)
+
+    def on_liquidation(self, api: API, liquidation: Liquidation):
+        self.callback.on_liquidation(api, liquidation)
@@ -1,4 +1,4 @@
-from typing import List, Literal, Optional, Callable, Tuple
+from typing import List, Literal, Optional, Callable, Tuple, Union
 from datetime import datetime
 from decimal import Decimal
 from enum import Enum, auto
@@ -12,6 +12,16 @@ class TradeType(Enum):
     """限价"""
     Market = auto()
     """市价"""
+    StopMarket = auto()
+    """止损市价"""
+    StopLimit = auto()
+    """止损限价"""
+    TakeProfitMarket = auto()
+    """止盈市价"""
+    TakeProfitLimit = auto()
+    """止盈限价"""
+    TrailingStopMarket = auto()
+    """跟踪止损"""
 
 class TradeSide(Enum):
     """
@@ -23,6 +33,50 @@ class TradeSide(Enum):
     Short = auto()
     """做空"""
 
+class PositionMode(Enum):
+    """
+    持仓模式
+    """
+
+    Hedge = auto()
+    """双向持仓"""
+    OneWay = auto()
+    """单向持仓"""
+
+class MarginMode(Enum):
+    """
+    保证金模式
+    """
+
+    Cross = auto()
+    """全仓"""
+    Isolated = auto()
+    """逐仓"""
+
+class TimeInForce(Enum):
+    """
+    订单有效方式
+    """
+
+    Gtc = auto()
+    """成交为止"""
+    Ioc = auto()
+    """立即成交并取消剩余"""
+    Fok = auto()
+    """全部成交或立即取消"""
+    Gtx = auto()
+    """只做挂单, 仅限价单"""
+
+class WorkingType(Enum):
+    """
+    条件单触发价格类型
+    """
+
+    Last = auto()
+    """最新价格"""
+    Mark = auto()
+    """标记价格"""
+
 class OrderStatus(Enum):
     """
     订单状态
@@ -91,6 +145,42 @@ def print(*args):
     打印日志, 默认`debug`级别
     """
 
+class KlineInterval(Enum):
+    """
+    K线周期
+    """
+
+    M1 = auto()
+    """1分钟"""
+    M3 = auto()
+    """3分钟"""
+    M5 = auto()
+    """5分钟"""
+    M15 = auto()
+    """15分钟"""
+    M30 = auto()
+    """30分钟"""
+    H1 = auto()
+    """1小时"""
+    H2 = auto()
+    """2小时"""
+    H4 = auto()
+    """4小时"""
+    H6 = auto()
+    """6小时"""
+    H8 = auto()
+    """8小时"""
+    H12 = auto()
+    """12小时"""
+    D1 = auto()
+    """1天"""
+    D3 = auto()
+    """3天"""
+    W1 = auto()
+    """1周"""
+    Mo1 = auto()
+    """1月"""
+
 class Kline:
     """
     K线
@@ -118,6 +208,221 @@ class Kline:
     """买方成交额"""
     trades: int
     """交易笔数"""
+    interval: KlineInterval
+    """K线周期"""
+
+class KlineColumns:
+    """
+    按列存储的K线, 按开盘时间升序, 价格与数量为浮点数
+    """
+
+    open_time: List[datetime]
+    """开盘时间"""
+    open: List[float]
+    """开盘价"""
+    high: List[float]
+    """最高价"""
+    low: List[float]
+    """最低价"""
+    close: List[float]
+    """收盘价"""
+    size: List[float]
+    """成交量"""
+    cash: List[float]
+    """成交额"""
+    buy_size: List[float]
+    """买方成交量"""
+    buy_cash: List[float]
+    """买方成交额"""
+    trades: List[int]
+    """交易笔数"""
+
+    def __len__(self) -> int: ...
+
+class Ema:
+    """
+    指数移动平均(EMA)
+    以前`period`个值的简单平均作为初始值, 之后按`2 / (period + 1)`平滑
+    """
+
+    def __init__(self, period: int):
+        """
+        [`period`] : 周期
+        """
+
+    def update(self, value: Decimal) -> Optional[Decimal]:
+        """
+        加入一个值, 返回最新的指标值
+        """
+
+    def period(self) -> int:
+        """
+        周期
+        """
+
+    def value(self) -> Optional[Decimal]:
+        """
+        指标值, 加入的值不足`period`个时为空
+        """
+
+class Rsi:
+    """
+    相对强弱指数(RSI)
+    采用Wilder平滑, 以前`period`个涨跌幅的简单平均作为初始值. 区间内无涨跌时为50
+    """
+
+    def __init__(self, period: int = 14):
+        """
+        [`period`] : 周期
+        """
+
+    def update(self, close: Decimal) -> Optional[Decimal]:
+        """
+        加入一个收盘价, 返回最新的指标值
+        """
+
+    def period(self) -> int:
+        """
+        周期
+        """
+
+    def value(self) -> Optional[Decimal]:
+        """
+        指标值(0~100), 加入的收盘价不足`period + 1`个时为空
+        """
+
+class Atr:
+    """
+    平均真实波幅(ATR)
+    真实波幅需要前一根K线的收盘价, 首根K线不计入. 采用Wilder平滑, 以前`period`个真实波幅的简单平均作为初始值
+    """
+
+    def __init__(self, period: int = 14):
+        """
+        [`period`] : 周期
+        """
+
+    def update(self, kline: Kline) -> Optional[Decimal]:
+        """
+        加入一根K线, 返回最新的指标值
+        """
+
+    def period(self) -> int:
+        """
+        周期
+        """
+
+    def value(self) -> Optional[Decimal]:
+        """
+        指标值, 加入的K线不足`period + 1`根时为空
+        """
+
+class BollingerValue:
+    """
+    布林带指标值
+    """
+
+    upper: Decimal
+    """上轨"""
+    middle: Decimal
+    """中轨"""
+    lower: Decimal
+    """下轨"""
+
+class Bollinger:
+    """
+    布林带(Bollinger Bands)
+    中轨为`period`个值的简单平均, 上下轨为中轨加减`multiplier`倍总体标准差
+    """
+
+    def __init__(self, period: int = 20, multiplier: Decimal = Decimal(2)):
+        """
+        [`period`] : 周期
+        [`multiplier`] : 标准差倍数
+        """
+
+    def update(self, value: Decimal) -> Optional[BollingerValue]:
+        """
+        加入一个值, 返回最新的指标值
+        """
+
+    def period(self) -> int:
+        """
+        周期
+        """
+
+    def multiplier(self) -> Decimal:
+        """
+        标准差倍数
+        """
+
+    def value(self) -> Optional[BollingerValue]:
+        """
+        指标值, 加入的值不足`period`个时为空
+        """
+
+class MacdValue:
+    """
+    MACD指标值
+    """
+
+    macd: Decimal
+    """快线与慢线EMA之差(DIF)"""
+    signal: Decimal
+    """macd的EMA(DEA)"""
+    histogram: Decimal
+    """柱状图: macd - signal"""
+
+class Macd:
+    """
+    指数平滑异同移动平均(MACD)
+    """
+
+    def __init__(self, fast: int = 12, slow: int = 26, signal: int = 9):
+        """
+        [`fast`] : 快线周期
+        [`slow`] : 慢线周期, 需大于快线周期
+        [`signal`] : 信号线周期
+        """
+
+    def update(self, value: Decimal) -> Optional[MacdValue]:
+        """
+        加入一个值, 返回最新的指标值
+        """
+
+    def value(self) -> Optional[MacdValue]:
+        """
+        指标值, 加入的值不足`slow + signal - 1`个时为空
+        """
+
+class Vwap:
+    """
+    成交量加权平均价(VWAP)
+    以成交额 / 成交量计算, 按`anchor`周期重置, 为空时从首根K线起累计
+    """
+
+    def __init__(self, anchor: Optional[KlineInterval] = KlineInterval.D1):
+        """
+        [`anchor`] : 重置周期
+        """
+
+    def update(self, kline: Kline) -> Optional[Decimal]:
+        """
+        加入一根K线, 返回最新的指标值
+        """
+
+    def anchor(self) -> Optional[KlineInterval]:
+        """
+        重置周期
+        """
+
+    def value(self) -> Optional[Decimal]:
+        """
+        指标值, 当前周期内无成交时为空
+        """
+
+Indicator = Union[Ema, Rsi, Atr, Bollinger, Macd, Vwap]
+"""可注册到引擎的指标, 引擎在每根已收盘K线通知前更新指标, 基于单个值的指标使用收盘价"""
 
 class Order:
     """
@@ -126,6 +431,10 @@ class Order:
 
     id: str
     """ID"""
+    client_id: str
+    """客户端订单ID, 与ID相同"""
+    group_id: Optional[str]
+    """所属订单组ID"""
     symbol: str
     """交易对"""
     type: TradeType
@@ -134,6 +443,16 @@ class Order:
     """交易方向"""
     reduce_only: bool
     """是否只减仓"""
+    close_position: bool
+    """是否全部平仓"""
+    time_in_force: TimeInForce
+    """订单有效方式"""
+    stop_price: Decimal
+    """触发价格, 仅条件单. 跟踪止损单为随行情移动后的当前触发价格"""
+    working_type: WorkingType
+    """触发价格类型, 仅条件单"""
+    callback_rate: Decimal
+    """跟踪止损回调比例, 仅跟踪止损单"""
     status: OrderStatus
     """订单状态"""
     price: Decimal
@@ -146,6 +465,88 @@ class Order:
     """成交数量"""
     deal_fee: Decimal
     """成交手续费"""
+    deal_slippage: Decimal
+    """成交滑点成本"""
+    deal_pnl: Decimal
+    """成交已实现盈亏, 不含手续费"""
+    create_time: datetime
+    """创建时间"""
+    queue_time: datetime
+    """排队时间: 创建时间, 修改价格或增加数量后为修改时间"""
+
+class OrderRequest:
+    """
+    下单请求
+    """
+
+    symbol: str
+    """交易对"""
+    type: TradeType
+    """交易类型"""
+    side: TradeSide
+    """交易方向"""
+    reduce_only: bool
+    """是否只减仓"""
+    close_position: bool
+    """是否全部平仓"""
+    price: Decimal
+    """价格, 市价单忽略"""
+    size: Decimal
+    """数量"""
+    time_in_force: TimeInForce
+    """订单有效方式"""
+    stop_price: Decimal
+    """触发价格, 仅止损/止盈单"""
+    working_type: WorkingType
+    """触发价格类型, 仅条件单"""
+    callback_rate: Decimal
+    """跟踪止损回调比例, 仅跟踪止损单"""
+    client_id: Optional[str]
+    """客户端订单ID, 同时作为订单ID, 未完成订单中不可重复. 为空时自动生成"""
+
+    def __init__(
+        self,
+        symbol: str,
+        type: TradeType,
+        side: TradeSide,
+        size: Decimal,
+        price: Decimal = Decimal(0),
+        reduce_only: bool = False,
+        close_position: bool = False,
+        time_in_force: TimeInForce = TimeInForce.Gtc,
+        client_id: Optional[str] = None,
+        stop_price: Decimal = Decimal(0),
+        working_type: WorkingType = WorkingType.Last,
+        callback_rate: Decimal = Decimal(0),
+    ): ...
+
+class OrderGroupType(Enum):
+    """
+    订单组类型
+    """
+
+    Oco = auto()
+    """二选一"""
+    Bracket = auto()
+    """括号订单"""
+
+class OrderGroup:
+    """
+    订单组
+    """
+
+    id: str
+    """ID"""
+    symbol: str
+    """交易对"""
+    type: OrderGroupType
+    """订单组类型"""
+    entry_id: Optional[str]
+    """入场订单ID, 仅括号订单"""
+    order_ids: List[str]
+    """互斥订单ID"""
+    orders_inactive: List[Order]
+    """未激活的止损/止盈订单"""
     create_time: datetime
     """创建时间"""
 
@@ -160,22 +561,117 @@ class Position:
     """持仓均价"""
     size: Decimal
     """持仓"""
+    margin_isolated: Decimal
+    """逐仓保证金, 仅在逐仓模式下生效"""
 
-class API:
-    def time(self) -> datetime:
-        """
-        获取当前时间
-        """
+class PositionReason(Enum):
+    """
+    持仓变动原因
+    """
 
-    def stop(self):
-        """
-        停止运行
-        """
+    Trade = auto()
+    """成交"""
+    Liquidation = auto()
+    """强平"""
+    Funding = auto()
+    """资金费用"""
 
-    def init_symbol(self, symbol: str):
+class PositionEvent:
+    """
+    持仓事件
+    """
+
+    symbol: str
+    """交易对"""
+    reason: PositionReason
+    """变动原因"""
+    before: Position
+    """变动前持仓"""
+    after: Position
+    """变动后持仓"""
+    margin_before: Decimal
+    """变动前持仓占用保证金, 逐仓模式下为交易对的逐仓保证金"""
+    margin_after: Decimal
+    """变动后持仓占用保证金"""
+    time: datetime
+    """变动时间"""
+
+class Funding:
+    """
+    资金费用
+    """
+
+    symbol: str
+    """交易对"""
+    side: TradeSide
+    """持仓方向"""
+    rate: Decimal
+    """资金费率"""
+    mark_price: Decimal
+    """标记价格"""
+    size: Decimal
+    """持仓数量"""
+    fee: Decimal
+    """资金费用, 正数为收入, 负数为支出"""
+    time: datetime
+    """结算时间"""
+
+class Trade:
+    """
+    成交记录
+    """
+
+    symbol: str
+    """交易对"""
+    order_id: str
+    """订单ID"""
+    side: TradeSide
+    """交易方向"""
+    reduce_only: bool
+    """只减仓"""
+    price: Decimal
+    """成交价格"""
+    size: Decimal
+    """成交数量"""
+    fee: Decimal
+    """手续费"""
+    is_maker: bool
+    """是否为挂单成交"""
+    pnl: Decimal
+    """已实现盈亏, 不含手续费"""
+    time: datetime
+    """成交时间"""
+
+class Liquidation:
+    """
+    强平
+    """
+
+    symbol: str
+    """交易对"""
+    side: TradeSide
+    """持仓方向"""
+    price: Decimal
+    """强平价格"""
+    size: Decimal
+    """强平数量"""
+    pnl: Decimal
+    """已实现盈亏"""
+    fee: Decimal
+    """强平手续费"""
+    time: datetime
+    """强平时间"""
+
+class AccountSnapshot:
+    """
+    账户快照
+    一次加锁复制的账户、持仓、订单与行情状态, 读取任意数量的字段均保持一致
+    创建快照时完整复制账户, 开销随交易对与挂单数量增长, 应在需要一致读取多个字段时使用
+    """
+
+    def time(self) -> datetime:
         """
-        初始化交易对
-        [`symbol`] : 交易对
+        获取快照时间
         """
 
     def order(self, symbol: str, id: str) -> Optional[Order]:
@@ -227,6 +723,19 @@ class API:
         [`symbol`] : 交易对
         """
 
+    def order_group(self, symbol: str, id: str) -> Optional[OrderGroup]:
+        """
+        获取订单组, 组内订单全部完成后不存在
+        [`symbol`] : 交易对
+        [`id`] : 订单组ID
+        """
+
+    def order_groups(self, symbol: str) -> List[OrderGroup]:
+        """
+        获取订单组列表
+        [`symbol`] : 交易对
+        """
+
     def leverage(self, symbol: str) -> int:
         """
         获取杠杆倍率
@@ -347,6 +856,31 @@ class API:
         [`symbol`] : 交易对
         """
 
+    def liquidation_price(self, symbol: str, side: TradeSide) -> Decimal:
+        """
+        获取强平价格
+        [`symbol`] : 交易对
+        [`side`] : 持仓方向
+        """
+
+    def position_mode(self, symbol: str) -> PositionMode:
+        """
+        获取持仓模式
+        [`symbol`] : 交易对
+        """
+
+    def margin_mode(self, symbol: str) -> MarginMode:
+        """
+        获取保证金模式
+        [`symbol`] : 交易对
+        """
+
+    def margin_isolated(self, symbol: str) -> Decimal:
+        """
+        获取逐仓保证金
+        [`symbol`] : 交易对
+        """
+
     def price_mark(self, symbol: str) -> Decimal:
         """
         获取标记价格
@@ -425,26 +959,457 @@ class API:
         [`symbol`] : 交易对
         """
 
-    def long_market_open(self, symbol: str, size: Decimal) -> str:
+class API:
+    def time(self) -> datetime:
         """
-        做多市价开仓
-        [`symbol`] : 交易对
-        [`size`] : 数量
+        获取当前时间
         """
 
-    def long_limit_open(self, symbol: str, size: Decimal, price: Decimal) -> str:
+    def stop(self):
         """
-        做多限价开仓
-        [`symbol`] : 交易对
-        [`size`] : 数量
-        [`price`] : 价格
+        停止运行
         """
 
-    def long_market_close(self, symbol: str, size: Decimal) -> str:
+    def init_symbol(self, symbol: str):
         """
-        做多市价平仓
+        初始化交易对
         [`symbol`] : 交易对
-        [`size`] : 数量
+        """
+
+    def init_symbol_with_rule(
+        self,
+        symbol: str,
+        price_tick: Decimal,
+        size_tick: Decimal,
+        price_min: Optional[Decimal] = None,
+        price_max: Optional[Decimal] = None,
+        size_min: Optional[Decimal] = None,
+        size_max: Optional[Decimal] = None,
+        amount_min: Optional[Decimal] = None,
+        order_max: Optional[int] = None,
+    ):
+        """
+        以自定义交易规则初始化交易对, 不随交易规则快照切换. 币安实盘不支持
+        [`symbol`] : 交易对
+        [`price_tick`] : 价格步长
+        [`size_tick`] : 数量步长
+        [`price_min`] : 最小价格, 为空时不限制
+        [`price_max`] : 最大价格, 为空时不限制
+        [`size_min`] : 最小数量, 为空时不限制
+        [`size_max`] : 最大数量, 为空时不限制
+        [`amount_min`] : 最小下单金额, 为空时不限制
+        [`order_max`] : 最大订单数量, 为空时为200
+        """
+
+    def kline_subscribe(self, symbol: str, intervals: List[KlineInterval]):
+        """
+        订阅交易对的K线周期, 替换已有订阅, 需在初始化交易对后调用. 默认仅订阅1分钟K线
+        各周期K线收盘后通过on_kline通知, 同一时间按周期从小到大通知
+        [`symbol`] : 交易对
+        [`intervals`] : K线周期列表
+        """
+
+    def indicator_register(
+        self, symbol: str, interval: KlineInterval, name: str, indicator: Indicator
+    ):
+        """
+        注册指标, 在该周期每根已收盘K线通知前更新
+        [`symbol`] : 交易对
+        [`interval`] : K线周期, 需已订阅
+        [`name`] : 指标名称, 同一交易对同一周期下不能重复
+        [`indicator`] : 指标
+        """
+
+    def indicator(
+        self, symbol: str, interval: KlineInterval, name: str
+    ) -> Optional[Indicator]:
+        """
+        获取已注册指标的当前状态
+        [`symbol`] : 交易对
+        [`interval`] : K线周期
+        [`name`] : 指标名称
+        """
+
+    def order(self, symbol: str, id: str) -> Optional[Order]:
+        """
+        获取订单
+        [`symbol`] : 交易对
+        [`id`] : 订单ID
+        """
+
+    def orders(self, symbol: str) -> List[Order]:
+        """
+        获取订单
+        [`symbol`] : 交易对
+        """
+
+    def orders_long(self, symbol: str) -> List[Order]:
+        """
+        获取做多订单
+        [`symbol`] : 交易对
+        """
+
+    def orders_long_open(self, symbol: str) -> List[Order]:
+        """
+        获取做多开仓订单
+        [`symbol`] : 交易对
+        """
+
+    def orders_long_close(self, symbol: str) -> List[Order]:
+        """
+        获取做多平仓订单
+        [`symbol`] : 交易对
+        """
+
+    def orders_short(self, symbol: str) -> List[Order]:
+        """
+        获取做空订单
+        [`symbol`] : 交易对
+        """
+
+    def orders_short_open(self, symbol: str) -> List[Order]:
+        """
+        获取做空开仓订单
+        [`symbol`] : 交易对
+        """
+
+    def orders_short_close(self, symbol: str) -> List[Order]:
+        """
+        获取做空平仓订单
+        [`symbol`] : 交易对
+        """
+
+    def leverage(self, symbol: str) -> int:
+        """
+        获取杠杆倍率
+        [`symbol`] : 交易对
+        """
+
+    def cash(self) -> Decimal:
+        """
+        获取资金
+        """
+
+    def cash_available(self) -> Decimal:
+        """
+        获取可用资金
+        """
+
+    def cash_frozen(self) -> Decimal:
+        """
+        获取冻结资金
+        """
+
+    def margin(self) -> Decimal:
+        """
+        获取保证金
+        """
+
+    def pnl(self) -> Decimal:
+        """
+        获取持仓盈亏
+        """
+
+    def long_price(self, symbol: str) -> Decimal:
+        """
+        获取做多均价
+        [`symbol`] : 交易对
+        """
+
+    def long_size(self, symbol: str) -> Decimal:
+        """
+        获取做多持仓
+        [`symbol`] : 交易对
+        """
+
+    def long_size_available(self, symbol: str) -> Decimal:
+        """
+        获取做多可用持仓
+        [`symbol`] : 交易对
+        """
+
+    def long_size_frozen(self, symbol: str) -> Decimal:
+        """
+        获取做多冻结持仓
+        [`symbol`] : 交易对
+        """
+
+    def long_margin(self, symbol: str) -> Decimal:
+        """
+        获取做多保证金
+        [`symbol`] : 交易对
+        """
+
+    def long_pnl(self, symbol: str) -> Decimal:
+        """
+        获取做多盈亏
+        [`symbol`] : 交易对
+        """
+
+    def short_price(self, symbol: str) -> Decimal:
+        """
+        获取做空均价
+        [`symbol`] : 交易对
+        """
+
+    def short_size(self, symbol: str) -> Decimal:
+        """
+        获取做空持仓
+        [`symbol`] : 交易对
+        """
+
+    def short_size_available(self, symbol: str) -> Decimal:
+        """
+        获取做空可用持仓
+        [`symbol`] : 交易对
+        """
+
+    def short_size_frozen(self, symbol: str) -> Decimal:
+        """
+        获取做空冻结持仓
+        [`symbol`] : 交易对
+        """
+
+    def short_margin(self, symbol: str) -> Decimal:
+        """
+        获取做空保证金
+        [`symbol`] : 交易对
+        """
+
+    def short_pnl(self, symbol: str) -> Decimal:
+        """
+        获取做空盈亏
+        [`symbol`] : 交易对
+        """
+
+    def symbols(self) -> List[str]:
+        """
+        获取交易对列表
+        """
+
+    def symbol_pnl(self, symbol: str) -> Decimal:
+        """
+        获取交易对盈亏
+        [`symbol`] : 交易对
+        """
+
+    def symbol_margin(self, symbol: str) -> Decimal:
+        """
+        获取交易对保证金
+        [`symbol`] : 交易对
+        """
+
+    def fundings(self, symbol: str) -> List[Funding]:
+        """
+        获取资金费用记录
+        [`symbol`] : 交易对
+        """
+
+    def orders_history(self, symbol: str, begin: datetime, end: datetime) -> List[Order]:
+        """
+        获取已完成订单(已成交或已取消)
+        [`symbol`] : 交易对
+        [`begin`] : 开始时间(包含), 按订单创建时间筛选
+        [`end`] : 结束时间(不包含)
+        """
+
+    def trades(self, symbol: str, begin: datetime, end: datetime) -> List[Trade]:
+        """
+        获取成交记录
+        [`symbol`] : 交易对
+        [`begin`] : 开始时间(包含)
+        [`end`] : 结束时间(不包含)
+        """
+
+    def funding_fee(self, symbol: str) -> Decimal:
+        """
+        获取累计资金费用
+        [`symbol`] : 交易对
+        """
+
+    def liquidation_price(self, symbol: str, side: TradeSide) -> Decimal:
+        """
+        获取强平价格
+        [`symbol`] : 交易对
+        [`side`] : 持仓方向
+        """
+
+    def position_mode(self, symbol: str) -> PositionMode:
+        """
+        获取持仓模式
+        [`symbol`] : 交易对
+        """
+
+    def margin_mode(self, symbol: str) -> MarginMode:
+        """
+        获取保证金模式
+        [`symbol`] : 交易对
+        """
+
+    def snapshot(self) -> AccountSnapshot:
+        """
+        一次加锁获取账户快照, 读取多个字段时保持一致
+        快照完整复制账户, 仅读取单个字段时直接使用对应方法
+        """
+
+    def margin_isolated(self, symbol: str) -> Decimal:
+        """
+        获取逐仓保证金
+        [`symbol`] : 交易对
+        """
+
+    def order_group(self, symbol: str, id: str) -> Optional[OrderGroup]:
+        """
+        获取订单组, 组内订单全部完成后不存在
+        [`symbol`] : 交易对
+        [`id`] : 订单组ID
+        """
+
+    def order_groups(self, symbol: str) -> List[OrderGroup]:
+        """
+        获取订单组列表
+        [`symbol`] : 交易对
+        """
+
+    def price_mark(self, symbol: str) -> Decimal:
+        """
+        获取标记价格
+        [`symbol`] : 交易对
+        """
+
+    def price_last(self, symbol: str) -> Decimal:
+        """
+        获取最新价格
+        [`symbol`] : 交易对
+        """
+
+    def price_index(self, symbol: str) -> Decimal:
+        """
+        获取指数价格
+        [`symbol`] : 交易对
+        """
+
+    def price_settlement(self, symbol: str) -> Decimal:
+        """
+        获取结算价格
+        [`symbol`] : 交易对
+        """
+
+    def time_settlement(self, symbol: str) -> datetime:
+        """
+        获取下一次结算时间
+        [`symbol`] : 交易对
+        """
+
+    def rule_price_min(self, symbol: str) -> Decimal:
+        """
+        获取最小交易价格
+        [`symbol`] : 交易对
+        """
+
+    def rule_price_max(self, symbol: str) -> Decimal:
+        """
+        获取最大交易价格
+        [`symbol`] : 交易对
+        """
+
+    def rule_price_tick(self, symbol: str) -> Decimal:
+        """
+        获取交易价格步长
+        [`symbol`] : 交易对
+        """
+
+    def rule_size_min(self, symbol: str) -> Decimal:
+        """
+        获取最小交易数量
+        [`symbol`] : 交易对
+        """
+
+    def rule_size_max(self, symbol: str) -> Decimal:
+        """
+        获取最大交易数量
+        [`symbol`] : 交易对
+        """
+
+    def rule_size_tick(self, symbol: str) -> Decimal:
+        """
+        获取交易数量步长
+        [`symbol`] : 交易对
+        """
+
+    def rule_amount_min(self, symbol: str) -> Decimal:
+        """
+        获取最小交易金额
+        [`symbol`] : 交易对
+        """
+
+    def rule_order_max(self, symbol: str) -> int:
+        """
+        获取最大订单数量
+        [`symbol`] : 交易对
+        """
+
+    def klines(
+        self, symbol: str, interval: KlineInterval, limit: int
+    ) -> KlineColumns:
+        """
+        获取最近的已收盘K线, 不包含未收盘的K线
+        [`symbol`] : 交易对
+        [`interval`] : K线周期, 需已订阅
+        [`limit`] : 数量, 不超过引擎的K线缓存容量
+        """
+
+    def order_place(
+        self,
+        symbol: str,
+        type: TradeType,
+        side: TradeSide,
+        size: Decimal,
+        price: Decimal = Decimal(0),
+        reduce_only: bool = False,
+        close_position: bool = False,
+        time_in_force: TimeInForce = TimeInForce.Gtc,
+        client_id: Optional[str] = None,
+        stop_price: Decimal = Decimal(0),
+        working_type: WorkingType = WorkingType.Last,
+        callback_rate: Decimal = Decimal(0),
+    ) -> str:
+        """
+        下单
+        [`symbol`] : 交易对
+        [`type`] : 交易类型
+        [`side`] : 交易方向
+        [`size`] : 数量, 全部平仓时忽略
+        [`price`] : 价格, 市价单忽略
+        [`reduce_only`] : 是否只减仓
+        [`close_position`] : 是否全部平仓, 按成交时的持仓数量平仓
+        [`time_in_force`] : 订单有效方式
+        [`client_id`] : 客户端订单ID, 同时作为订单ID, 不能与未完成订单重复
+        [`stop_price`] : 触发价格, 仅止损/止盈单
+        [`working_type`] : 触发价格类型, 仅条件单
+        [`callback_rate`] : 回调比例, 如0.01表示1%, 仅跟踪止损单
+        """
+
+    def long_market_open(self, symbol: str, size: Decimal) -> str:
+        """
+        做多市价开仓
+        [`symbol`] : 交易对
+        [`size`] : 数量
+        """
+
+    def long_limit_open(self, symbol: str, size: Decimal, price: Decimal) -> str:
+        """
+        做多限价开仓
+        [`symbol`] : 交易对
+        [`size`] : 数量
+        [`price`] : 价格
+        """
+
+    def long_market_close(self, symbol: str, size: Decimal) -> str:
+        """
+        做多市价平仓
+        [`symbol`] : 交易对
+        [`size`] : 数量
         """
 
     def long_limit_close(self, symbol: str, size: Decimal, price: Decimal) -> str:
@@ -487,18 +1452,57 @@ class API:
 
     def order_close(self, symbol: str, id: str):
         """
-        平仓订单
+        取消订单, 订单不存在或已完成时抛出异常
         [`symbol`] : 交易对
         [`id`] : 订单ID
         """
 
     def order_cancel_many(self, symbol: str, ids: List[str]):
         """
-        取消多个订单
+        取消多个订单, 任一订单不存在或已完成时抛出异常且不取消任何订单
         [`symbol`] : 交易对
         [`ids`] : 订单ID列表
         """
 
+    def order_amend(self, symbol: str, id: str, price: Decimal, size: Decimal):
+        """
+        修改限价订单的价格与数量, 订单ID不变, 校验规则与下单相同
+        仅减少数量时保留排队优先级; 修改价格或增加数量时重新排队
+        重新排队的订单在下一根K线开盘即可成交时按吃单成交, 否则仍为挂单
+        [`symbol`] : 交易对
+        [`id`] : 订单ID
+        [`price`] : 修改后的价格
+        [`size`] : 修改后的订单总数量, 必须大于已成交数量
+        """
+
+    def order_place_oco(self, requests: List[OrderRequest]) -> str:
+        """
+        下单一组二选一(OCO)订单, 返回订单组ID
+        组内任意订单成交(含部分成交)或完成后, 撤销其余订单
+        [`requests`] : 下单请求列表, 至少两个, 交易对相同
+        """
+
+    def order_place_bracket(
+        self,
+        entry: OrderRequest,
+        stop_loss: Optional[OrderRequest] = None,
+        take_profit: Optional[OrderRequest] = None,
+    ) -> str:
+        """
+        下单入场订单并附带止损/止盈订单, 返回订单组ID
+        止损/止盈订单与入场订单同向只减仓, 数量忽略, 入场订单成交后按成交数量激活, 二者互斥
+        [`entry`] : 入场订单
+        [`stop_loss`] : 止损订单
+        [`take_profit`] : 止盈订单
+        """
+
+    def order_group_cancel(self, symbol: str, id: str):
+        """
+        撤销订单组内全部未完成订单, 订单组不存在时抛出异常
+        [`symbol`] : 交易对
+        [`id`] : 订单组ID
+        """
+
     def leverage_set(self, symbol: str, leverage: int):
         """
         设置杠杆倍率
@@ -506,16 +1510,113 @@ class API:
         [`leverage`] : 杠杆倍率
         """
 
+    def position_mode_set(self, symbol: str, mode: PositionMode):
+        """
+        设置持仓模式, 存在持仓或挂单时不能切换
+        单向持仓模式下开仓单先抵消反向持仓, 超出部分开仓
+        [`symbol`] : 交易对
+        [`mode`] : 持仓模式
+        """
+
+    def margin_mode_set(self, symbol: str, mode: MarginMode):
+        """
+        设置保证金模式, 存在持仓或挂单时不能切换
+        [`symbol`] : 交易对
+        [`mode`] : 保证金模式
+        """
+
+    def margin_add(self, symbol: str, side: TradeSide, amount: Decimal):
+        """
+        追加逐仓保证金
+        [`symbol`] : 交易对
+        [`side`] : 持仓方向
+        [`amount`] : 数额
+        """
+
+    def margin_remove(self, symbol: str, side: TradeSide, amount: Decimal):
+        """
+        减少逐仓保证金
+        [`symbol`] : 交易对
+        [`side`] : 持仓方向
+        [`amount`] : 数额
+        """
+
 def download_history_data(metas: List[Tuple[str, str, str]]):
     """
     下载历史数据
     [`metas`] : 历史数据元组列表. Tuple(交易对, 开始时间, 结束时间)
     """
 
+class EquityPoint:
+    """
+    权益曲线采样点
+    """
+
+    time: datetime
+    """时间"""
+    cash: Decimal
+    """资金"""
+    pnl: Decimal
+    """未实现盈亏"""
+    equity: Decimal
+    """权益: 资金 + 未实现盈亏"""
+
+class BacktestReport:
+    """
+    回测报告
+    比率类指标无法计算时(如无波动、无亏损)为0
+    """
+
+    begin: datetime
+    """开始时间"""
+    end: datetime
+    """结束时间"""
+    equity_begin: Decimal
+    """初始权益"""
+    equity_end: Decimal
+    """结束权益"""
+    total_return: float
+    """总收益率"""
+    cagr: float
+    """年化收益率"""
+    max_drawdown: float
+    """最大回撤比例"""
+    max_drawdown_duration: int
+    """最大回撤持续时间(分钟)"""
+    sharpe: float
+    """夏普比率(年化, 无风险利率为0)"""
+    sortino: float
+    """索提诺比率(年化)"""
+    calmar: float
+    """卡玛比率: 年化收益率 / 最大回撤"""
+    trade_count: int
+    """平仓成交笔数"""
+    win_rate: float
+    """胜率"""
+    profit_factor: float
+    """盈亏比: 平仓盈利合计 / 平仓亏损合计"""
+    trade_avg: Decimal
+    """平仓成交的平均已实现盈亏, 不含手续费"""
+    exposure: float
+    """持仓时间占比"""
+    fee_total: Decimal
+    """手续费合计, 含强平手续费"""
+    funding_total: Decimal
+    """资金费用合计"""
+    equity_curve: List[EquityPoint]
+    """权益曲线"""
+
+    def to_json(self) -> str:
+        """
+        序列化为JSON
+        """
+
 BasicCallback = Callable[[API], None]
 KlineCallback = Callable[[API, Kline], None]
 OrderCallback = Callable[[API, Order], None]
-PositionCallback = Callable[[API, Position], None]
+PositionCallback = Callable[[API, PositionEvent], None]
+FundingCallback = Callable[[API, Funding], None]
+LiquidationCallback = Callable[[API, Liquidation], None]
 
 def run_backtest__(
     begin: str,
@@ -523,7 +1624,17 @@ def run_backtest__(
     cash: Decimal = Decimal("1000"),
     fee_rate_taker: Decimal = Decimal("0.0005"),
     fee_rate_maker: Decimal = Decimal("0.0005"),
-    slippage_rate: Decimal = Decimal("0.01"),
+    fee_rate_liquidation: Decimal = Decimal("0.0125"),
+    slippage_rate: Decimal = Decimal("0"),
+    slippage_model: Literal["fixed", "volume", "spread"] = "fixed",
+    match_mode: Literal["close", "ohlc"] = "close",
+    kline_path: Literal["ohlc", "olhc", "nearest"] = "nearest",
+    participation_rate: Optional[Decimal] = None,
+    ledger_path: Optional[str] = None,
+    rule_path: Optional[str] = None,
+    kline_history: int = 1000,
+    timer_minutely: bool = True,
+    history_path: Optional[str] = None,
     on_init: Optional[BasicCallback] = None,
     on_start: Optional[BasicCallback] = None,
     on_stop: Optional[BasicCallback] = None,
@@ -533,7 +1644,9 @@ def run_backtest__(
     on_kline: Optional[KlineCallback] = None,
     on_order: Optional[OrderCallback] = None,
     on_position: Optional[PositionCallback] = None,
-):
+    on_funding: Optional[FundingCallback] = None,
+    on_liquidation: Optional[LiquidationCallback] = None,
+) -> BacktestReport:
     """
     运行回测
     [`begin`] : 开始时间
@@ -541,7 +1654,117 @@ def run_backtest__(
     [`cash`] : 初始资金
     [`fee_rate_taker`] : 吃单手续费
     [`fee_rate_maker`] : 挂单手续费
-    [`slippage_rate`] : 滑点
+    [`fee_rate_liquidation`] : 强平手续费
+    [`slippage_rate`] : 滑点, 默认为0即不计滑点
+    [`slippage_model`] : 滑点模型
+    [`match_mode`] : 撮合模式. close: 收盘价撮合, ohlc: K线路径撮合
+    [`kline_path`] : K线内价格路径假设. ohlc: 开高低收, olhc: 开低高收, nearest: 较近的极值先到达
+    [`participation_rate`] : 成交量参与率. 每根K线成交数量不超过K线成交量 * 参与率, 为空时订单一次全部成交
+    [`ledger_path`] : 账本导出路径. 回测结束后以JSON格式写入订单、成交、资金费用与强平记录
+    [`rule_path`] : 交易规则快照路径, 文件或按日期命名的版本目录. 为空时使用缓存目录下的快照, 快照或其中的交易对不存在时不限制
+    [`kline_history`] : 每个交易对每个K线周期缓存的已收盘K线数量
+    [`timer_minutely`] : 是否执行每分钟事件. 关闭后定时事件只在整点调度, 跳过没有行情的分钟
+    [`history_path`] : 历史数据目录, 为空时使用下载历史数据的缓存目录
+    [`on_init`] : 初始化事件
+    [`on_start`] : 开始事件
+    [`on_stop`] : 停止事件
+    [`on_daily`] : 每日事件
+    [`on_hourly`] : 每小时事件
+    [`on_minutely`] : 每分钟事件
+    [`on_kline`] : K线事件
+    [`on_order`] : 订单事件
+    [`on_position`] : 持仓事件
+    [`on_funding`] : 资金费用事件
+    [`on_liquidation`] : 强平事件
+    """
+
+def run_paper__(
+    replay_path: Optional[str] = None,
+    replay_begin: Optional[str] = None,
+    ws_url: Optional[str] = None,
+    state_path: Optional[str] = None,
+    cash: Decimal = Decimal("1000"),
+    fee_rate_taker: Decimal = Decimal("0.0005"),
+    fee_rate_maker: Decimal = Decimal("0.0005"),
+    fee_rate_liquidation: Decimal = Decimal("0.0125"),
+    slippage_rate: Decimal = Decimal("0"),
+    slippage_model: Literal["fixed", "volume", "spread"] = "fixed",
+    match_mode: Literal["close", "ohlc"] = "close",
+    kline_path: Literal["ohlc", "olhc", "nearest"] = "nearest",
+    participation_rate: Optional[Decimal] = None,
+    rule_path: Optional[str] = None,
+    kline_history: int = 1000,
+    timer_minutely: bool = True,
+    on_init: Optional[BasicCallback] = None,
+    on_start: Optional[BasicCallback] = None,
+    on_stop: Optional[BasicCallback] = None,
+    on_daily: Optional[BasicCallback] = None,
+    on_hourly: Optional[BasicCallback] = None,
+    on_minutely: Optional[BasicCallback] = None,
+    on_kline: Optional[KlineCallback] = None,
+    on_order: Optional[OrderCallback] = None,
+    on_position: Optional[PositionCallback] = None,
+    on_funding: Optional[FundingCallback] = None,
+    on_liquidation: Optional[LiquidationCallback] = None,
+):
+    """
+    运行模拟盘: 实时行情或本地回放, 按回测规则撮合, 直到调用 `API.stop` 或回放完成
+    [`replay_path`] : 回放文件路径, 每行一条币安组合数据流消息. 为空时使用币安实时行情
+    [`replay_begin`] : 回放开始时间, 回放模式下必填
+    [`ws_url`] : 实时行情数据流地址, 为空时使用主网
+    [`state_path`] : 状态文件路径. 启动时恢复, 每分钟及停止时保存
+    [`cash`] : 初始资金
+    [`fee_rate_taker`] : 吃单手续费
+    [`fee_rate_maker`] : 挂单手续费
+    [`fee_rate_liquidation`] : 强平手续费
+    [`slippage_rate`] : 滑点, 默认为0即不计滑点
+    [`slippage_model`] : 滑点模型
+    [`match_mode`] : 撮合模式. close: 收盘价撮合, ohlc: K线路径撮合
+    [`kline_path`] : K线内价格路径假设. ohlc: 开高低收, olhc: 开低高收, nearest: 较近的极值先到达
+    [`participation_rate`] : 成交量参与率. 每根K线成交数量不超过K线成交量 * 参与率, 为空时订单一次全部成交
+    [`rule_path`] : 交易规则快照路径, 文件或按日期命名的版本目录. 为空时使用缓存目录下的快照, 快照或其中的交易对不存在时不限制
+    [`kline_history`] : 每个交易对每个K线周期缓存的已收盘K线数量
+    [`timer_minutely`] : 是否执行每分钟事件
+    [`on_init`] : 初始化事件
+    [`on_start`] : 开始事件
+    [`on_stop`] : 停止事件
+    [`on_daily`] : 每日事件
+    [`on_hourly`] : 每小时事件
+    [`on_minutely`] : 每分钟事件
+    [`on_kline`] : K线事件
+    [`on_order`] : 订单事件
+    [`on_position`] : 持仓事件
+    [`on_funding`] : 资金费用事件
+    [`on_liquidation`] : 强平事件
+    """
+
+def run_live__(
+    api_key: str,
+    api_secret: str,
+    testnet: bool = True,
+    rest_url: Optional[str] = None,
+    ws_url: Optional[str] = None,
+    kline_history: int = 1000,
+    on_init: Optional[BasicCallback] = None,
+    on_start: Optional[BasicCallback] = None,
+    on_stop: Optional[BasicCallback] = None,
+    on_daily: Optional[BasicCallback] = None,
+    on_hourly: Optional[BasicCallback] = None,
+    on_minutely: Optional[BasicCallback] = None,
+    on_kline: Optional[KlineCallback] = None,
+    on_order: Optional[OrderCallback] = None,
+    on_position: Optional[PositionCallback] = None,
+    on_funding: Optional[FundingCallback] = None,
+    on_liquidation: Optional[LiquidationCallback] = None,
+):
+    """
+    运行币安U本位合约实盘, 直到调用 `API.stop`
+    [`api_key`] : API Key
+    [`api_secret`] : API Secret
+    [`testnet`] : 是否使用测试网, 否则为主网
+    [`rest_url`] : REST接口地址, 为空时按网络使用币安地址
+    [`ws_url`] : 数据流地址, 为空时按网络使用币安地址
+    [`kline_history`] : 每个交易对每个K线周期缓存的已收盘K线数量
     [`on_init`] : 初始化事件
     [`on_start`] : 开始事件
     [`on_stop`] : 停止事件
@@ -551,4 +1774,6 @@ def run_backtest__(
     [`on_kline`] : K线事件
     [`on_order`] : 订单事件
     [`on_position`] : 持仓事件
+    [`on_funding`] : 资金费用事件
+    [`on_liquidation`] : 强平事件
     """
@@ -0,0 +1,47 @@
+from .destiny import *
+from .strategy import Strategy
+from typing import Optional
+import setproctitle
+
+
+def run_live(
+    strategy: Strategy,
+    api_key: str,
+    api_secret: str,
+    testnet: bool = True,
+    rest_url: Optional[str] = None,
+    ws_url: Optional[str] = None,
+    kline_history: int = 1000,
+) -> Strategy:
+    """
+    运行币安U本位合约实盘, 直到调用 `API.stop`
+    [`api_key`] : API Key
+    [`api_secret`] : API Secret
+    [`testnet`] : 是否使用测试网, 否则为主网
+    [`rest_url`] : REST接口地址, 为空时按网络使用币安地址
+    [`ws_url`] : 数据流地址, 为空时按网络使用币安地址
+    [`kline_history`] : 每个交易对每个K线周期缓存的已收盘K线数量
+    账户、持仓与订单以币安为准, 交易规则在启动时从币安同步
+    """
+
+    setproctitle.setproctitle(f"wealth-discovery-destiny-live")
+    run_live__(
+        api_key,
+        api_secret,
+        testnet,
+        rest_url,
+        ws_url,
+        kline_history,
+        strategy.on_init,
+        strategy.on_start,
+        strategy.on_stop,
+        strategy.on_daily,
+        strategy.on_hourly,
+        strategy.on_minutely,
+        strategy.on_kline,
+        strategy.on_order,
+        strategy.on_position,
+        strategy.on_funding,
+        strategy.on_liquidation,
+    )
+    return strategy
@@ -0,0 +1,77 @@
+from .destiny import *
+from .strategy import Strategy
+from decimal import Decimal
+from typing import Literal, Optional
+import setproctitle
+
+
+def run_paper(
+    strategy: Strategy,
+    replay_path: Optional[str] = None,
+    replay_begin: Optional[str] = None,
+    ws_url: Optional[str] = None,
+    state_path: Optional[str] = None,
+    cash: Decimal = Decimal("1000"),
+    fee_rate_taker: Decimal = Decimal("0.0005"),
+    fee_rate_maker: Decimal = Decimal("0.0005"),
+    fee_rate_liquidation: Decimal = Decimal("0.0125"),
+    slippage_rate: Decimal = Decimal("0"),
+    slippage_model: Literal["fixed", "volume", "spread"] = "fixed",
+    match_mode: Literal["close", "ohlc"] = "close",
+    kline_path: Literal["ohlc", "olhc", "nearest"] = "nearest",
+    participation_rate: Optional[Decimal] = None,
+    rule_path: Optional[str] = None,
+    kline_history: int = 1000,
+    timer_minutely: bool = True,
+) -> Strategy:
+    """
+    运行模拟盘: 实时行情或本地回放, 按回测规则撮合, 直到调用 `API.stop` 或回放完成
+    [`replay_path`] : 回放文件路径, 每行一条币安组合数据流消息. 为空时使用币安实时行情
+    [`replay_begin`] : 回放开始时间, 回放模式下必填
+    [`ws_url`] : 实时行情数据流地址, 为空时使用主网
+    [`state_path`] : 状态文件路径. 启动时恢复, 每分钟及停止时保存
+    [`cash`] : 初始资金
+    [`fee_rate_taker`] : 吃单手续费
+    [`fee_rate_maker`] : 挂单手续费
+    [`fee_rate_liquidation`] : 强平手续费
+    [`slippage_rate`] : 滑点, 默认为0即不计滑点
+    [`slippage_model`] : 滑点模型
+    [`match_mode`] : 撮合模式. close: 收盘价撮合, ohlc: K线路径撮合
+    [`kline_path`] : K线内价格路径假设. ohlc: 开高低收, olhc: 开低高收, nearest: 较近的极值先到达
+    [`participation_rate`] : 成交量参与率. 每根K线成交数量不超过K线成交量 * 参与率, 为空时订单一次全部成交
+    [`rule_path`] : 交易规则快照路径, 文件或按日期命名的版本目录. 为空时使用缓存目录下的快照, 快照或其中的交易对不存在时不限制
+    [`kline_history`] : 每个交易对每个K线周期缓存的已收盘K线数量
+    [`timer_minutely`] : 是否执行每分钟事件
+    """
+
+    setproctitle.setproctitle(f"wealth-discovery-destiny-paper")
+    run_paper__(
+        replay_path,
+        replay_begin,
+        ws_url,
+        state_path,
+        cash,
+        fee_rate_taker,
+        fee_rate_maker,
+        fee_rate_liquidation,
+        slippage_rate,
+        slippage_model,
+        match_mode,
+        kline_path,
+        participation_rate,
+        rule_path,
+        kline_history,
+        timer_minutely,
+        strategy.on_init,
+        strategy.on_start,
+        strategy.on_stop,
+        strategy.on_daily,
+        strategy.on_hourly,
+        strategy.on_minutely,
+        strategy.on_kline,
+        strategy.on_order,
+        strategy.on_position,
+        strategy.on_funding,
+        strategy.on_liquidation,
+    )
+    return strategy
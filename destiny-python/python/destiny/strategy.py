@@ -36,7 +36,15 @@ class Strategy(ABC):
         pass
 
     @abstractmethod
-    def on_position(self, api: API, position: Position):
+    def on_position(self, api: API, event: PositionEvent):
+        pass
+
+    @abstractmethod
+    def on_funding(self, api: API, funding: Funding):
+        pass
+
+    @abstractmethod
+    def on_liquidation(self, api: API, liquidation: Liquidation):
         pass
 
 
@@ -70,5 +78,11 @@ class StrategyWrapper(Strategy):
     def on_order(self, api: API, order: Order):
         self.callback.on_order(api, order)
 
-    def on_position(self, api: API, position: Position):
-        self.callback.on_position(api, position)
+    def on_position(self, api: API, event: PositionEvent):
+        self.callback.on_position(api, event)
+
+    def on_funding(self, api: API, funding: Funding):
+        self.callback.on_funding(api, funding)
+
+    def on_liquidation(self, api: API, liquidation: Liquidation):
+        self.callback.on_liquidation(api, liquidation)
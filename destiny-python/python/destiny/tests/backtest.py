@@ -43,7 +43,13 @@ class MyStrategy(Strategy):
     def on_order(self, api: API, order: Order):
         pass
 
-    def on_position(self, api: API, position: Position):
+    def on_position(self, api: API, event: PositionEvent):
+        pass
+
+    def on_funding(self, api: API, funding: Funding):
+        pass
+
+    def on_liquidation(self, api: API, liquidation: Liquidation):
         pass
 
 
@@ -5,10 +5,35 @@ use pyo3::{prelude::*, types::PyTuple};
 fn destiny(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<TradeType>()?;
     m.add_class::<TradeSide>()?;
+    m.add_class::<PositionMode>()?;
+    m.add_class::<MarginMode>()?;
+    m.add_class::<TimeInForce>()?;
+    m.add_class::<WorkingType>()?;
     m.add_class::<OrderStatus>()?;
+    m.add_class::<KlineInterval>()?;
     m.add_class::<Kline>()?;
+    m.add_class::<KlineColumns>()?;
+    m.add_class::<Ema>()?;
+    m.add_class::<Rsi>()?;
+    m.add_class::<Atr>()?;
+    m.add_class::<Bollinger>()?;
+    m.add_class::<BollingerValue>()?;
+    m.add_class::<Macd>()?;
+    m.add_class::<MacdValue>()?;
+    m.add_class::<Vwap>()?;
     m.add_class::<Order>()?;
+    m.add_class::<OrderRequest>()?;
+    m.add_class::<OrderGroupType>()?;
+    m.add_class::<OrderGroup>()?;
     m.add_class::<Position>()?;
+    m.add_class::<PositionReason>()?;
+    m.add_class::<PositionEvent>()?;
+    m.add_class::<Funding>()?;
+    m.add_class::<Liquidation>()?;
+    m.add_class::<Trade>()?;
+    m.add_class::<EquityPoint>()?;
+    m.add_class::<BacktestReport>()?;
+    m.add_class::<AccountSnapshot>()?;
     m.add_class::<PythonEngine>()?;
     m.add_function(wrap_pyfunction!(init_log, m)?)?;
     m.add_function(wrap_pyfunction!(free_log, m)?)?;
@@ -20,6 +45,8 @@ fn destiny(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(log_print, m)?)?;
     m.add_function(wrap_pyfunction!(download_history_data, m)?)?;
     m.add_function(wrap_pyfunction!(run_backtest, m)?)?;
+    m.add_function(wrap_pyfunction!(run_paper, m)?)?;
+    m.add_function(wrap_pyfunction!(run_live, m)?)?;
     Ok(())
 }
 
@@ -175,6 +202,63 @@ impl PythonEngine {
         self.0.symbol_init(symbol)
     }
 
+    #[pyo3(signature = (
+        symbol,
+        price_tick,
+        size_tick,
+        price_min = None,
+        price_max = None,
+        size_min = None,
+        size_max = None,
+        amount_min = None,
+        order_max = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn init_symbol_with_rule(
+        &self,
+        symbol: &str,
+        price_tick: Decimal,
+        size_tick: Decimal,
+        price_min: Option<Decimal>,
+        price_max: Option<Decimal>,
+        size_min: Option<Decimal>,
+        size_max: Option<Decimal>,
+        amount_min: Option<Decimal>,
+        order_max: Option<i64>,
+    ) -> Result<()> {
+        let default = SymbolRule::default();
+        self.0.symbol_init_with_rule(
+            symbol,
+            SymbolRule {
+                price_min: price_min.unwrap_or(default.price_min),
+                price_max: price_max.unwrap_or(default.price_max),
+                price_tick,
+                size_min: size_min.unwrap_or(default.size_min),
+                size_max: size_max.unwrap_or(default.size_max),
+                size_tick,
+                amount_min: amount_min.unwrap_or(default.amount_min),
+                order_max: order_max.unwrap_or(default.order_max),
+                margin_tiers: default.margin_tiers,
+            },
+        )
+    }
+
+    #[pyo3(signature = (symbol, intervals))]
+    fn kline_subscribe(&self, symbol: &str, intervals: Vec<KlineInterval>) -> Result<()> {
+        self.0.kline_subscribe(symbol, &intervals)
+    }
+
+    #[pyo3(signature = (symbol, interval, name, indicator))]
+    fn indicator_register(
+        &self,
+        symbol: &str,
+        interval: KlineInterval,
+        name: &str,
+        indicator: Indicator,
+    ) -> Result<()> {
+        self.0.indicator_register(symbol, interval, name, indicator)
+    }
+
     #[pyo3(signature = (symbol, id))]
     fn order(&self, symbol: &str, id: &str) -> Option<Order> {
         self.0.order(symbol, id)
@@ -320,6 +404,60 @@ impl PythonEngine {
         self.0.symbol_margin(symbol)
     }
 
+    #[pyo3(signature = (symbol))]
+    fn fundings(&self, symbol: &str) -> Vec<Funding> {
+        self.0.fundings(symbol)
+    }
+
+    #[pyo3(signature = (symbol, begin, end))]
+    fn orders_history(&self, symbol: &str, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Order> {
+        self.0.orders_history(symbol, begin, end)
+    }
+
+    #[pyo3(signature = (symbol, begin, end))]
+    fn trades(&self, symbol: &str, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Trade> {
+        self.0.trades(symbol, begin, end)
+    }
+
+    #[pyo3(signature = (symbol))]
+    fn funding_fee(&self, symbol: &str) -> Decimal {
+        self.0.funding_fee(symbol)
+    }
+
+    #[pyo3(signature = (symbol, side))]
+    fn liquidation_price(&self, symbol: &str, side: TradeSide) -> Decimal {
+        self.0.liquidation_price(symbol, side)
+    }
+
+    #[pyo3(signature = (symbol))]
+    fn position_mode(&self, symbol: &str) -> PositionMode {
+        self.0.position_mode(symbol)
+    }
+
+    #[pyo3(signature = (symbol))]
+    fn margin_mode(&self, symbol: &str) -> MarginMode {
+        self.0.margin_mode(symbol)
+    }
+
+    fn snapshot(&self) -> AccountSnapshot {
+        self.0.snapshot()
+    }
+
+    #[pyo3(signature = (symbol))]
+    fn margin_isolated(&self, symbol: &str) -> Decimal {
+        self.0.margin_isolated(symbol)
+    }
+
+    #[pyo3(signature = (symbol, id))]
+    fn order_group(&self, symbol: &str, id: &str) -> Option<OrderGroup> {
+        self.0.order_group(symbol, id)
+    }
+
+    #[pyo3(signature = (symbol))]
+    fn order_groups(&self, symbol: &str) -> Vec<OrderGroup> {
+        self.0.order_groups(symbol)
+    }
+
     #[pyo3(signature = (symbol))]
     fn price_mark(&self, symbol: &str) -> Decimal {
         self.0.price_mark(symbol)
@@ -385,6 +523,16 @@ impl PythonEngine {
         self.0.rule_order_max(symbol)
     }
 
+    #[pyo3(signature = (symbol, interval, limit))]
+    fn klines(&self, symbol: &str, interval: KlineInterval, limit: usize) -> KlineColumns {
+        KlineColumns::from(self.0.klines(symbol, interval, limit).as_slice())
+    }
+
+    #[pyo3(signature = (symbol, interval, name))]
+    fn indicator(&self, symbol: &str, interval: KlineInterval, name: &str) -> Option<Indicator> {
+        self.0.indicator(symbol, interval, name)
+    }
+
     #[pyo3(signature = (symbol, size))]
     fn long_market_open(&self, symbol: &str, size: Decimal) -> Result<String> {
         tokio::task::block_in_place(move || {
@@ -449,6 +597,75 @@ impl PythonEngine {
         })
     }
 
+    #[pyo3(signature = (symbol, r#type, side, size, price=Decimal::ZERO, reduce_only=false, close_position=false, time_in_force=TimeInForce::Gtc, client_id=None, stop_price=Decimal::ZERO, working_type=WorkingType::Last, callback_rate=Decimal::ZERO))]
+    #[allow(clippy::too_many_arguments)]
+    fn order_place(
+        &self,
+        symbol: &str,
+        r#type: TradeType,
+        side: TradeSide,
+        size: Decimal,
+        price: Decimal,
+        reduce_only: bool,
+        close_position: bool,
+        time_in_force: TimeInForce,
+        client_id: Option<String>,
+        stop_price: Decimal,
+        working_type: WorkingType,
+        callback_rate: Decimal,
+    ) -> Result<String> {
+        let request = OrderRequest {
+            symbol: symbol.to_string(),
+            r#type,
+            side,
+            reduce_only,
+            close_position,
+            price,
+            size,
+            time_in_force,
+            stop_price,
+            working_type,
+            callback_rate,
+            client_id,
+        };
+        tokio::task::block_in_place(move || {
+            tokio::runtime::Handle::current()
+                .block_on(async move { self.0.order_place(request).await })
+        })
+    }
+
+    #[pyo3(signature = (requests))]
+    fn order_place_oco(&self, requests: Vec<OrderRequest>) -> Result<String> {
+        tokio::task::block_in_place(move || {
+            tokio::runtime::Handle::current()
+                .block_on(async move { self.0.order_place_oco(requests).await })
+        })
+    }
+
+    #[pyo3(signature = (entry, stop_loss=None, take_profit=None))]
+    fn order_place_bracket(
+        &self,
+        entry: OrderRequest,
+        stop_loss: Option<OrderRequest>,
+        take_profit: Option<OrderRequest>,
+    ) -> Result<String> {
+        tokio::task::block_in_place(move || {
+            tokio::runtime::Handle::current().block_on(async move {
+                self.0
+                    .order_place_bracket(entry, stop_loss, take_profit)
+                    .await
+            })
+        })
+    }
+
+    #[pyo3(signature = (symbol, id))]
+    fn order_group_cancel(&self, symbol: &str, id: &str) -> Result<()> {
+        tokio::task::block_in_place(move || {
+            tokio::runtime::Handle::current()
+                .block_on(async move { self.0.order_group_cancel(symbol, id).await })
+        })
+    }
+
     #[pyo3(signature = (symbol, id))]
     fn order_close(&self, symbol: &str, id: &str) -> Result<()> {
         tokio::task::block_in_place(move || {
@@ -465,6 +682,14 @@ impl PythonEngine {
         })
     }
 
+    #[pyo3(signature = (symbol, id, price, size))]
+    fn order_amend(&self, symbol: &str, id: &str, price: Decimal, size: Decimal) -> Result<()> {
+        tokio::task::block_in_place(move || {
+            tokio::runtime::Handle::current()
+                .block_on(async move { self.0.order_amend(symbol, id, price, size).await })
+        })
+    }
+
     #[pyo3(signature = (symbol, leverage))]
     fn leverage_set(&self, symbol: &str, leverage: u32) -> Result<()> {
         tokio::task::block_in_place(move || {
@@ -472,6 +697,38 @@ impl PythonEngine {
                 .block_on(async move { self.0.leverage_set(symbol, leverage).await })
         })
     }
+
+    #[pyo3(signature = (symbol, mode))]
+    fn position_mode_set(&self, symbol: &str, mode: PositionMode) -> Result<()> {
+        tokio::task::block_in_place(move || {
+            tokio::runtime::Handle::current()
+                .block_on(async move { self.0.position_mode_set(symbol, mode).await })
+        })
+    }
+
+    #[pyo3(signature = (symbol, mode))]
+    fn margin_mode_set(&self, symbol: &str, mode: MarginMode) -> Result<()> {
+        tokio::task::block_in_place(move || {
+            tokio::runtime::Handle::current()
+                .block_on(async move { self.0.margin_mode_set(symbol, mode).await })
+        })
+    }
+
+    #[pyo3(signature = (symbol, side, amount))]
+    fn margin_add(&self, symbol: &str, side: TradeSide, amount: Decimal) -> Result<()> {
+        tokio::task::block_in_place(move || {
+            tokio::runtime::Handle::current()
+                .block_on(async move { self.0.margin_add(symbol, side, amount).await })
+        })
+    }
+
+    #[pyo3(signature = (symbol, side, amount))]
+    fn margin_remove(&self, symbol: &str, side: TradeSide, amount: Decimal) -> Result<()> {
+        tokio::task::block_in_place(move || {
+            tokio::runtime::Handle::current()
+                .block_on(async move { self.0.margin_remove(symbol, side, amount).await })
+        })
+    }
 }
 
 struct PythonStrategy {
@@ -484,6 +741,8 @@ struct PythonStrategy {
     on_kline: Option<Py<PyAny>>,
     on_order: Option<Py<PyAny>>,
     on_position: Option<Py<PyAny>>,
+    on_funding: Option<Py<PyAny>>,
+    on_liquidation: Option<Py<PyAny>>,
 }
 
 impl PythonStrategy {
@@ -498,6 +757,8 @@ impl PythonStrategy {
         on_kline: Option<Py<PyAny>>,
         on_order: Option<Py<PyAny>>,
         on_position: Option<Py<PyAny>>,
+        on_funding: Option<Py<PyAny>>,
+        on_liquidation: Option<Py<PyAny>>,
     ) -> Self {
         Self {
             on_init,
@@ -509,6 +770,8 @@ impl PythonStrategy {
             on_kline,
             on_order,
             on_position,
+            on_funding,
+            on_liquidation,
         }
     }
 }
@@ -587,10 +850,32 @@ impl Strategy for PythonStrategy {
         }
         Ok(())
     }
-    async fn on_position(&self, engine: Arc<dyn Engine>, position: Position) -> Result<()> {
+    async fn on_position(&self, engine: Arc<dyn Engine>, event: PositionEvent) -> Result<()> {
         if let Some(callback) = &self.on_position {
             Python::with_gil(|py| {
-                callback.call1(py, (PythonEngine(engine), position))?;
+                callback.call1(py, (PythonEngine(engine), event))?;
+                anyhow::Ok(())
+            })?;
+        }
+        Ok(())
+    }
+    async fn on_funding(&self, engine: Arc<dyn Engine>, funding: Funding) -> Result<()> {
+        if let Some(callback) = &self.on_funding {
+            Python::with_gil(|py| {
+                callback.call1(py, (PythonEngine(engine), funding))?;
+                anyhow::Ok(())
+            })?;
+        }
+        Ok(())
+    }
+    async fn on_liquidation(
+        &self,
+        engine: Arc<dyn Engine>,
+        liquidation: Liquidation,
+    ) -> Result<()> {
+        if let Some(callback) = &self.on_liquidation {
+            Python::with_gil(|py| {
+                callback.call1(py, (PythonEngine(engine), liquidation))?;
                 anyhow::Ok(())
             })?;
         }
@@ -626,6 +911,47 @@ fn download_history_data(py: Python<'_>, metas: Vec<(String, String, String)>) -
     })
 }
 
+/// 回测与模拟盘共用的撮合、手续费与滑点参数
+#[allow(clippy::too_many_arguments)]
+fn simulate_config(
+    cash: Decimal,
+    fee_rate_taker: Decimal,
+    fee_rate_maker: Decimal,
+    fee_rate_liquidation: Decimal,
+    slippage_rate: Decimal,
+    slippage_model: &str,
+    match_mode: &str,
+    kline_path: &str,
+    participation_rate: Option<Decimal>,
+) -> Result<BacktestConfigBuilder> {
+    let mut builder = BacktestConfigBuilder::default();
+    builder
+        .cash(cash)
+        .fee_rate_taker(fee_rate_taker)
+        .fee_rate_maker(fee_rate_maker)
+        .fee_rate_liquidation(fee_rate_liquidation)
+        .slippage_rate(slippage_rate)
+        .slippage_model(match slippage_model {
+            "fixed" => SlippageModel::Fixed,
+            "volume" => SlippageModel::Volume,
+            "spread" => SlippageModel::Spread,
+            _ => anyhow::bail!("未知的滑点模型: {}", slippage_model),
+        })
+        .match_mode(match match_mode {
+            "close" => MatchMode::Close,
+            "ohlc" => MatchMode::Ohlc,
+            _ => anyhow::bail!("未知的撮合模式: {}", match_mode),
+        })
+        .kline_path(match kline_path {
+            "ohlc" => KlinePath::OpenHighLowClose,
+            "olhc" => KlinePath::OpenLowHighClose,
+            "nearest" => KlinePath::Nearest,
+            _ => anyhow::bail!("未知的K线路径: {}", kline_path),
+        })
+        .participation_rate(participation_rate);
+    Ok(builder)
+}
+
 #[pyfunction]
 #[pyo3(
     name="run_backtest__", 
@@ -635,7 +961,17 @@ fn download_history_data(py: Python<'_>, metas: Vec<(String, String, String)>) -
         cash = dec!(1000),
         fee_rate_taker = dec!(0.0005),
         fee_rate_maker = dec!(0.0005),
-        slippage_rate = dec!(0.01),
+        fee_rate_liquidation = dec!(0.0125),
+        slippage_rate = dec!(0),
+        slippage_model = "fixed",
+        match_mode = "close",
+        kline_path = "nearest",
+        participation_rate = None,
+        ledger_path = None,
+        rule_path = None,
+        kline_history = 1000,
+        timer_minutely = true,
+        history_path = None,
         on_init = None,
         on_start = None,
         on_stop = None,
@@ -645,6 +981,8 @@ fn download_history_data(py: Python<'_>, metas: Vec<(String, String, String)>) -
         on_kline = None,
         on_order = None,
         on_position = None,
+        on_funding = None,
+        on_liquidation = None,
     )
 )]
 #[allow(clippy::too_many_arguments)]
@@ -655,7 +993,17 @@ fn run_backtest(
     cash: Decimal,
     fee_rate_taker: Decimal,
     fee_rate_maker: Decimal,
+    fee_rate_liquidation: Decimal,
     slippage_rate: Decimal,
+    slippage_model: &str,
+    match_mode: &str,
+    kline_path: &str,
+    participation_rate: Option<Decimal>,
+    ledger_path: Option<PathBuf>,
+    rule_path: Option<PathBuf>,
+    kline_history: usize,
+    timer_minutely: bool,
+    history_path: Option<PathBuf>,
     on_init: Option<Py<PyAny>>,
     on_start: Option<Py<PyAny>>,
     on_stop: Option<Py<PyAny>>,
@@ -665,17 +1013,146 @@ fn run_backtest(
     on_kline: Option<Py<PyAny>>,
     on_order: Option<Py<PyAny>>,
     on_position: Option<Py<PyAny>>,
-) -> Result<()> {
+    on_funding: Option<Py<PyAny>>,
+    on_liquidation: Option<Py<PyAny>>,
+) -> Result<BacktestReport> {
     py.allow_threads(|| {
         RUNTIME.block_on(async move {
             Backtest::run(
-                BacktestConfigBuilder::default()
-                    .begin(begin.to_date()?)
-                    .end(end.to_date()?)
-                    .cash(cash)
-                    .fee_rate_taker(fee_rate_taker)
-                    .fee_rate_maker(fee_rate_maker)
-                    .slippage_rate(slippage_rate)
+                simulate_config(
+                    cash,
+                    fee_rate_taker,
+                    fee_rate_maker,
+                    fee_rate_liquidation,
+                    slippage_rate,
+                    slippage_model,
+                    match_mode,
+                    kline_path,
+                    participation_rate,
+                )?
+                .begin(begin.to_date()?)
+                .end(end.to_date()?)
+                .ledger_path(ledger_path)
+                .rule_path(rule_path)
+                .kline_history(kline_history)
+                .timer_minutely(timer_minutely)
+                .history_path(history_path)
+                .build()?,
+                Arc::new(PythonStrategy::new(
+                    on_init,
+                    on_start,
+                    on_stop,
+                    on_daily,
+                    on_hourly,
+                    on_minutely,
+                    on_kline,
+                    on_order,
+                    on_position,
+                    on_funding,
+                    on_liquidation,
+                )),
+            )
+            .await
+        })
+    })
+}
+
+#[pyfunction]
+#[pyo3(
+    name="run_paper__", 
+    signature = (
+        replay_path = None,
+        replay_begin = None,
+        ws_url = None,
+        state_path = None,
+        cash = dec!(1000),
+        fee_rate_taker = dec!(0.0005),
+        fee_rate_maker = dec!(0.0005),
+        fee_rate_liquidation = dec!(0.0125),
+        slippage_rate = dec!(0),
+        slippage_model = "fixed",
+        match_mode = "close",
+        kline_path = "nearest",
+        participation_rate = None,
+        rule_path = None,
+        kline_history = 1000,
+        timer_minutely = true,
+        on_init = None,
+        on_start = None,
+        on_stop = None,
+        on_daily = None,
+        on_hourly = None,
+        on_minutely = None,
+        on_kline = None,
+        on_order = None,
+        on_position = None,
+        on_funding = None,
+        on_liquidation = None,
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+fn run_paper(
+    py: Python<'_>,
+    replay_path: Option<PathBuf>,
+    replay_begin: Option<&str>,
+    ws_url: Option<String>,
+    state_path: Option<PathBuf>,
+    cash: Decimal,
+    fee_rate_taker: Decimal,
+    fee_rate_maker: Decimal,
+    fee_rate_liquidation: Decimal,
+    slippage_rate: Decimal,
+    slippage_model: &str,
+    match_mode: &str,
+    kline_path: &str,
+    participation_rate: Option<Decimal>,
+    rule_path: Option<PathBuf>,
+    kline_history: usize,
+    timer_minutely: bool,
+    on_init: Option<Py<PyAny>>,
+    on_start: Option<Py<PyAny>>,
+    on_stop: Option<Py<PyAny>>,
+    on_daily: Option<Py<PyAny>>,
+    on_hourly: Option<Py<PyAny>>,
+    on_minutely: Option<Py<PyAny>>,
+    on_kline: Option<Py<PyAny>>,
+    on_order: Option<Py<PyAny>>,
+    on_position: Option<Py<PyAny>>,
+    on_funding: Option<Py<PyAny>>,
+    on_liquidation: Option<Py<PyAny>>,
+) -> Result<()> {
+    py.allow_threads(|| {
+        RUNTIME.block_on(async move {
+            let source = match replay_path {
+                Some(path) => PaperSource::Replay {
+                    path,
+                    begin: replay_begin
+                        .ok_or(anyhow::anyhow!("回放模式需要指定开始时间"))?
+                        .to_date()?,
+                },
+                None => PaperSource::Binance { ws_url },
+            };
+            Paper::run(
+                PaperConfigBuilder::default()
+                    .source(source)
+                    .backtest(
+                        simulate_config(
+                            cash,
+                            fee_rate_taker,
+                            fee_rate_maker,
+                            fee_rate_liquidation,
+                            slippage_rate,
+                            slippage_model,
+                            match_mode,
+                            kline_path,
+                            participation_rate,
+                        )?
+                        .rule_path(rule_path)
+                        .kline_history(kline_history)
+                        .timer_minutely(timer_minutely)
+                        .to_owned(),
+                    )
+                    .state_path(state_path)
                     .build()?,
                 Arc::new(PythonStrategy::new(
                     on_init,
@@ -687,10 +1164,89 @@ fn run_backtest(
                     on_kline,
                     on_order,
                     on_position,
+                    on_funding,
+                    on_liquidation,
                 )),
             )
-            .await?;
-            anyhow::Ok(())
+            .await
+        })
+    })
+}
+
+#[pyfunction]
+#[pyo3(
+    name="run_live__", 
+    signature = (
+        api_key,
+        api_secret,
+        testnet = true,
+        rest_url = None,
+        ws_url = None,
+        kline_history = 1000,
+        on_init = None,
+        on_start = None,
+        on_stop = None,
+        on_daily = None,
+        on_hourly = None,
+        on_minutely = None,
+        on_kline = None,
+        on_order = None,
+        on_position = None,
+        on_funding = None,
+        on_liquidation = None,
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+fn run_live(
+    py: Python<'_>,
+    api_key: &str,
+    api_secret: &str,
+    testnet: bool,
+    rest_url: Option<String>,
+    ws_url: Option<String>,
+    kline_history: usize,
+    on_init: Option<Py<PyAny>>,
+    on_start: Option<Py<PyAny>>,
+    on_stop: Option<Py<PyAny>>,
+    on_daily: Option<Py<PyAny>>,
+    on_hourly: Option<Py<PyAny>>,
+    on_minutely: Option<Py<PyAny>>,
+    on_kline: Option<Py<PyAny>>,
+    on_order: Option<Py<PyAny>>,
+    on_position: Option<Py<PyAny>>,
+    on_funding: Option<Py<PyAny>>,
+    on_liquidation: Option<Py<PyAny>>,
+) -> Result<()> {
+    py.allow_threads(|| {
+        RUNTIME.block_on(async move {
+            Binance::run(
+                BinanceConfigBuilder::default()
+                    .run_mode(if testnet {
+                        RunMode::Testnet
+                    } else {
+                        RunMode::Mainnet
+                    })
+                    .api_key(api_key.to_string())
+                    .api_secret(api_secret.to_string())
+                    .rest_url(rest_url)
+                    .ws_url(ws_url)
+                    .kline_history(kline_history)
+                    .build()?,
+                Arc::new(PythonStrategy::new(
+                    on_init,
+                    on_start,
+                    on_stop,
+                    on_daily,
+                    on_hourly,
+                    on_minutely,
+                    on_kline,
+                    on_order,
+                    on_position,
+                    on_funding,
+                    on_liquidation,
+                )),
+            )
+            .await
         })
     })
 }
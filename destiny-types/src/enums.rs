@@ -1,3 +1,4 @@
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumIter};
 
@@ -6,6 +7,8 @@ use strum::{Display, EnumIter};
 pub enum RunMode {
     /// 回测
     Backtest,
+    /// 模拟盘: 实时行情, 模拟撮合
+    Paper,
     /// 测试网
     Testnet,
     /// 主网
@@ -20,6 +23,39 @@ pub enum TradeType {
     Limit,
     /// 市价
     Market,
+    /// 止损市价: 触发后按市价成交
+    StopMarket,
+    /// 止损限价: 触发后按限价挂单
+    StopLimit,
+    /// 止盈市价: 触发后按市价成交
+    TakeProfitMarket,
+    /// 止盈限价: 触发后按限价挂单
+    TakeProfitLimit,
+    /// 跟踪止损: 触发价格随行情按回调比例移动, 触发后按市价成交
+    TrailingStopMarket,
+}
+
+impl TradeType {
+    /// 是否按市价成交, 含触发后按市价成交的条件单
+    pub fn is_market(&self) -> bool {
+        matches!(
+            self,
+            TradeType::Market
+                | TradeType::StopMarket
+                | TradeType::TakeProfitMarket
+                | TradeType::TrailingStopMarket
+        )
+    }
+
+    /// 是否按限价成交, 含触发后按限价挂单的条件单
+    pub fn is_limit(&self) -> bool {
+        !self.is_market()
+    }
+
+    /// 是否为条件单: 价格到达触发价格后才参与撮合
+    pub fn is_conditional(&self) -> bool {
+        !matches!(self, TradeType::Limit | TradeType::Market)
+    }
 }
 
 /// 交易方向
@@ -32,10 +68,96 @@ pub enum TradeSide {
     Short,
 }
 
+/// 保证金模式
+#[cfg_attr(feature = "python", pyo3::pyclass(eq, eq_int, frozen))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Display)]
+pub enum MarginMode {
+    /// 全仓: 所有全仓交易对共享账户权益
+    Cross,
+    /// 逐仓: 交易对使用独立的保证金余额
+    Isolated,
+}
+
+/// 持仓模式
+#[cfg_attr(feature = "python", pyo3::pyclass(eq, eq_int, frozen))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Display)]
+pub enum PositionMode {
+    /// 双向持仓: 多空持仓相互独立
+    Hedge,
+    /// 单向持仓: 多空持仓相互抵消, 同一时间只持有一个方向
+    OneWay,
+}
+
+/// 订单有效方式
+#[cfg_attr(feature = "python", pyo3::pyclass(eq, eq_int, frozen))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Display)]
+pub enum TimeInForce {
+    /// 成交为止: 未成交部分持续挂单
+    #[strum(serialize = "GTC")]
+    Gtc,
+    /// 立即成交并取消剩余: 首次撮合未成交的部分取消
+    #[strum(serialize = "IOC")]
+    Ioc,
+    /// 全部成交或立即取消: 首次撮合不能全部成交则取消
+    #[strum(serialize = "FOK")]
+    Fok,
+    /// 只做挂单: 提交时即可成交则拒绝, 仅限价单
+    #[strum(serialize = "GTX")]
+    Gtx,
+}
+
+/// 订单组类型
+#[cfg_attr(feature = "python", pyo3::pyclass(eq, eq_int, frozen))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Display)]
+pub enum OrderGroupType {
+    /// 二选一: 任意订单成交或完成后撤销其余订单
+    Oco,
+    /// 括号订单: 入场订单成交后激活止损与止盈订单, 二者互斥
+    Bracket,
+}
+
+/// 条件单触发价格类型
+#[cfg_attr(feature = "python", pyo3::pyclass(eq, eq_int, frozen))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Display)]
+pub enum WorkingType {
+    /// 最新价格
+    Last,
+    /// 标记价格
+    Mark,
+}
+
+/// 持仓变动原因
+#[cfg_attr(feature = "python", pyo3::pyclass(eq, eq_int, frozen))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Display)]
+pub enum PositionReason {
+    /// 成交
+    Trade,
+    /// 强平
+    Liquidation,
+    /// 资金费用
+    Funding,
+}
+
 /// K线周期
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Display, EnumIter)]
+#[cfg_attr(feature = "python", pyo3::pyclass(eq, eq_int, frozen))]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Deserialize,
+    Serialize,
+    Display,
+    EnumIter,
+)]
 pub enum KlineInterval {
     /// 1分钟
+    #[default]
     #[strum(serialize = "1m")]
     M1,
     /// 3分钟
@@ -82,6 +204,65 @@ pub enum KlineInterval {
     Mo1,
 }
 
+impl KlineInterval {
+    /// 周期的分钟数, 月线长度不固定时为空
+    pub fn minutes(&self) -> Option<i64> {
+        match self {
+            KlineInterval::M1 => Some(1),
+            KlineInterval::M3 => Some(3),
+            KlineInterval::M5 => Some(5),
+            KlineInterval::M15 => Some(15),
+            KlineInterval::M30 => Some(30),
+            KlineInterval::H1 => Some(60),
+            KlineInterval::H2 => Some(2 * 60),
+            KlineInterval::H4 => Some(4 * 60),
+            KlineInterval::H6 => Some(6 * 60),
+            KlineInterval::H8 => Some(8 * 60),
+            KlineInterval::H12 => Some(12 * 60),
+            KlineInterval::D1 => Some(24 * 60),
+            KlineInterval::D3 => Some(3 * 24 * 60),
+            KlineInterval::W1 => Some(7 * 24 * 60),
+            KlineInterval::Mo1 => None,
+        }
+    }
+
+    /// [`time`]所在K线的开盘时间
+    /// <br> 周线从周一开始, 月线从每月1日开始, 其余周期从1970-01-01起按周期长度对齐
+    pub fn open_time(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        match self.minutes() {
+            Some(minutes) => {
+                // 1970-01-01为周四, 偏移3天使周线从周一开始
+                let offset = if *self == KlineInterval::W1 {
+                    3 * 24 * 60
+                } else {
+                    0
+                };
+                let time_minutes = time.timestamp().div_euclid(60) + offset;
+                let open_minutes = time_minutes - time_minutes.rem_euclid(minutes) - offset;
+                DateTime::from_timestamp(open_minutes * 60, 0).unwrap_or_default()
+            }
+            None => Utc
+                .with_ymd_and_hms(time.year(), time.month(), 1, 0, 0, 0)
+                .unwrap(),
+        }
+    }
+
+    /// 开盘时间为[`open_time`]的K线的下一根K线开盘时间
+    pub fn next_open_time(&self, open_time: DateTime<Utc>) -> DateTime<Utc> {
+        match self.minutes() {
+            Some(minutes) => open_time + Duration::minutes(minutes),
+            None => {
+                let (year, month) = if open_time.month() == 12 {
+                    (open_time.year() + 1, 1)
+                } else {
+                    (open_time.year(), open_time.month() + 1)
+                };
+                Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap()
+            }
+        }
+    }
+}
+
 /// 订单状态
 #[cfg_attr(feature = "python", pyo3::pyclass(eq, eq_int, frozen))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Display)]
@@ -37,6 +37,98 @@ pub struct SymbolRule {
     pub amount_min: Decimal,
     /// 最大订单数量
     pub order_max: i64,
+    /// 维持保证金档位, 按名义价值上限升序排列
+    pub margin_tiers: Vec<MarginTier>,
+}
+
+impl Default for SymbolRule {
+    /// 近似不限制的交易规则: 价格与数量范围为1e-8~1e8, 步长为1e-8, 最多200个订单
+    fn default() -> Self {
+        Self {
+            price_min: Decimal::new(1, 8),
+            price_max: Decimal::from(100_000_000),
+            price_tick: Decimal::new(1, 8),
+            size_min: Decimal::new(1, 8),
+            size_max: Decimal::from(100_000_000),
+            size_tick: Decimal::new(1, 8),
+            amount_min: Decimal::new(1, 8),
+            order_max: 200,
+            margin_tiers: MarginTier::defaults(),
+        }
+    }
+}
+
+impl SymbolRule {
+    /// 名义价值所在的维持保证金档位
+    pub fn margin_tier(&self, notional: Decimal) -> Option<&MarginTier> {
+        self.margin_tiers
+            .iter()
+            .find(|tier| notional <= tier.notional_max)
+            .or(self.margin_tiers.last())
+    }
+}
+
+/// 维持保证金档位
+#[derive(Debug, Clone)]
+pub struct MarginTier {
+    /// 名义价值上限
+    pub notional_max: Decimal,
+    /// 最大杠杆倍率
+    pub leverage_max: u32,
+    /// 维持保证金率
+    pub maintenance_rate: Decimal,
+    /// 维持保证金速算额
+    pub maintenance_amount: Decimal,
+}
+
+impl MarginTier {
+    pub fn new(
+        notional_max: Decimal,
+        leverage_max: u32,
+        maintenance_rate: Decimal,
+        maintenance_amount: Decimal,
+    ) -> Self {
+        Self {
+            notional_max,
+            leverage_max,
+            maintenance_rate,
+            maintenance_amount,
+        }
+    }
+
+    /// 默认维持保证金档位, 参考币安U本位合约主流交易对
+    pub fn defaults() -> Vec<Self> {
+        // (名义价值上限, 最大杠杆倍率, 维持保证金率(万分之), 维持保证金速算额)
+        [
+            (50_000, 125, 40, 0),
+            (250_000, 100, 50, 50),
+            (1_000_000, 50, 100, 1_300),
+            (10_000_000, 20, 250, 16_300),
+            (20_000_000, 10, 500, 266_300),
+            (50_000_000, 5, 1_000, 1_266_300),
+            (100_000_000, 4, 1_250, 2_516_300),
+            (200_000_000, 3, 1_500, 5_016_300),
+            (300_000_000, 2, 2_500, 25_016_300),
+            (500_000_000, 1, 5_000, 100_016_300),
+        ]
+        .into_iter()
+        .map(
+            |(notional_max, leverage_max, maintenance_rate, maintenance_amount)| {
+                Self::new(
+                    Decimal::from(notional_max),
+                    leverage_max,
+                    Decimal::new(maintenance_rate, 4),
+                    Decimal::from(maintenance_amount),
+                )
+            },
+        )
+        .collect()
+    }
+
+    /// 名义价值对应的维持保证金
+    pub fn maintenance(&self, notional: Decimal) -> Decimal {
+        (notional * self.maintenance_rate - self.maintenance_amount).max(Decimal::ZERO)
+    }
 }
 
 /// 交易对行情
@@ -91,6 +183,9 @@ pub struct Kline {
     pub buy_cash: Decimal,
     /// 交易笔数
     pub trades: i64,
+    /// K线周期
+    #[serde(default)]
+    pub interval: KlineInterval,
 }
 
 /// 深度信息
@@ -163,6 +258,10 @@ pub struct FundingRateHistory {
 pub struct Order {
     /// ID
     pub id: String,
+    /// 客户端订单ID, 与[`Order::id`]相同
+    pub client_id: String,
+    /// 所属订单组ID
+    pub group_id: Option<String>,
     /// 交易对
     pub symbol: String,
     /// 交易类型
@@ -171,6 +270,18 @@ pub struct Order {
     pub side: TradeSide,
     /// 开仓订单
     pub reduce_only: bool,
+    /// 全部平仓: 成交时按当前持仓数量平仓
+    pub close_position: bool,
+    /// 有效方式
+    pub time_in_force: TimeInForce,
+    /// 触发价格, 仅条件单. 跟踪止损单为随行情移动后的当前触发价格
+    #[serde(with = "serde_decimal")]
+    pub stop_price: Decimal,
+    /// 触发价格类型, 仅条件单
+    pub working_type: WorkingType,
+    /// 跟踪止损回调比例, 仅跟踪止损单
+    #[serde(with = "serde_decimal")]
+    pub callback_rate: Decimal,
     /// 订单状态
     pub status: OrderStatus,
     /// 价格
@@ -188,18 +299,293 @@ pub struct Order {
     /// 成交手续费
     #[serde(with = "serde_decimal")]
     pub deal_fee: Decimal,
+    /// 成交滑点成本
+    #[serde(with = "serde_decimal")]
+    pub deal_slippage: Decimal,
+    /// 成交已实现盈亏, 不含手续费
+    #[serde(with = "serde_decimal")]
+    pub deal_pnl: Decimal,
     /// 创建时间
     #[serde(with = "serde_chrono")]
     pub create_time: DateTime<Utc>,
+    /// 排队时间: 创建时间, 修改价格或增加数量后为修改时间
+    /// <br> 同一成交位置的订单按排队时间先后成交
+    #[serde(with = "serde_chrono")]
+    pub queue_time: DateTime<Utc>,
+}
+
+/// 下单请求
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all))]
+#[derive(Debug, Clone)]
+pub struct OrderRequest {
+    /// 交易对
+    pub symbol: String,
+    /// 交易类型
+    pub r#type: TradeType,
+    /// 交易方向
+    pub side: TradeSide,
+    /// 只减仓
+    pub reduce_only: bool,
+    /// 全部平仓: 忽略数量, 按持仓数量平仓, 隐含只减仓
+    pub close_position: bool,
+    /// 价格, 市价单忽略
+    pub price: Decimal,
+    /// 数量
+    pub size: Decimal,
+    /// 有效方式
+    pub time_in_force: TimeInForce,
+    /// 触发价格, 仅止损/止盈单
+    pub stop_price: Decimal,
+    /// 触发价格类型, 仅条件单
+    pub working_type: WorkingType,
+    /// 跟踪止损回调比例, 如0.01表示1%, 仅跟踪止损单
+    pub callback_rate: Decimal,
+    /// 客户端订单ID, 同时作为订单ID, 未完成订单中不可重复. 为空时自动生成
+    pub client_id: Option<String>,
+}
+
+impl OrderRequest {
+    /// 市价单
+    pub fn market(symbol: &str, side: TradeSide, reduce_only: bool, size: Decimal) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            r#type: TradeType::Market,
+            side,
+            reduce_only,
+            close_position: false,
+            price: Decimal::ZERO,
+            size,
+            time_in_force: TimeInForce::Gtc,
+            stop_price: Decimal::ZERO,
+            working_type: WorkingType::Last,
+            callback_rate: Decimal::ZERO,
+            client_id: None,
+        }
+    }
+
+    /// 限价单
+    pub fn limit(
+        symbol: &str,
+        side: TradeSide,
+        reduce_only: bool,
+        size: Decimal,
+        price: Decimal,
+    ) -> Self {
+        Self {
+            r#type: TradeType::Limit,
+            price,
+            ..Self::market(symbol, side, reduce_only, size)
+        }
+    }
+
+    /// 止损市价单
+    pub fn stop_market(
+        symbol: &str,
+        side: TradeSide,
+        reduce_only: bool,
+        size: Decimal,
+        stop_price: Decimal,
+    ) -> Self {
+        Self {
+            r#type: TradeType::StopMarket,
+            stop_price,
+            ..Self::market(symbol, side, reduce_only, size)
+        }
+    }
+
+    /// 止损限价单
+    pub fn stop_limit(
+        symbol: &str,
+        side: TradeSide,
+        reduce_only: bool,
+        size: Decimal,
+        price: Decimal,
+        stop_price: Decimal,
+    ) -> Self {
+        Self {
+            r#type: TradeType::StopLimit,
+            stop_price,
+            ..Self::limit(symbol, side, reduce_only, size, price)
+        }
+    }
+
+    /// 止盈市价单
+    pub fn take_profit_market(
+        symbol: &str,
+        side: TradeSide,
+        reduce_only: bool,
+        size: Decimal,
+        stop_price: Decimal,
+    ) -> Self {
+        Self {
+            r#type: TradeType::TakeProfitMarket,
+            stop_price,
+            ..Self::market(symbol, side, reduce_only, size)
+        }
+    }
+
+    /// 止盈限价单
+    pub fn take_profit_limit(
+        symbol: &str,
+        side: TradeSide,
+        reduce_only: bool,
+        size: Decimal,
+        price: Decimal,
+        stop_price: Decimal,
+    ) -> Self {
+        Self {
+            r#type: TradeType::TakeProfitLimit,
+            stop_price,
+            ..Self::limit(symbol, side, reduce_only, size, price)
+        }
+    }
+
+    /// 跟踪止损单
+    pub fn trailing_stop_market(
+        symbol: &str,
+        side: TradeSide,
+        reduce_only: bool,
+        size: Decimal,
+        callback_rate: Decimal,
+    ) -> Self {
+        Self {
+            r#type: TradeType::TrailingStopMarket,
+            callback_rate,
+            ..Self::market(symbol, side, reduce_only, size)
+        }
+    }
+
+    /// 是否为买入: 做多开仓或做空平仓
+    pub fn is_buy(&self) -> bool {
+        match self.side {
+            TradeSide::Long => !self.reduce_only,
+            TradeSide::Short => self.reduce_only,
+        }
+    }
+
+    pub fn with_working_type(mut self, working_type: WorkingType) -> Self {
+        self.working_type = working_type;
+        self
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn with_client_id(mut self, client_id: &str) -> Self {
+        self.client_id = Some(client_id.to_string());
+        self
+    }
+
+    pub fn with_close_position(mut self) -> Self {
+        self.close_position = true;
+        self.reduce_only = true;
+        self
+    }
+}
+
+#[cfg(feature = "python")]
+#[pyo3::pymethods]
+impl OrderRequest {
+    #[new]
+    #[pyo3(signature = (symbol, r#type, side, size, price=Decimal::ZERO, reduce_only=false, close_position=false, time_in_force=TimeInForce::Gtc, client_id=None, stop_price=Decimal::ZERO, working_type=WorkingType::Last, callback_rate=Decimal::ZERO))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        symbol: String,
+        r#type: TradeType,
+        side: TradeSide,
+        size: Decimal,
+        price: Decimal,
+        reduce_only: bool,
+        close_position: bool,
+        time_in_force: TimeInForce,
+        client_id: Option<String>,
+        stop_price: Decimal,
+        working_type: WorkingType,
+        callback_rate: Decimal,
+    ) -> Self {
+        Self {
+            symbol,
+            r#type,
+            side,
+            reduce_only,
+            close_position,
+            price,
+            size,
+            time_in_force,
+            stop_price,
+            working_type,
+            callback_rate,
+            client_id,
+        }
+    }
 }
 
 impl Order {
+    /// 是否为立即成交类订单(IOC/FOK), 不会持续挂单
+    pub fn is_immediate(&self) -> bool {
+        matches!(self.time_in_force, TimeInForce::Ioc | TimeInForce::Fok)
+    }
+
+    /// 是否为买入: 做多开仓或做空平仓
+    pub fn is_buy(&self) -> bool {
+        match self.side {
+            TradeSide::Long => !self.reduce_only,
+            TradeSide::Short => self.reduce_only,
+        }
+    }
+
+    /// 是否为等待触发的条件单
+    /// <br> 未触发的条件单不参与撮合, 不占用保证金与可平仓数量
+    pub fn is_pending_trigger(&self) -> bool {
+        self.r#type.is_conditional() && self.status == OrderStatus::Created
+    }
+
+    /// 是否在价格向上到达触发价格时触发
+    /// <br> 买入止损与卖出止盈向上触发, 卖出止损与买入止盈向下触发
+    pub fn is_trigger_rising(&self) -> bool {
+        match self.r#type {
+            TradeType::TakeProfitMarket | TradeType::TakeProfitLimit => !self.is_buy(),
+            _ => self.is_buy(),
+        }
+    }
+
+    /// 价格[`price`]是否满足触发条件
+    pub fn is_triggered(&self, price: Decimal) -> bool {
+        if self.is_trigger_rising() {
+            price >= self.stop_price
+        } else {
+            price <= self.stop_price
+        }
+    }
+
+    /// 使用K线的最高价与最低价判断条件单是否触发
+    /// <br> 跟踪止损单未被已有触发价格触发时, 按K线极值与回调比例移动触发价格, 再以收盘价判断
+    pub fn trigger(&mut self, kline: &Kline) -> bool {
+        let rising = self.is_trigger_rising();
+        if self.is_triggered(if rising { kline.high } else { kline.low }) {
+            return true;
+        }
+        if self.r#type != TradeType::TrailingStopMarket {
+            return false;
+        }
+        self.stop_price = if rising {
+            self.stop_price
+                .min(kline.low * (Decimal::ONE + self.callback_rate))
+        } else {
+            self.stop_price
+                .max(kline.high * (Decimal::ONE - self.callback_rate))
+        };
+        self.is_triggered(kline.close)
+    }
+
     pub fn margin(&self, mark_price: Decimal, leverage: u32) -> Decimal {
-        if self.reduce_only {
+        if self.reduce_only || self.is_pending_trigger() {
             return Decimal::ZERO;
         }
         (self.size - self.deal_size)
-            * if self.r#type == TradeType::Limit {
+            * if self.r#type.is_limit() {
                 self.price
             } else {
                 mark_price
@@ -208,16 +594,41 @@ impl Order {
     }
 }
 
+/// 订单组
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all, frozen))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderGroup {
+    /// ID
+    pub id: String,
+    /// 交易对
+    pub symbol: String,
+    /// 订单组类型
+    pub r#type: OrderGroupType,
+    /// 入场订单ID, 仅括号订单
+    pub entry_id: Option<String>,
+    /// 互斥订单ID: 任意订单成交(含部分成交)或完成后撤销其余订单
+    pub order_ids: Vec<String>,
+    /// 未激活的止损/止盈订单, 入场订单首次成交后按成交数量激活
+    pub orders_inactive: Vec<Order>,
+    /// 创建时间
+    pub create_time: DateTime<Utc>,
+}
+
 /// 持仓
 #[cfg_attr(feature = "python", pyo3::pyclass(get_all, frozen))]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     /// 方向
     pub side: TradeSide,
     /// 持仓均价
+    #[serde(with = "serde_decimal")]
     pub price: Decimal,
     /// 持仓
+    #[serde(with = "serde_decimal")]
     pub size: Decimal,
+    /// 逐仓保证金, 仅在[`MarginMode::Isolated`]下生效
+    #[serde(with = "serde_decimal", default)]
+    pub margin_isolated: Decimal,
 }
 
 impl Position {
@@ -225,6 +636,31 @@ impl Position {
         self.size * self.price / Decimal::from(leverage)
     }
 
+    /// 加仓, 按加权平均更新持仓均价
+    pub fn open(&mut self, price: Decimal, size: Decimal) {
+        if size.is_zero() {
+            return;
+        }
+        self.price = (price * size + self.price * self.size) / (size + self.size);
+        self.size += size;
+    }
+
+    /// 减仓, 返回已实现盈亏
+    pub fn close(&mut self, price: Decimal, size: Decimal) -> Decimal {
+        if size.is_zero() {
+            return Decimal::ZERO;
+        }
+        let profit = match self.side {
+            TradeSide::Long => (price - self.price) * size,
+            TradeSide::Short => (self.price - price) * size,
+        };
+        self.size -= size;
+        if self.size.is_zero() {
+            self.price = Decimal::ZERO;
+        }
+        profit
+    }
+
     pub fn pnl(&self, mark_price: Decimal) -> Decimal {
         if self.size.is_zero() {
             return Decimal::ZERO;
@@ -236,6 +672,209 @@ impl Position {
                 TradeSide::Short => Decimal::NEGATIVE_ONE,
             }
     }
+
+    /// 维持保证金
+    pub fn maintenance(&self, mark_price: Decimal, rule: &SymbolRule) -> Decimal {
+        let notional = self.size * mark_price;
+        rule.margin_tier(notional)
+            .map(|tier| tier.maintenance(notional))
+            .unwrap_or_default()
+    }
+
+    /// 强平价格
+    /// <br> [`equity`] : 可用于承担该持仓亏损的权益, 不含该持仓自身的未实现盈亏与维持保证金
+    /// <br> 即满足 [`equity`] + 未实现盈亏 = 维持保证金 时的标记价格
+    pub fn liquidation_price(
+        &self,
+        equity: Decimal,
+        mark_price: Decimal,
+        rule: &SymbolRule,
+    ) -> Decimal {
+        if self.size.is_zero() {
+            return Decimal::ZERO;
+        }
+        let Some(tier) = rule.margin_tier(self.size * mark_price) else {
+            return Decimal::ZERO;
+        };
+        let price = match self.side {
+            TradeSide::Long => {
+                (self.size * self.price - equity - tier.maintenance_amount)
+                    / (self.size * (Decimal::ONE - tier.maintenance_rate))
+            }
+            TradeSide::Short => {
+                (equity + self.size * self.price + tier.maintenance_amount)
+                    / (self.size * (Decimal::ONE + tier.maintenance_rate))
+            }
+        };
+        price.max(Decimal::ZERO)
+    }
+}
+
+/// 强平
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all, frozen))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Liquidation {
+    /// 交易对
+    pub symbol: String,
+    /// 持仓方向
+    pub side: TradeSide,
+    /// 强平价格
+    #[serde(with = "serde_decimal")]
+    pub price: Decimal,
+    /// 强平数量
+    #[serde(with = "serde_decimal")]
+    pub size: Decimal,
+    /// 已实现盈亏
+    #[serde(with = "serde_decimal")]
+    pub pnl: Decimal,
+    /// 强平手续费
+    #[serde(with = "serde_decimal")]
+    pub fee: Decimal,
+    /// 强平时间
+    #[serde(with = "serde_chrono")]
+    pub time: DateTime<Utc>,
+}
+
+/// 持仓事件
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all, frozen))]
+#[derive(Debug, Clone)]
+pub struct PositionEvent {
+    /// 交易对
+    pub symbol: String,
+    /// 变动原因
+    pub reason: PositionReason,
+    /// 变动前持仓
+    pub before: Position,
+    /// 变动后持仓
+    pub after: Position,
+    /// 变动前持仓占用保证金, 逐仓模式下为该方向的逐仓保证金
+    pub margin_before: Decimal,
+    /// 变动后持仓占用保证金
+    pub margin_after: Decimal,
+    /// 变动时间
+    pub time: DateTime<Utc>,
+}
+
+/// 资金费用
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all, frozen))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Funding {
+    /// 交易对
+    pub symbol: String,
+    /// 持仓方向
+    pub side: TradeSide,
+    /// 资金费率
+    #[serde(with = "serde_decimal")]
+    pub rate: Decimal,
+    /// 标记价格
+    #[serde(with = "serde_decimal")]
+    pub mark_price: Decimal,
+    /// 持仓数量
+    #[serde(with = "serde_decimal")]
+    pub size: Decimal,
+    /// 资金费用, 正数为收入, 负数为支出
+    #[serde(with = "serde_decimal")]
+    pub fee: Decimal,
+    /// 结算时间
+    #[serde(with = "serde_chrono")]
+    pub time: DateTime<Utc>,
+}
+
+impl Funding {
+    /// 按标记价格计算持仓的资金费用
+    /// <br> 资金费率为正时多头支付空头, 为负时空头支付多头
+    pub fn new(
+        symbol: &str,
+        position: &Position,
+        rate: Decimal,
+        mark_price: Decimal,
+        time: DateTime<Utc>,
+    ) -> Self {
+        let fee = position.size
+            * mark_price
+            * rate
+            * match position.side {
+                TradeSide::Long => Decimal::NEGATIVE_ONE,
+                TradeSide::Short => Decimal::ONE,
+            };
+        Self {
+            symbol: symbol.to_string(),
+            side: position.side,
+            rate,
+            mark_price,
+            size: position.size,
+            fee,
+            time,
+        }
+    }
+}
+
+/// 成交记录
+#[cfg_attr(feature = "python", pyo3::pyclass(get_all, frozen))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    /// 交易对
+    pub symbol: String,
+    /// 订单ID
+    pub order_id: String,
+    /// 交易方向
+    pub side: TradeSide,
+    /// 只减仓
+    pub reduce_only: bool,
+    /// 成交价格
+    #[serde(with = "serde_decimal")]
+    pub price: Decimal,
+    /// 成交数量
+    #[serde(with = "serde_decimal")]
+    pub size: Decimal,
+    /// 手续费
+    #[serde(with = "serde_decimal")]
+    pub fee: Decimal,
+    /// 是否为挂单成交
+    pub is_maker: bool,
+    /// 已实现盈亏, 不含手续费
+    #[serde(with = "serde_decimal")]
+    pub pnl: Decimal,
+    /// 成交时间
+    #[serde(with = "serde_chrono")]
+    pub time: DateTime<Utc>,
+}
+
+/// 交易对账本
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymbolLedger {
+    /// 已完成订单(已成交或已取消)
+    pub orders: Vec<Order>,
+    /// 成交记录
+    pub trades: Vec<Trade>,
+    /// 资金费用
+    pub fundings: Vec<Funding>,
+    /// 强平记录
+    pub liquidations: Vec<Liquidation>,
+}
+
+impl SymbolLedger {
+    pub fn funding_fee(&self) -> Decimal {
+        self.fundings.iter().map(|funding| funding.fee).sum()
+    }
+
+    /// 创建时间在[`begin`, `end`)内的已完成订单
+    pub fn orders_between(&self, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Order> {
+        self.orders
+            .iter()
+            .filter(|order| begin <= order.create_time && order.create_time < end)
+            .cloned()
+            .collect()
+    }
+
+    /// 成交时间在[`begin`, `end`)内的成交记录
+    pub fn trades_between(&self, begin: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Trade> {
+        self.trades
+            .iter()
+            .filter(|trade| begin <= trade.time && trade.time < end)
+            .cloned()
+            .collect()
+    }
 }
 
 /// 持仓
@@ -245,22 +884,211 @@ pub struct SymbolPosition {
     pub symbol: Symbol,
     /// 杠杆倍率
     pub leverage: u32,
+    /// 持仓模式
+    pub position_mode: PositionMode,
+    /// 保证金模式
+    pub margin_mode: MarginMode,
     /// 做多持仓
     pub long: Position,
     /// 做空持仓
     pub short: Position,
     /// 订单
     pub orders: HashMap<String, Order>,
+    /// 订单组, 组内订单全部完成后移除
+    pub groups: HashMap<String, OrderGroup>,
 }
 
 impl SymbolPosition {
+    /// 无持仓、无订单的交易对持仓: 1倍杠杆, 双向持仓, 全仓
+    pub fn new(symbol: &str, rule: SymbolRule) -> Self {
+        Self {
+            symbol: Symbol {
+                symbol: symbol.to_string(),
+                enable: true,
+                rule,
+                market: SymbolMarket {
+                    mark: Decimal::ZERO,
+                    index: Decimal::ZERO,
+                    last: Decimal::ZERO,
+                    settlement: Decimal::ZERO,
+                    settlement_time: Default::default(),
+                    time: Default::default(),
+                },
+            },
+            leverage: 1,
+            position_mode: PositionMode::Hedge,
+            margin_mode: MarginMode::Cross,
+            long: Position {
+                side: TradeSide::Long,
+                price: Decimal::ZERO,
+                size: Decimal::ZERO,
+                margin_isolated: Decimal::ZERO,
+            },
+            short: Position {
+                side: TradeSide::Short,
+                price: Decimal::ZERO,
+                size: Decimal::ZERO,
+                margin_isolated: Decimal::ZERO,
+            },
+            orders: Default::default(),
+            groups: Default::default(),
+        }
+    }
+
+    /// 订单成交或完成后维护所属订单组, 返回被撤销的同组订单
+    /// <br> 括号订单的入场订单成交时, 激活止损与止盈订单并将其数量更新为入场订单的成交数量
+    /// <br> 入场订单未成交即完成时, 整个订单组失效
+    /// <br> 其余订单成交(含部分成交)或完成时, 撤销组内其余订单(含未完成的入场订单)
+    pub fn group_update(&mut self, order: &Order) -> Vec<Order> {
+        let Some(group_id) = order.group_id.as_ref() else {
+            return Vec::new();
+        };
+        let Some(group) = self.groups.get_mut(group_id) else {
+            return Vec::new();
+        };
+        let finished = !matches!(
+            order.status,
+            OrderStatus::Created | OrderStatus::Submitted | OrderStatus::PartialFilled
+        );
+
+        if group.entry_id.as_ref() == Some(&order.id) {
+            if !order.deal_size.is_zero() {
+                for order in group.orders_inactive.drain(..) {
+                    self.orders.insert(order.id.clone(), order);
+                }
+                for id in group.order_ids.iter() {
+                    if let Some(bracket) = self.orders.get_mut(id) {
+                        bracket.size = order.deal_size;
+                    }
+                }
+            } else if finished {
+                self.groups.remove(group_id);
+            }
+            return Vec::new();
+        }
+
+        if order.deal_size.is_zero() && !finished {
+            return Vec::new();
+        }
+        let group = self.groups.remove(group_id).unwrap();
+        group
+            .entry_id
+            .iter()
+            .chain(group.order_ids.iter())
+            .filter(|id| **id != order.id)
+            .filter_map(|id| self.orders.remove(id))
+            .map(|mut order| {
+                order.status = OrderStatus::Canceled;
+                order
+            })
+            .collect()
+    }
+
+    /// 移除订单并设置为完成状态, 返回该订单与被撤销的同组订单
+    pub fn order_finish(&mut self, id: &str, status: OrderStatus) -> Vec<Order> {
+        let Some(mut order) = self.orders.remove(id) else {
+            return Vec::new();
+        };
+        order.status = status;
+        let mut orders = self.group_update(&order);
+        orders.insert(0, order);
+        orders
+    }
+
+    /// 挂单占用保证金: 单向持仓模式下开仓单仅对超出反向持仓的部分占用保证金
     pub fn margin_orders(&self) -> Decimal {
         self.orders
             .par_iter()
-            .map(|(_, order)| order.margin(self.symbol.market.mark, self.leverage))
+            .map(|(_, order)| {
+                let margin = order.margin(self.symbol.market.mark, self.leverage);
+                let size = order.size - order.deal_size;
+                if order.reduce_only || size.is_zero() {
+                    return margin;
+                }
+                margin * self.size_to_open(order.side, size) / size
+            })
             .sum::<Decimal>()
     }
 
+    pub fn is_one_way(&self) -> bool {
+        self.position_mode == PositionMode::OneWay
+    }
+
+    pub fn position(&self, side: TradeSide) -> &Position {
+        match side {
+            TradeSide::Long => &self.long,
+            TradeSide::Short => &self.short,
+        }
+    }
+
+    pub fn position_mut(&mut self, side: TradeSide) -> &mut Position {
+        match side {
+            TradeSide::Long => &mut self.long,
+            TradeSide::Short => &mut self.short,
+        }
+    }
+
+    /// 持仓占用保证金, 逐仓模式下为该方向的逐仓保证金
+    pub fn position_margin(&self, side: TradeSide) -> Decimal {
+        if self.is_isolated() {
+            self.position(side).margin_isolated
+        } else {
+            self.position(side).margin(self.leverage)
+        }
+    }
+
+    /// 多空持仓及其占用保证金快照, 用于生成持仓事件
+    pub fn position_snapshot(&self) -> [(Position, Decimal); 2] {
+        [TradeSide::Long, TradeSide::Short]
+            .map(|side| (self.position(side).clone(), self.position_margin(side)))
+    }
+
+    /// 与快照相比发生变动的持仓事件
+    /// <br> 持仓数量、均价或占用保证金任一变化即视为变动, 变动前后均无持仓的方向不产生事件
+    pub fn position_events(
+        &self,
+        snapshot: [(Position, Decimal); 2],
+        reason: PositionReason,
+        time: DateTime<Utc>,
+    ) -> Vec<PositionEvent> {
+        snapshot
+            .into_iter()
+            .filter_map(|(before, margin_before)| {
+                let after = self.position(before.side);
+                let margin_after = self.position_margin(before.side);
+                if (before.size.is_zero() && after.size.is_zero())
+                    || before.size == after.size
+                        && before.price == after.price
+                        && margin_before == margin_after
+                {
+                    return None;
+                }
+                Some(PositionEvent {
+                    symbol: self.symbol.symbol.clone(),
+                    reason,
+                    before,
+                    after: after.clone(),
+                    margin_before,
+                    margin_after,
+                    time,
+                })
+            })
+            .collect()
+    }
+
+    /// 开仓单实际新增的持仓数量
+    /// <br> 单向持仓模式下先抵消反向持仓, 超出部分才会开仓
+    pub fn size_to_open(&self, side: TradeSide, size: Decimal) -> Decimal {
+        if !self.is_one_way() {
+            return size;
+        }
+        let opposite = match side {
+            TradeSide::Long => self.short.size,
+            TradeSide::Short => self.long.size,
+        };
+        (size - opposite).max(Decimal::ZERO)
+    }
+
     pub fn margin_long(&self) -> Decimal {
         self.long.margin(self.leverage)
     }
@@ -273,8 +1101,119 @@ impl SymbolPosition {
         self.margin_long() + self.margin_short()
     }
 
+    /// 逐仓保证金: 多空两个方向之和
+    pub fn margin_isolated(&self) -> Decimal {
+        self.long.margin_isolated + self.short.margin_isolated
+    }
+
+    /// 占用保证金: 逐仓模式下持仓占用为逐仓保证金
     pub fn margin(&self) -> Decimal {
-        self.margin_orders() + self.margin_positions()
+        if self.is_isolated() {
+            self.margin_orders() + self.margin_isolated()
+        } else {
+            self.margin_orders() + self.margin_positions()
+        }
+    }
+
+    pub fn is_isolated(&self) -> bool {
+        self.margin_mode == MarginMode::Isolated
+    }
+
+    /// 该方向的逐仓持仓是否触发强平: 该方向的逐仓保证金 + 未实现盈亏低于维持保证金
+    pub fn is_liquidatable_isolated(&self, side: TradeSide) -> bool {
+        let position = self.position(side);
+        let mark_price = self.symbol.market.mark;
+        let maintenance = position.maintenance(mark_price, &self.symbol.rule);
+        self.is_isolated()
+            && maintenance > Decimal::ZERO
+            && position.margin_isolated + position.pnl(mark_price) < maintenance
+    }
+
+    /// 成交后调整该方向的逐仓保证金
+    /// <br> 开仓按成交金额 / 杠杆倍率追加, 减仓按减仓数量占该方向持仓的比例释放
+    /// <br> 需在更新持仓数量之前调用
+    fn margin_isolated_fill(
+        &mut self,
+        side: TradeSide,
+        reduce_only: bool,
+        price: Decimal,
+        size: Decimal,
+    ) {
+        if !self.is_isolated() || size.is_zero() {
+            return;
+        }
+        let leverage = Decimal::from(self.leverage);
+        let position = self.position_mut(side);
+        if reduce_only {
+            if position.size <= size {
+                position.margin_isolated = Decimal::ZERO;
+            } else {
+                position.margin_isolated -= position.margin_isolated * size / position.size;
+            }
+        } else {
+            position.margin_isolated += price * size / leverage;
+        }
+    }
+
+    /// 拆分成交数量: (减仓方向, 减仓数量, 开仓数量)
+    /// <br> 单向持仓模式下开仓单先抵消反向持仓, 超出部分开仓
+    /// <br> 只减仓单的成交数量不超过当前持仓
+    fn fill_split(&self, order: &Order, size: Decimal) -> (TradeSide, Decimal, Decimal) {
+        if order.reduce_only {
+            let size = size.min(self.position(order.side).size);
+            return (order.side, size, Decimal::ZERO);
+        }
+        if !self.is_one_way() {
+            return (order.side, Decimal::ZERO, size);
+        }
+        let opposite = match order.side {
+            TradeSide::Long => TradeSide::Short,
+            TradeSide::Short => TradeSide::Long,
+        };
+        let reduce_size = size.min(self.position(opposite).size);
+        (opposite, reduce_size, size - reduce_size)
+    }
+
+    /// 订单本次可成交的数量
+    pub fn fill_size(&self, order: &Order, size: Decimal) -> Decimal {
+        let (_, reduce_size, open_size) = self.fill_split(order, size);
+        reduce_size + open_size
+    }
+
+    /// 订单成交, 更新持仓、逐仓保证金与订单成交信息, 返回已实现盈亏(不含手续费)
+    /// <br> [`price`] : 成交价格, 开仓按成交价格加权平均更新持仓均价
+    /// <br> [`size`] : 成交数量, 只减仓单超出当前持仓的部分不成交
+    /// <br> [`fee`] : 手续费, 仅记录在订单上, 由调用方从资金中扣除
+    pub fn fill(
+        &mut self,
+        order: &mut Order,
+        price: Decimal,
+        size: Decimal,
+        fee: Decimal,
+    ) -> Decimal {
+        let (reduce_side, reduce_size, open_size) = self.fill_split(order, size);
+        let size = reduce_size + open_size;
+        if size.is_zero() {
+            return Decimal::ZERO;
+        }
+
+        self.margin_isolated_fill(reduce_side, true, price, reduce_size);
+        let pnl = self.position_mut(reduce_side).close(price, reduce_size);
+        self.margin_isolated_fill(order.side, false, price, open_size);
+        self.position_mut(order.side).open(price, open_size);
+
+        order.deal_price =
+            (order.deal_price * order.deal_size + price * size) / (order.deal_size + size);
+        order.deal_size += size;
+        order.deal_fee += fee;
+        order.deal_pnl += pnl;
+        order.status = if order.deal_size >= order.size {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartialFilled
+        };
+
+        pnl
     }
 
     pub fn long_pnl(&self) -> Decimal {
@@ -289,11 +1228,65 @@ impl SymbolPosition {
         self.long_pnl() + self.short_pnl()
     }
 
+    pub fn maintenance_long(&self) -> Decimal {
+        self.long
+            .maintenance(self.symbol.market.mark, &self.symbol.rule)
+    }
+
+    pub fn maintenance_short(&self) -> Decimal {
+        self.short
+            .maintenance(self.symbol.market.mark, &self.symbol.rule)
+    }
+
+    pub fn maintenance(&self) -> Decimal {
+        self.maintenance_long() + self.maintenance_short()
+    }
+
+    /// 强平价格
+    /// <br> [`equity`] : 可用于承担该交易对亏损的权益, 不含该交易对的未实现盈亏与维持保证金
+    /// <br> 多空双向持仓时两侧盈亏同时随标记价格变动, 按交易对整体求解
+    pub fn liquidation_price(&self, equity: Decimal) -> Decimal {
+        let mark_price = self.symbol.market.mark;
+        let rule = &self.symbol.rule;
+        if self.short.size.is_zero() {
+            return self.long.liquidation_price(equity, mark_price, rule);
+        }
+        if self.long.size.is_zero() {
+            return self.short.liquidation_price(equity, mark_price, rule);
+        }
+
+        let (Some(tier_long), Some(tier_short)) = (
+            rule.margin_tier(self.long.size * mark_price),
+            rule.margin_tier(self.short.size * mark_price),
+        ) else {
+            return Decimal::ZERO;
+        };
+        let numerator = equity - self.long.size * self.long.price
+            + self.short.size * self.short.price
+            + tier_long.maintenance_amount
+            + tier_short.maintenance_amount;
+        let denominator = self.long.size * tier_long.maintenance_rate
+            + self.short.size * tier_short.maintenance_rate
+            - self.long.size
+            + self.short.size;
+        if denominator.is_zero() {
+            return Decimal::ZERO;
+        }
+        (numerator / denominator).max(Decimal::ZERO)
+    }
+
+    /// 做多冻结数量: 做多平仓挂单
+    /// <br> 单向持仓模式下做空开仓挂单同样会抵消做多持仓, 一并计入
     pub fn long_size_frozen(&self) -> Decimal {
+        let one_way = self.is_one_way();
         self.orders
             .par_iter()
             .filter_map(|(_, order)| {
-                if order.side == TradeSide::Long && order.reduce_only {
+                if order.is_pending_trigger() {
+                    None
+                } else if (order.side == TradeSide::Long && order.reduce_only)
+                    || (one_way && order.side == TradeSide::Short && !order.reduce_only)
+                {
                     Some(order)
                 } else {
                     None
@@ -304,14 +1297,21 @@ impl SymbolPosition {
     }
 
     pub fn long_size_available(&self) -> Decimal {
-        self.long.size - self.long_size_frozen()
+        (self.long.size - self.long_size_frozen()).max(Decimal::ZERO)
     }
 
+    /// 做空冻结数量: 做空平仓挂单
+    /// <br> 单向持仓模式下做多开仓挂单同样会抵消做空持仓, 一并计入
     pub fn short_size_frozen(&self) -> Decimal {
+        let one_way = self.is_one_way();
         self.orders
             .par_iter()
             .filter_map(|(_, order)| {
-                if order.side == TradeSide::Short && order.reduce_only {
+                if order.is_pending_trigger() {
+                    None
+                } else if (order.side == TradeSide::Short && order.reduce_only)
+                    || (one_way && order.side == TradeSide::Long && !order.reduce_only)
+                {
                     Some(order)
                 } else {
                     None
@@ -322,7 +1322,7 @@ impl SymbolPosition {
     }
 
     pub fn short_size_available(&self) -> Decimal {
-        self.short.size - self.short_size_frozen()
+        (self.short.size - self.short_size_frozen()).max(Decimal::ZERO)
     }
 }
 
@@ -354,7 +1354,81 @@ impl Account {
         self.margin()
     }
 
+    /// 全仓未实现盈亏
+    pub fn pnl_cross(&self) -> Decimal {
+        self.positions
+            .par_iter()
+            .filter(|(_, pos)| !pos.is_isolated())
+            .map(|(_, pos)| pos.pnl())
+            .sum::<Decimal>()
+    }
+
+    /// 逐仓保证金合计
+    pub fn margin_isolated(&self) -> Decimal {
+        self.positions
+            .par_iter()
+            .filter(|(_, pos)| pos.is_isolated())
+            .map(|(_, pos)| pos.margin_isolated())
+            .sum::<Decimal>()
+    }
+
+    /// 可用资金: 逐仓交易对的未实现盈亏不计入
     pub fn cash_available(&self) -> Decimal {
-        self.cash - self.cash_frozen() + self.pnl()
+        self.cash - self.cash_frozen() + self.pnl_cross()
+    }
+
+    /// 权益: 资金 + 未实现盈亏
+    pub fn equity(&self) -> Decimal {
+        self.cash + self.pnl()
+    }
+
+    /// 全仓权益: 资金 - 逐仓保证金 + 全仓未实现盈亏
+    pub fn equity_cross(&self) -> Decimal {
+        self.cash - self.margin_isolated() + self.pnl_cross()
+    }
+
+    /// 维持保证金
+    pub fn maintenance(&self) -> Decimal {
+        self.positions
+            .par_iter()
+            .map(|(_, pos)| pos.maintenance())
+            .sum::<Decimal>()
+    }
+
+    /// 全仓维持保证金
+    pub fn maintenance_cross(&self) -> Decimal {
+        self.positions
+            .par_iter()
+            .filter(|(_, pos)| !pos.is_isolated())
+            .map(|(_, pos)| pos.maintenance())
+            .sum::<Decimal>()
+    }
+
+    /// 全仓是否触发强平: 全仓权益低于全仓维持保证金
+    pub fn is_liquidatable(&self) -> bool {
+        let maintenance = self.maintenance_cross();
+        maintenance > Decimal::ZERO && self.equity_cross() < maintenance
+    }
+
+    /// 强平价格
+    pub fn liquidation_price(&self, symbol: &str, side: TradeSide) -> Decimal {
+        let Some(symbol_position) = self.positions.get(symbol) else {
+            return Decimal::ZERO;
+        };
+        let position = symbol_position.position(side);
+        if position.size.is_zero() {
+            return Decimal::ZERO;
+        }
+        // 逐仓的多空两个方向各自以该方向的逐仓保证金承担亏损
+        if symbol_position.is_isolated() {
+            return position.liquidation_price(
+                position.margin_isolated,
+                symbol_position.symbol.market.mark,
+                &symbol_position.symbol.rule,
+            );
+        }
+        let equity = self.equity_cross() - symbol_position.pnl() + symbol_position.maintenance()
+            - self.maintenance_cross();
+        symbol_position.liquidation_price(equity)
     }
 }